@@ -1,10 +1,47 @@
-use std::{io, option::Option};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fs, io,
+    option::Option,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::{self, Column, Config, FieldFormat, PasteMode, StorageBackend},
+    encrypt::{self, PasswordField},
+    export,
+    history::HistoryTextField,
+    import::{self, ImportFormat, MergeStrategy, WizardStep},
+    integrity::IntegrityError,
+    keymap::{self, Action, Keymap, ModeKey},
+    merge::TaskUpdate,
+    query::Query,
+    splash::{self, Loading},
+    state::State,
+    status_bar::StatusBarSegment,
+    sync::{FileWatcher, wait_for_change},
+    tags::{TagEdit, TagReport},
+    theme::Theme,
+    todotxt::DEFAULT_TODOTXT_PATH,
+    undo::UndoAction,
+};
+
+use crossterm::{
+    cursor::SetCursorStyle,
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+        EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent, KeyEventKind,
+        KeyboardEnhancementFlags, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    terminal::SetTitle,
+};
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use futures_util::StreamExt;
 
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
+    layout::{Position, Rect},
     style::{
         Color,
         palette::tailwind::GREEN,
@@ -17,7 +54,35 @@ use ratatui::{
     DefaultTerminal, Frame,
 };
 
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
 const COMPLETED_TEXT_FG_COLOR: Color = GREEN.c300;
+pub const MAX_TITLE_LEN: usize = 80;
+pub const DUE_DATE_FORMAT: &str = "%Y-%m-%d";
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(3);
+/// Commands that change tasks, turned away in read-only mode
+const CHANGING_COMMANDS: &[&str] = &[
+    "import",
+    "decrypt",
+    "move",
+    "swap-groups",
+    "clean",
+    "move-to",
+    "depends",
+    "parent",
+    "start",
+    "bulk-status",
+    "bulk-due",
+    "bulk-tag",
+];
+/// How long the task picked by `w` stays highlighted
+const NUDGE_DURATION: Duration = Duration::from_secs(3);
+/// Days the forecast shows, today included, after its past due column
+pub const FORECAST_DAYS: usize = 7;
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
 pub struct App {
     exit: bool,
@@ -25,237 +90,3301 @@ pub struct App {
     mode: Mode,
     pub currently_editing: CurrentlyEditing,
     editing_existing_item: Index,
-    pub title_field: String,
-    pub info_field: String,
+    pub title_field: HistoryTextField,
+    pub info_field: HistoryTextField,
+    pub url_field: HistoryTextField,
+    pub due_date_field: HistoryTextField,
+    pub command_field: HistoryTextField,
+    pub inline_title_field: HistoryTextField,
+    pub search_field: HistoryTextField,
+    pub quick_add_field: HistoryTextField,
+    pub undo_stack: Vec<UndoAction>,
+    pub toast: Option<String>,
+    pub inspector_scroll: u16,
+    /// Errors that were only shown in a toast, oldest first, for `:logs`
+    pub log_buffer: VecDeque<String>,
+    pub log_scroll: u16,
+    pub layout_mode: LayoutMode,
+    pub detail_tab: DetailTab,
+    /// When each task's status was changed this session, from what to what, oldest first
+    pub status_changes: HashMap<Uuid, Vec<(DateTime<Local>, Status, Status)>>,
+    /// Show how the task being edited would export, next to the form
+    pub show_preview: bool,
+    /// Show the list as one section per tag instead of in order
+    pub group_by_tag: bool,
+    /// Tasks can be looked through, searched and exported but not changed, set by
+    /// `--read-only` or `:lock`
+    pub read_only: bool,
+    pub integrity_errors: Vec<IntegrityError>,
+    pub changed_since_last_session: Vec<String>,
+    /// The tasks file, while it's still being read on another thread
+    loading: Option<Loading>,
+    /// Where the minimap grid was last drawn and how many rows it was scrolled by, so clicks
+    /// can be mapped back to tasks
+    pub minimap_grid: Rect,
+    pub minimap_scroll: usize,
+    /// How many day columns the timeline last had room for
+    pub timeline_days: usize,
+    /// How many days the Gantt chart last had room for
+    pub gantt_days: usize,
+    /// The theme highlighted in the theme picker, drawn with instead of the configured one
+    /// until it's picked or the picker is closed
+    pub preview_theme: Option<Theme>,
+    mouse_captured: bool,
+    /// What each key does in the modes that don't handle keys themselves
+    keymap: Keymap,
+    /// Whether the terminal cursor has been switched to a blinking bar for typing
+    cursor_bar: bool,
+    /// Show the state overlay that Ctrl+D toggles, in debug builds
+    #[cfg(debug_assertions)]
+    pub debug_overlay: bool,
+    /// How long the last frame took to render, for the debug overlay
+    #[cfg(debug_assertions)]
+    pub last_render: Duration,
+    /// Where the focused edit form field's cursor was drawn, for the terminal cursor
+    pub edit_cursor: Option<Position>,
+    /// The terminal title last set, so it's only written when it changes
+    last_window_title: String,
+    /// The project the split view showed last, to open again with `\`
+    last_split_project: Option<String>,
+    /// Started with `--quick`, so quit as soon as the edit form is done with
+    quick_entry: bool,
+    /// Tasks whose notes are in files that haven't been read yet, see `load_notes`
+    pub notes_unread: HashSet<Uuid>,
+    /// Watching the tasks file for other instances' changes, with `sync_mode` on
+    pub watcher: Option<FileWatcher>,
+    /// A hash of the tasks file as this instance last loaded or saved it, and when
+    pub synced_hash: Option<u64>,
+    pub synced_at: DateTime<Utc>,
+    /// The active task `w` picked at random and when to stop highlighting it
+    nudge: Option<(Uuid, Instant)>,
+    dirty: bool,
+    last_save: Instant,
+    last_tick: Instant,
+    spinner_state: usize,
+    /// How many tasks the list fit when it was last drawn, what Page Down and Page Up move by
+    pub page_size: usize,
+    config: Config,
+    /// `config.status_bar_format`, parsed once when the config is loaded
+    pub status_bar: Vec<StatusBarSegment>,
+    config_path: PathBuf,
+    /// The recent files and the file last switched to, saved to `state_path`
+    state: State,
+    state_path: PathBuf,
+    storage: StorageBackend,
 }
 
 pub struct TodoList {
     pub items: Vec<Task>,
     pub state: ListState,
+    /// When this list was last written to disk, if it was loaded from a file that recorded it
+    pub last_opened: Option<DateTime<Utc>>,
+    /// Status for new tasks in this list, overriding `Config::default_status`. Lets a project
+    /// file start its tasks as active, say
+    pub default_status: Option<Status>,
+    /// Tasks whose subtasks are hidden from `as_flat_with_subtasks`
+    pub collapsed: HashSet<Uuid>,
+    /// Where each task is in `items`, kept up to date by the methods that add, remove and
+    /// move tasks. Code that rearranges `items` directly should call `rebuild_index` after
+    index: HashMap<Uuid, usize>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
+    pub id: Uuid,
+    pub depends_on: Option<Uuid>,
+    /// The task this is a subtask of. Unlike `depends_on` it doesn't block anything, so
+    /// subtasks can be finished before the task they belong to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Uuid>,
     pub title: String,
     pub info: String,
+    pub url: Option<String>,
+    /// When work on it is meant to begin, for the Gantt chart
+    pub start_date: Option<NaiveDate>,
+    pub due_date: Option<NaiveDate>,
+    /// `None` for tasks saved before modification times were tracked
+    pub last_modified: Option<DateTime<Utc>>,
+    #[serde(rename = "status")]
     pub mode: Status,
+    /// Kept out of the list, in the archive view, without being deleted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_archived: bool,
 }
 
 struct Index {
     index: Option<usize>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Status {
     Upcoming,
     Active,
     Completed,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CurrentlyEditing {
     Title,
+    Url,
+    DueDate,
     Info,
 }
 
 enum Mode {
+    /// Waiting on the tasks from `App::loading`
+    Splash,
     View,
     Edit,
     Help,
+    TaskDetail,
+    Command,
+    Welcome,
+    Inspector,
+    /// The selected task's JSON, being edited in place of the inspector. `error` is why
+    /// the last attempt to save it didn't parse
+    JsonEditor {
+        field: HistoryTextField,
+        error: Option<String>,
+    },
+    Logs,
+    IntegrityReport,
+    WelcomeBack,
+    Focus,
+    InlineEdit,
+    Search,
+    QuickAdd,
+    About,
+    /// A message shown over `previous_mode` until it expires or a key is pressed. Notifying
+    /// again while one is up stacks the new one on top
+    Notification {
+        message: String,
+        expires_at: Instant,
+        previous_mode: Box<Mode>,
+    },
+    /// Choosing which columns the list shows. Holds every column, the shown ones first in
+    /// their order, so hidden ones can be switched back on
+    ColumnEditor {
+        columns: Vec<(Column, bool)>,
+        selected: usize,
+    },
+    /// A grid with a cell per task, for getting around long lists
+    Minimap { cursor: usize },
+    /// Tasks with dates as bars across a column per day, starting `offset` days after the
+    /// earliest date. `selected` indexes into `TodoList::gantt_rows`
+    Gantt { offset: usize, selected: usize },
+    /// Tasks with due dates in a column per day, starting `offset` days from today.
+    /// `selected` indexes into `App::timeline`
+    Timeline { offset: usize, selected: usize },
+    /// The coming week in a column per day after a past due one. `column` indexes into
+    /// `App::forecast` and `row` into that column
+    Forecast { column: usize, row: usize },
+    /// The tasks next to a project, to move tasks between them
+    Split {
+        name: String,
+        path: PathBuf,
+        project: TodoList,
+        focus_right: bool,
+    },
+    /// Picking a tasks file from `State::recent_files` to switch to
+    RecentFiles { selected: usize },
+    /// Picking a tasks file from `Config::task_files` to switch to
+    ListPicker { selected: usize },
+    /// Trying out themes, `selected` indexing into `Theme::ALL`
+    ThemePicker { selected: usize },
+    /// The archived tasks, `selected` indexing into `TodoList::archived`
+    Archive { selected: usize },
+    /// Every tag with its task count. `selected` indexes into `TodoList::tag_counts`, and
+    /// `edit` holds the new name while renaming or merging the selected tag
+    TagManager {
+        selected: usize,
+        edit: Option<(TagEdit, String)>,
+    },
+    /// Asking for the passphrase to encrypt the tasks to `path` with, or to decrypt `path`
+    /// with and import from
+    Encrypt {
+        path: PathBuf,
+        decrypt: bool,
+        passphrase: PasswordField,
+    },
+    /// Choosing what to import and how, a step at a time
+    ImportWizard(WizardStep),
+    /// Waiting for the user to confirm an import
+    DiffImport {
+        new_tasks: Vec<Task>,
+        updated_tasks: Vec<TaskUpdate>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    Vertical,
+    Horizontal,
+}
+
+/// What the pane under or beside the list shows about the selected task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailTab {
+    Overview,
+    Notes,
+    /// Status changes made this session
+    History,
+    Metadata,
+}
+
+impl DetailTab {
+    pub const ALL: [DetailTab; 4] =
+        [DetailTab::Overview, DetailTab::Notes, DetailTab::History, DetailTab::Metadata];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DetailTab::Overview => "Overview",
+            DetailTab::Notes => "Notes",
+            DetailTab::History => "History",
+            DetailTab::Metadata => "Metadata",
+        }
+    }
+
+    fn next(self) -> DetailTab {
+        let i = DetailTab::ALL.iter().position(|&tab| tab == self).unwrap_or(0);
+        DetailTab::ALL[(i + 1) % DetailTab::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleError {
+    TooLong,
+    Blank,
 }
 
 impl App {
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+    pub fn new(config_path: PathBuf, state_path: PathBuf, tasks_path: PathBuf) -> io::Result<App> {
+        if !config_path.try_exists()? {
+            Config::write_default(&config_path)?;
+        }
+        let config = Config::load(&config_path)?;
+        let state = State::load(&state_path);
+        let storage = state.launch_storage(&config, tasks_path);
+        let tasks_exist = storage.exists()?;
+        let mut loading = Loading::start(storage.clone());
+        let loaded = loading.poll(splash::SPLASH_DELAY);
+
+        let mut app = App {
+            mode: Mode::Splash,
+            status_bar: StatusBarSegment::parse(&config.status_bar_format),
+            config,
+            config_path,
+            state,
+            state_path,
+            storage,
+            ..App::default()
+        };
+        app.state.remember_file(app.storage.path());
+        // Only the recent files are lost if this fails, not worth refusing to start over
+        if let Err(error) = app.state.save(&app.state_path) {
+            app.toast = Some(app.log_error(format!("Couldn't remember this file: {error}")));
+        }
+        match loaded {
+            Some(list) => app.finish_loading(list?, tasks_exist),
+            None => app.loading = Some(loading),
+        }
+        Ok(app)
+    }
+
+    fn finish_loading(&mut self, list: TodoList, tasks_exist: bool) {
+        self.changed_since_last_session = match list.last_opened {
+            Some(last_opened) => list
+                .items
+                .iter()
+                .filter(|task| task.last_modified.is_some_and(|modified| modified > last_opened))
+                .map(|task| task.title.clone())
+                .collect(),
+            None => vec![],
+        };
+        self.mode = if self.config.first_launch && !tasks_exist {
+            Mode::Welcome
+        } else if !self.changed_since_last_session.is_empty() {
+            Mode::WelcomeBack
+        } else {
+            Mode::View
+        };
+        self.list = list;
+        self.mark_notes_unread();
+        self.mark_synced();
+    }
+
+    /// Picks up the tasks once the thread loading them is done, blocking until then when
+    /// `block` is set
+    pub fn poll_loading(&mut self, block: bool) -> io::Result<()> {
+        let Some(mut loading) = self.loading.take() else {
+            return Ok(());
+        };
+        let loaded = if block {
+            loading.wait()
+        } else {
+            match loading.poll(Duration::ZERO) {
+                Some(loaded) => loaded,
+                None => {
+                    self.loading = Some(loading);
+                    return Ok(());
+                }
+            }
+        };
+        // Only a file that's there takes long enough to load for the splash screen
+        self.finish_loading(loaded?, true);
+        Ok(())
+    }
+
+    /// How far along loading the tasks is, and what it's doing, while the splash is up
+    pub fn splash(&self) -> Option<(f64, &str)> {
+        self.loading.as_ref().map(|loading| (loading.ratio, loading.message.as_str()))
+    }
+
+    pub async fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        // xterm's "push title", so the title from before can be popped back on exit
+        crossterm::execute!(
+            io::stdout(),
+            crossterm::style::Print("\x1b[22;0t"),
+            EnableBracketedPaste
+        )?;
+        // Without it Ctrl+Enter arrives as a plain Enter, and Ctrl+I as Tab
+        let enhanced_keys = crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if enhanced_keys {
+            crossterm::execute!(
+                io::stdout(),
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            )?;
+        }
         terminal.draw(|frame| self.draw(frame))?;
 
+        let mut events = EventStream::new();
         while !self.exit {
+            self.poll_loading(false)?;
+            self.sync_from_disk();
+            self.update_window_title()?;
+            self.sync_cursor_style()?;
             terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events()?;
+
+            // Whichever comes first of input, another instance saving, the next tick and the
+            // next auto-save
+            let timeout = self.poll_timeout();
+            tokio::select! {
+                event = events.next() => match event {
+                    Some(event) => self.handle_event(event?, terminal)?,
+                    None => self.exit = true,
+                },
+                () = wait_for_change(&mut self.watcher) => {}
+                () = tokio::time::sleep(timeout) => {}
+            }
+            if self.last_tick.elapsed() >= self.config.tick_rate {
+                self.on_tick();
+            }
+            self.auto_save();
+        }
+
+        // Terminals without a title stack at least don't keep showing a stale count
+        crossterm::execute!(
+            io::stdout(),
+            SetTitle(""),
+            crossterm::style::Print("\x1b[23;0t"),
+            DisableBracketedPaste
+        )?;
+        if enhanced_keys {
+            crossterm::execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
+        }
+        if self.cursor_bar {
+            crossterm::execute!(io::stdout(), SetCursorStyle::DefaultUserShape)?;
+        }
+        self.save()
+    }
+
+    fn window_title(&self) -> String {
+        let active = self.list.items.iter().filter(|task| task.mode == Status::Active).count();
+        format!("Ratatodo ({} tasks, {active} active)", self.list.items.len())
+    }
+
+    fn update_window_title(&mut self) -> io::Result<()> {
+        let title = self.window_title();
+        if title != self.last_window_title {
+            crossterm::execute!(io::stdout(), SetTitle(&title))?;
+            self.last_window_title = title;
+        }
+        Ok(())
+    }
+
+    /// Waits until the next tick, or the next auto-save if that comes first
+    fn poll_timeout(&self) -> Duration {
+        let tick = self.config.tick_rate.saturating_sub(self.last_tick.elapsed());
+        if self.loading.is_some() {
+            // Often enough for the gauge to move smoothly and the list to show up promptly
+            return tick.min(Duration::from_millis(50));
+        }
+        match self.config.auto_save_interval {
+            Some(interval) if self.dirty => {
+                tick.min(interval.saturating_sub(self.last_save.elapsed()))
+            }
+            _ => tick,
+        }
+    }
+
+    fn on_tick(&mut self) {
+        self.last_tick = Instant::now();
+        self.spinner_state = self.spinner_state.wrapping_add(1);
+        self.expire_notifications();
+    }
+
+    pub fn notify(&mut self, message: &str, duration: Duration) {
+        let previous_mode = std::mem::replace(&mut self.mode, Mode::View);
+        self.mode = Mode::Notification {
+            message: message.into(),
+            expires_at: Instant::now() + duration,
+            previous_mode: Box::new(previous_mode),
+        };
+    }
+
+    /// The message of the notification on top, if one is showing
+    pub fn notification(&self) -> Option<&str> {
+        match &self.mode {
+            Mode::Notification { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Pops notifications off the stack until the top one hasn't expired yet
+    fn expire_notifications(&mut self) {
+        while let Mode::Notification { expires_at, .. } = &self.mode
+            && *expires_at <= Instant::now()
+        {
+            self.dismiss_notification();
+        }
+    }
+
+    fn dismiss_notification(&mut self) {
+        if let Mode::Notification { previous_mode, .. } =
+            std::mem::replace(&mut self.mode, Mode::View)
+        {
+            self.mode = *previous_mode;
+        }
+    }
+
+    fn auto_save(&mut self) {
+        let Some(interval) = self.config.auto_save_interval else {
+            return;
+        };
+
+        if !self.dirty {
+            // Nothing to write, so start the next interval from now
+            self.last_save = Instant::now();
+        } else if self.last_save.elapsed() >= interval
+            && let Err(error) = self.save()
+        {
+            self.toast = Some(self.log_error(format!("Auto-save failed: {error}")));
         }
+    }
+
+    pub fn spinner(&self) -> char {
+        SPINNER_FRAMES[self.spinner_state % SPINNER_FRAMES.len()]
+    }
 
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// How many ticks have passed, for animations slower than the spinner
+    pub fn ticks(&self) -> usize {
+        self.spinner_state
+    }
+
+    /// Whether there are changes that haven't been saved yet
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Whether there are changes that auto-save is going to write out
+    pub fn is_auto_save_pending(&self) -> bool {
+        self.is_dirty() && self.config.auto_save_interval.is_some()
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        // Nothing's been loaded to save, and saving would write over the file being read
+        if self.loading.is_some() {
+            return Ok(());
+        }
+        // Failed saves also restart the interval so a broken path isn't retried in a busy loop
+        self.last_save = Instant::now();
+        match self.list_for_saving()? {
+            Some(list) => self.storage.save(&list)?,
+            None => self.storage.save(&self.list)?,
+        }
+        self.dirty = false;
+        self.mark_synced();
         Ok(())
     }
 
     fn draw(&mut self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+        self.edit_cursor = None;
+        #[cfg(debug_assertions)]
+        let started = Instant::now();
+        frame.render_widget(&mut *self, frame.area());
+        self.theme().apply(frame.buffer_mut());
+        #[cfg(debug_assertions)]
+        {
+            self.last_render = started.elapsed();
+            if self.debug_overlay {
+                self.render_debug_overlay(frame.area(), frame.buffer_mut());
+            }
+        }
+        // ratatui hides the terminal cursor unless it's given somewhere to put it
+        if let Some(position) = self.edit_cursor {
+            frame.set_cursor_position(position);
+        }
+    }
+
+    /// Throws away what ratatui thinks is on screen and repaints every cell
+    fn force_redraw(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        terminal.clear()?;
+        terminal.draw(|frame| self.draw(frame))?;
+        Ok(())
     }
 
-    fn handle_events(&mut self) -> io::Result<()> {
-        match event::read()? {
+    fn handle_event(&mut self, event: Event, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        match event {
+            // Handled ahead of the per-mode bindings so it works everywhere, text fields included
+            Event::Key(key_event)
+                if key_event.kind == KeyEventKind::Press
+                    && key_event.code == KeyCode::Char('l')
+                    && key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                self.force_redraw(terminal)?
+            }
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_events(key_event)
+                self.handle_key_events(key_event);
+                self.sync_mouse_capture()?;
             }
+            Event::Paste(text) => self.handle_paste(&text),
+            Event::Mouse(mouse_event) => {
+                self.handle_minimap_mouse(mouse_event);
+                self.sync_mouse_capture()?;
+            }
+            // Some multiplexers leave stale cells behind on resize
+            Event::Resize(_, _) => self.force_redraw(terminal)?,
             _ => {}
         }
 
         Ok(())
     }
 
-    fn handle_key_events(&mut self, key_event: KeyEvent) {
+    pub fn handle_key_events(&mut self, key_event: KeyEvent) {
+        // Ahead of the keymap and the per-mode handlers, so it works everywhere
+        #[cfg(debug_assertions)]
+        if key_event.code == KeyCode::Char('d')
+            && key_event.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.debug_overlay = !self.debug_overlay;
+            return;
+        }
+        if key_event.code == KeyCode::Char('p') && key_event.modifiers.contains(KeyModifiers::ALT)
+        {
+            self.toggle_paste_mode();
+            return;
+        }
+        if matches!(self.mode, Mode::View) {
+            self.toast = None;
+        }
+        let (code, modifiers) = keymap::lookup_key(key_event.code, key_event.modifiers);
+        if let Some(mode) = self.mode_key() {
+            if let Some(&action) = self.keymap.get(&(mode, code, modifiers)) {
+                self.dispatch(action);
+            }
+            return;
+        }
+
         match self.mode {
-            Mode::View => self.handle_view_input(key_event),
             Mode::Edit => self.handle_edit_input(key_event),
-            Mode::Help => {
-                if key_event.code == KeyCode::Esc {
-                    self.mode = Mode::View
+            Mode::Command => self.handle_command_input(key_event),
+            Mode::Splash if matches!(key_event.code, KeyCode::Char('q') | KeyCode::Esc) => {
+                self.exit()
+            }
+            Mode::Splash => {}
+            Mode::Welcome => self.dismiss_welcome(),
+            Mode::WelcomeBack | Mode::About => self.mode = Mode::View,
+            Mode::InlineEdit => self.handle_inline_edit_input(key_event),
+            Mode::Search => self.handle_search_input(key_event),
+            Mode::QuickAdd => self.handle_quick_add_input(key_event),
+            Mode::DiffImport { .. } => self.handle_diff_import_input(key_event),
+            Mode::ImportWizard(_) => self.handle_import_wizard_input(key_event),
+            Mode::Encrypt { .. } => self.handle_encrypt_input(key_event),
+            Mode::RecentFiles { .. } => self.handle_recent_files_input(key_event),
+            Mode::ListPicker { .. } => self.handle_list_picker_input(key_event),
+            Mode::ThemePicker { .. } => self.handle_theme_picker_input(key_event),
+            Mode::Archive { .. } => self.handle_archive_input(key_event),
+            Mode::TagManager { .. } => self.handle_tag_manager_input(key_event),
+            Mode::JsonEditor { .. } => self.handle_json_editor_input(key_event),
+            Mode::Minimap { .. } => self.handle_minimap_input(key_event),
+            Mode::Timeline { .. } => self.handle_timeline_input(key_event),
+            Mode::Gantt { .. } => self.handle_gantt_input(key_event),
+            Mode::Forecast { .. } => self.handle_forecast_input(key_event),
+            Mode::Split { .. } => self.handle_split_input(key_event),
+            Mode::ColumnEditor { .. } => self.handle_column_editor_input(key_event),
+            Mode::Notification { .. } => self.dismiss_notification(),
+            Mode::View
+            | Mode::TaskDetail
+            | Mode::Help
+            | Mode::Focus
+            | Mode::Inspector
+            | Mode::Logs
+            | Mode::IntegrityReport => {}
+        }
+    }
+
+    /// Which part of the keymap the current mode's keys are in, if they're in it at all
+    fn mode_key(&self) -> Option<ModeKey> {
+        match self.mode {
+            Mode::View => Some(ModeKey::View),
+            Mode::TaskDetail => Some(ModeKey::TaskDetail),
+            Mode::Help => Some(ModeKey::Help),
+            Mode::Focus => Some(ModeKey::Focus),
+            Mode::Inspector => Some(ModeKey::Inspector),
+            Mode::Logs => Some(ModeKey::Logs),
+            Mode::IntegrityReport => Some(ModeKey::IntegrityReport),
+            _ => None,
+        }
+    }
+
+    fn dispatch(&mut self, action: Action) {
+        if action.changes_tasks() && self.refuse_read_only() {
+            return;
+        }
+        let selected = self.list.state.selected();
+        match action {
+            Action::Exit => self.exit(),
+            Action::Back => self.mode = Mode::View,
+            Action::NewTask => self.mode = Mode::Edit,
+            Action::EditTask => self.edit_task(),
+            Action::DeleteTask => self.delete_task(),
+            Action::ToggleStatus => self.toggle_mode(),
+            Action::PasteTask => self.paste_task(),
+            Action::DuplicateTask => self.duplicate_task(),
+            Action::Undo => self.toast = Some(self.undo()),
+            Action::SelectNext => self.list.select_shown(true),
+            Action::SelectPrevious => self.list.select_shown(false),
+            Action::SelectFirst => self.list.select_shown_end(false),
+            Action::SelectLast => self.list.select_shown_end(true),
+            Action::SelectNextPage => self.list.select_page(true, self.page_size),
+            Action::SelectPreviousPage => self.list.select_page(false, self.page_size),
+            Action::ToggleCollapsed => self.toggle_collapsed(),
+            Action::SelectFirstIncomplete => match self.list.first_incomplete() {
+                Some(i) => self.list.state.select(Some(i)),
+                None => self.toast = Some("All tasks completed! 🎉".into()),
+            },
+            Action::CycleNext => self.cycle_selection(true),
+            Action::CyclePrevious => self.cycle_selection(false),
+            Action::ScrollDown | Action::ScrollUp | Action::ScrollToTop => {
+                let scroll = match self.mode {
+                    Mode::Logs => &mut self.log_scroll,
+                    _ => &mut self.inspector_scroll,
+                };
+                *scroll = match action {
+                    Action::ScrollDown => scroll.saturating_add(1),
+                    Action::ScrollUp => scroll.saturating_sub(1),
+                    _ => 0,
+                };
+            }
+            Action::RenameInline => {
+                if let Some(i) = selected {
+                    self.inline_title_field.set(self.list.items[i].title.clone());
+                    self.mode = Mode::InlineEdit;
                 }
             }
+            Action::OpenDetail if selected.is_some() => self.mode = Mode::TaskDetail,
+            Action::OpenFocus if selected.is_some() => self.mode = Mode::Focus,
+            Action::OpenDetail | Action::OpenFocus => {}
+            Action::OpenHelp => self.mode = Mode::Help,
+            Action::OpenAbout => self.mode = Mode::About,
+            Action::OpenCommand => self.mode = Mode::Command,
+            Action::OpenSearch => self.mode = Mode::Search,
+            Action::OpenQuickAdd => self.mode = Mode::QuickAdd,
+            Action::OpenInspector => self.open_inspector(),
+            Action::OpenJsonEditor => self.open_json_editor(),
+            Action::OpenRecentFiles => {
+                if self.state.recent_files.is_empty() {
+                    self.toast = Some("No recent files".into());
+                } else {
+                    self.mode = Mode::RecentFiles { selected: 0 };
+                }
+            }
+            Action::ArchiveTask => self.toast = self.archive_selected(),
+            Action::OpenArchive => self.mode = Mode::Archive { selected: 0 },
+            Action::OpenImportWizard => {
+                self.mode = Mode::ImportWizard(WizardStep::Format { selected: 0 })
+            }
+            Action::OpenListPicker => {
+                let files = &self.config.task_files;
+                if files.is_empty() {
+                    self.toast = Some("No task_files in the config to pick from".into());
+                } else {
+                    let selected = files.iter().position(|path| path == self.storage.path());
+                    self.mode = Mode::ListPicker { selected: selected.unwrap_or(0) };
+                }
+            }
+            Action::OpenForecast => self.mode = Mode::Forecast { column: 1, row: 0 },
+            Action::OpenSplit => self.toast = self.open_split(None),
+            Action::ToggleLayout => self.toggle_layout_mode(),
+            Action::CycleDetailTab => self.detail_tab = self.detail_tab.next(),
+            Action::CopyAsMarkdown => self.toast = Some(self.run_copy_as_markdown()),
+            Action::PickRandomActive => match self.list.random_active().map(|task| task.id) {
+                Some(id) => {
+                    self.list.state.select(self.list.position_of(id));
+                    self.nudge = Some((id, Instant::now() + NUDGE_DURATION));
+                }
+                None => self.toast = Some("No active tasks to pick from".into()),
+            },
+        }
+    }
+
+    /// Whether the list is locked, saying so when it is
+    fn refuse_read_only(&mut self) -> bool {
+        if self.read_only {
+            self.toast = Some("Read only, :unlock to make changes".into());
+        }
+        self.read_only
+    }
+
+    pub fn title_error(&self) -> Option<TitleError> {
+        TitleError::check(&self.title_field)
+    }
+
+    pub fn due_date_error(&self) -> Option<&'static str> {
+        parse_due_date(&self.due_date_field)
+            .err()
+            .map(|_| "⚠ Due date must be YYYY-MM-DD")
+    }
+
+    /// Checks every field whose format is [`FieldFormat::Url`], leaving empty ones alone
+    pub fn url_error(&self) -> Option<&'static str> {
+        let formats = self.config().field_format;
+        [
+            (formats.title, &self.title_field),
+            (formats.url, &self.url_field),
+            (formats.due_date, &self.due_date_field),
+            (formats.info, &self.info_field),
+        ]
+        .into_iter()
+        .any(|(format, field)| {
+            format == FieldFormat::Url
+                && !field.trim().is_empty()
+                && url::Url::parse(field.trim()).is_err()
+        })
+        .then_some("⚠ Not a valid URL")
+    }
+
+    pub fn field_format(&self, field: CurrentlyEditing) -> FieldFormat {
+        let formats = self.config().field_format;
+        match field {
+            CurrentlyEditing::Title => formats.title,
+            CurrentlyEditing::Url => formats.url,
+            CurrentlyEditing::DueDate => formats.due_date,
+            CurrentlyEditing::Info => formats.info,
+        }
+    }
+
+    fn form_is_valid(&self) -> bool {
+        self.title_error().is_none()
+            && self.due_date_error().is_none()
+            && self.url_error().is_none()
+    }
+
+    fn dismiss_welcome(&mut self) {
+        self.list.push(Task::new(
+            Status::Upcoming,
+            "Welcome to Ratatodo!",
+            "Press t to change this task's status, e to edit it or d to delete it.",
+        ));
+        self.config.first_launch = false;
+
+        let saved = self.save().and_then(|()| self.config.save(&self.config_path));
+        if let Err(error) = saved {
+            self.toast = Some(self.log_error(format!("Couldn't save: {error}")));
         }
+
+        self.mode = Mode::View;
     }
 
     fn new_task(&mut self) {
-        if !self.title_field.is_empty() {
+        if let Ok(due_date) = parse_due_date(&self.due_date_field)
+            && self.title_error().is_none()
+        {
+            // Control characters would let a URL break out of its OSC 8 escape sequence
+            let url: String = self.url_field.trim().chars().filter(|c| !c.is_control()).collect();
+            let url = (!url.is_empty()).then_some(url);
+
+            // Each save is its own undo step, so Ctrl+Enter runs can be undone one at a time
             if let Some(i) = self.editing_existing_item.index {
+                self.push_undo(UndoAction::Restore(vec![self.list.items[i].clone()]));
                 self.list.items[i].title = self.title_field.clone();
                 self.list.items[i].info = self.info_field.clone();
+                self.list.items[i].url = url;
+                self.list.items[i].due_date = due_date;
+                self.list.items[i].touch();
             } else {
-                self.list.items.push(Task::new(
-                    Status::Upcoming,
-                    &self.title_field,
-                    &self.info_field,
-                ));
-            }
-            self.title_field = "".into();
-            self.info_field = "".into();
+                let status = self.new_task_status();
+                let mut task = Task::new(status, &self.title_field, &self.info_field);
+                task.url = url;
+                task.due_date = due_date;
+                self.push_undo(UndoAction::Remove { ids: vec![task.id] });
+                self.list.push(task);
+            }
+            self.dirty = true;
+            for field in [
+                &mut self.title_field,
+                &mut self.info_field,
+                &mut self.url_field,
+                &mut self.due_date_field,
+            ] {
+                field.remember();
+                field.clear();
+            }
             self.currently_editing = CurrentlyEditing::Title;
             self.editing_existing_item = Index { index: None };
         }
     }
 
+    /// The task as the edit form would save it right now, for previewing. Fields that don't
+    /// parse yet are left out
+    pub fn draft_task(&self) -> Task {
+        let mut task = match self.editing_existing_item.index {
+            Some(i) => self.list.items[i].clone(),
+            None => Task::new(self.new_task_status(), "", ""),
+        };
+        task.title = self.title_field.to_string();
+        task.info = self.info_field.to_string();
+        let url = self.url_field.trim();
+        task.url = (!url.is_empty()).then(|| url.to_string());
+        task.due_date = parse_due_date(&self.due_date_field).ok().flatten();
+        task
+    }
+
+    /// Opens the edit form for a new task titled `title`, to quit once it's submitted or
+    /// cancelled
+    pub fn start_quick_entry(&mut self, title: &str) {
+        self.title_field.set(title.to_string());
+        self.currently_editing = CurrentlyEditing::Title;
+        self.quick_entry = true;
+        self.mode = Mode::Edit;
+    }
+
+    /// The status new tasks start with, from the list if it sets one or else the config
+    fn new_task_status(&self) -> Status {
+        self.list.default_status.unwrap_or(self.config.default_status)
+    }
+
+    fn paste_task(&mut self) {
+        match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+            Ok(text) => self.add_clipboard_task(&text),
+            Err(error) => {
+                self.toast = Some(self.log_error(format!("Clipboard unavailable: {error}")));
+            }
+        }
+    }
+
+    /// Adds a task titled with the clipboard's `text`, as long as it's a single valid title
+    fn add_clipboard_task(&mut self, text: &str) {
+        let title = text.trim();
+        if let Some(error) = TitleError::check(title) {
+            self.toast = Some(error.message().into());
+        } else if title.contains('\n') {
+            self.toast = Some("⚠ Clipboard text spans multiple lines".into());
+        } else {
+            let task = Task::new(self.new_task_status(), title, "");
+            self.push_undo(UndoAction::Remove { ids: vec![task.id] });
+            self.list.push(task);
+            self.dirty = true;
+        }
+    }
+
+    /// Adds a task for each line that isn't blank, saying how many came from `source`
+    pub fn add_batch_tasks<'a>(
+        &mut self,
+        lines: impl IntoIterator<Item = &'a str>,
+        source: &str,
+        infer_status: bool,
+    ) {
+        let mut tasks = vec![];
+        let mut skipped = 0;
+        for title in lines.into_iter().map(str::trim).filter(|line| !line.is_empty()) {
+            if TitleError::check(title).is_some() {
+                skipped += 1;
+            } else {
+                tasks.push(Task::new(self.new_task_status(), title, ""));
+            }
+        }
+        let mut batch = TodoList::from(tasks);
+        if infer_status {
+            batch.smart_status_inference();
+        }
+        let added = batch.items.len();
+        if added > 0 {
+            // The whole batch comes back out in one undo step
+            let ids = batch.items.iter().map(|task| task.id).collect();
+            self.push_undo(UndoAction::Remove { ids });
+        }
+        for task in batch {
+            self.list.push(task);
+        }
+
+        self.dirty |= added > 0;
+        self.toast = Some(match skipped {
+            0 => format!("Added {added} tasks from {source}"),
+            _ => format!("Added {added} tasks from {source}, skipped {skipped} with invalid titles"),
+        });
+    }
+
+    /// Pasted text goes into the field being typed in, unless it has several lines and
+    /// `paste_mode` says to make a task of each. Pasting into the list always does
+    pub fn handle_paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let several_lines = text.trim().contains('\n');
+        let adds_tasks = matches!(self.mode, Mode::View)
+            || several_lines && self.config.paste_mode == PasteMode::MultiTask;
+        if adds_tasks && self.refuse_read_only() {
+            return;
+        }
+        if several_lines && self.config.paste_mode == PasteMode::MultiTask {
+            self.mode = Mode::View;
+            self.add_batch_tasks(text.lines(), "the paste", false);
+            return;
+        }
+
+        let single_line = || text.split_whitespace().collect::<Vec<_>>().join(" ");
+        match self.mode {
+            Mode::View => self.add_batch_tasks(text.lines(), "the paste", false),
+            Mode::Edit => {
+                // The details are the only field that keeps line breaks
+                let text = match self.currently_editing {
+                    CurrentlyEditing::Info => text.clone(),
+                    _ => single_line(),
+                };
+                let field = self.editing_field_mut();
+                text.chars().for_each(|value| field.insert_char(value));
+            }
+            Mode::Command => self.command_field.push_str(&single_line()),
+            Mode::Search => self.search_field.push_str(&single_line()),
+            Mode::QuickAdd => self.quick_add_field.push_str(&single_line()),
+            Mode::InlineEdit => self.inline_title_field.push_str(&single_line()),
+            _ => {}
+        }
+    }
+
+    fn toggle_paste_mode(&mut self) {
+        let (mode, message) = match self.config.paste_mode {
+            PasteMode::Literal => (PasteMode::MultiTask, "Pasting makes a task of each line"),
+            PasteMode::MultiTask => (PasteMode::Literal, "Pasting puts the text in as is"),
+        };
+        self.config.paste_mode = mode;
+        self.notify(message, NOTIFICATION_DURATION);
+    }
+
     fn edit_task(&mut self) {
+        self.load_selected_notes();
         if let Some(i) = self.list.state.selected() {
-            self.title_field = self.list.items[i].title.clone();
-            self.info_field = self.list.items[i].info.clone();
+            self.title_field.set(self.list.items[i].title.clone());
+            self.info_field.set(self.list.items[i].info.clone());
+            self.url_field.set(self.list.items[i].url.clone().unwrap_or_default());
+            self.due_date_field.set(
+                self.list.items[i]
+                    .due_date
+                    .map(|date| date.format(DUE_DATE_FORMAT).to_string())
+                    .unwrap_or_default(),
+            );
             self.editing_existing_item = Index { index: Some(i) };
             self.mode = Mode::Edit;
         }
     }
 
+    fn duplicate_task(&mut self) {
+        if let Some(i) = self.list.state.selected() {
+            let copy = self.list.items[i].clone_as_new();
+            self.push_undo(UndoAction::Remove { ids: vec![copy.id] });
+            self.list.insert(i + 1, copy);
+            self.list.state.select(Some(i + 1));
+            self.dirty = true;
+        }
+    }
+
+    /// Hides the selected task's subtasks from the list, or shows them again
+    fn toggle_collapsed(&mut self) {
+        let Some(task) = self.list.state.selected().and_then(|i| self.list.items.get(i)) else {
+            return;
+        };
+        if self.list.subtask_progress(task).is_none() {
+            self.toast = Some("No subtasks to collapse".into());
+        } else if !self.list.collapsed.remove(&task.id) {
+            self.list.collapsed.insert(task.id);
+        }
+    }
+
     fn delete_task(&mut self) {
         if let Some(i) = self.list.state.selected() {
-            self.list.items.remove(i);
+            self.list.remove(i);
+            self.list.snap_selection();
+            self.dirty = true;
+        }
+    }
+
+    /// The prompt symbol and what's been typed, while one of the command line inputs is open
+    pub fn prompt(&self) -> Option<(&'static str, &str)> {
+        match self.mode {
+            Mode::Command => Some((":", self.command_field.as_str())),
+            // Says when the search has switched to matching fields
+            Mode::Search if Query::is_structured(&self.search_field) => {
+                Some(("filter ", self.search_field.as_str()))
+            }
+            Mode::Search => Some(("/", self.search_field.as_str())),
+            Mode::QuickAdd => Some(("+ ", self.quick_add_field.as_str())),
+            Mode::Encrypt { ref passphrase, .. } => Some(("passphrase ", passphrase.masked())),
+            Mode::TagManager { edit: Some((edit, ref name)), .. } => Some((edit.prompt(), name)),
+            _ => None,
+        }
+    }
+
+    /// What's being searched for, while typing a search that isn't blank. Queries with
+    /// fields in aren't plain text, so they don't count
+    pub fn search_query(&self) -> Option<&str> {
+        let query = self.search_field.trim();
+        let plain = !query.is_empty() && !Query::is_structured(query);
+        (matches!(self.mode, Mode::Search) && plain).then_some(query)
+    }
+
+    /// The name of the current mode, which `restore_mode` takes back
+    pub fn mode_name(&self) -> &'static str {
+        match self.mode {
+            Mode::Splash => "splash",
+            Mode::View => "view",
+            Mode::Edit => "edit",
+            Mode::Help => "help",
+            Mode::TaskDetail => "task-detail",
+            Mode::Command => "command",
+            Mode::Welcome => "welcome",
+            Mode::Inspector => "inspector",
+            Mode::JsonEditor { .. } => "json-editor",
+            Mode::Logs => "logs",
+            Mode::IntegrityReport => "integrity-report",
+            Mode::WelcomeBack => "welcome-back",
+            Mode::Focus => "focus",
+            Mode::InlineEdit => "inline-edit",
+            Mode::Search => "search",
+            Mode::QuickAdd => "quick-add",
+            Mode::About => "about",
+            Mode::Notification { .. } => "notification",
+            Mode::DiffImport { .. } => "diff-import",
+            Mode::ImportWizard(_) => "import-wizard",
+            Mode::Encrypt { .. } => "encrypt",
+            Mode::ColumnEditor { .. } => "column-editor",
+            Mode::RecentFiles { .. } => "recent-files",
+            Mode::ListPicker { .. } => "list-picker",
+            Mode::ThemePicker { .. } => "theme-picker",
+            Mode::Archive { .. } => "archive",
+            Mode::TagManager { .. } => "tag-manager",
+            Mode::Minimap { .. } => "minimap",
+            Mode::Timeline { .. } => "timeline",
+            Mode::Gantt { .. } => "gantt",
+            Mode::Forecast { .. } => "forecast",
+            Mode::Split { .. } => "split",
         }
     }
 
+    /// Switches to the mode named `name`. Modes that carry state of their own, like a pending
+    /// import, can't be rebuilt from a name and go back to the list instead
+    pub fn restore_mode(&mut self, name: &str) {
+        self.mode = match name {
+            "edit" => Mode::Edit,
+            "help" => Mode::Help,
+            "task-detail" => Mode::TaskDetail,
+            "command" => Mode::Command,
+            "welcome" => Mode::Welcome,
+            "inspector" => Mode::Inspector,
+            "logs" => Mode::Logs,
+            "integrity-report" => Mode::IntegrityReport,
+            "welcome-back" => Mode::WelcomeBack,
+            "focus" => Mode::Focus,
+            "inline-edit" => Mode::InlineEdit,
+            "search" => Mode::Search,
+            "quick-add" => Mode::QuickAdd,
+            "about" => Mode::About,
+            "recent-files" => Mode::RecentFiles { selected: 0 },
+            "list-picker" => Mode::ListPicker { selected: 0 },
+            "theme-picker" => Mode::ThemePicker { selected: 0 },
+            "archive" => Mode::Archive { selected: 0 },
+            "import-wizard" => Mode::ImportWizard(WizardStep::Format { selected: 0 }),
+            "tag-manager" => Mode::TagManager { selected: 0, edit: None },
+            "minimap" => Mode::Minimap {
+                cursor: self.list.state.selected().unwrap_or(0),
+            },
+            "timeline" => Mode::Timeline { offset: 0, selected: 0 },
+            "gantt" => Mode::Gantt { offset: 0, selected: 0 },
+            "forecast" => Mode::Forecast { column: 1, row: 0 },
+            "column-editor" => {
+                self.open_column_editor();
+                return;
+            }
+            _ => Mode::View,
+        };
+    }
+
+    /// The task `w` picked, while it's still highlighted
+    pub fn nudged_task(&self) -> Option<Uuid> {
+        self.nudge.filter(|&(_, until)| Instant::now() < until).map(|(id, _)| id)
+    }
+
+    pub fn is_editing_inline(&self) -> bool {
+        matches!(self.mode, Mode::InlineEdit)
+    }
+
     fn exit(&mut self) {
         self.exit = true;
     }
 
     fn toggle_mode(&mut self) {
         if let Some(i) = self.list.state.selected() {
-            self.list.items[i].mode = match self.list.items[i].mode {
-                Status::Upcoming => Status::Active,
-                Status::Active => Status::Completed,
-                Status::Completed => Status::Upcoming,
+            let next = self.config.status_cycle_direction.next(self.list.items[i].mode);
+            if !self.set_status(i, next) {
+                self.toast = Some("Depends on unfinished task".into());
+                return;
+            }
+            // The selection stays put, landing on whatever moved up into the gap
+            if next == Status::Completed && self.config.auto_sink_completed {
+                self.list.move_completed_to_bottom();
             }
         }
     }
 
-    fn toggle_editing_field(&mut self) {
-        match self.currently_editing {
-            CurrentlyEditing::Title => self.currently_editing = CurrentlyEditing::Info,
-            CurrentlyEditing::Info => self.currently_editing = CurrentlyEditing::Title,
+    /// Moves the task at `i` to `status`, recording the change for the history tab. Returns
+    /// false, leaving it as it was, when it depends on an unfinished task and `status` would
+    /// start or finish it. Sinking completed tasks is up to the caller, since it moves them
+    pub fn set_status(&mut self, i: usize, status: Status) -> bool {
+        if status != Status::Upcoming && self.list.blocking_task(&self.list.items[i]).is_some() {
+            return false;
         }
+        let task = &mut self.list.items[i];
+        self.status_changes.entry(task.id).or_default().push((Local::now(), task.mode, status));
+        task.mode = status;
+        task.touch();
+        self.dirty = true;
+        true
     }
 
-    fn handle_view_input(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Char('n')
-                | KeyCode::Char('i')
-                | KeyCode::Char('a')
-                | KeyCode::Char('o') => {
-                    self.mode = Mode::Edit;
-                }
-            KeyCode::Char('j') | KeyCode::Down => self.list.state.select_next(),
-            KeyCode::Char('k') | KeyCode::Up => self.list.state.select_previous(),
-            KeyCode::Char('h') => self.mode = Mode::Help,
-            KeyCode::Char('e') => self.edit_task(),
-            KeyCode::Delete | KeyCode::Backspace | KeyCode::Char('d') => self.delete_task(),
-            KeyCode::Char('l')
-                | KeyCode::Right
-                | KeyCode::Tab
-                | KeyCode::Left
-                | KeyCode::Char('t') => self.toggle_mode(),
-            _ => {}
+    fn set_dependency(&mut self, position: Option<&str>) -> String {
+        let Some(i) = self.list.state.selected() else {
+            return "No task selected".into();
+        };
+
+        let Some(position) = position else {
+            self.list.items[i].depends_on = None;
+            self.list.items[i].touch();
+            self.dirty = true;
+            return "Dependency cleared".into();
+        };
+
+        let row = position.parse::<usize>().ok().filter(|&n| n >= 1);
+        let Some(dependency) = row.and_then(|n| self.list.index_of_row(n - 1)) else {
+            return format!("No task at position {position}");
+        };
+
+        if dependency == i {
+            return "A task can't depend on itself".into();
+        }
+        // Every task in a loop would wait on the others forever
+        let id = self.list.items[i].id;
+        if self.list.links_to(&self.list.items[dependency], id, |task| task.depends_on) {
+            return format!(
+                "\"{}\" already depends on this task",
+                self.list.items[dependency].title
+            );
         }
+
+        self.list.items[i].depends_on = Some(self.list.items[dependency].id);
+        self.list.items[i].touch();
+        self.dirty = true;
+        format!("Now depends on \"{}\"", self.list.items[dependency].title)
     }
 
-    fn handle_edit_input(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Esc => self.mode = Mode::View,
-            KeyCode::Tab | KeyCode::Up | KeyCode::Down => self.toggle_editing_field(),
-            KeyCode::Backspace => match self.currently_editing {
-                CurrentlyEditing::Title => {
-                    self.title_field.pop();
-                }
-                CurrentlyEditing::Info => {
-                    self.info_field.pop();
-                }
-            },
-            KeyCode::Enter => match self.currently_editing {
-                CurrentlyEditing::Title => self.currently_editing = CurrentlyEditing::Info,
-                CurrentlyEditing::Info => {
-                    self.new_task();
-                    self.mode = Mode::View;
-                }
-            },
-            KeyCode::Char(value) => match self.currently_editing {
-                CurrentlyEditing::Title => {
-                    self.title_field.push(value);
-                }
-                CurrentlyEditing::Info => {
-                    self.info_field.push(value);
-                }
-            },
-            _ => {}
+    /// `:parent N` makes the selected task a subtask of the one at position `N`, and `:parent`
+    /// on its own makes it a top-level task again
+    fn set_parent(&mut self, position: Option<&str>) -> String {
+        let Some(i) = self.list.state.selected() else {
+            return "No task selected".into();
+        };
+
+        let Some(position) = position else {
+            self.list.items[i].parent = None;
+            self.list.items[i].touch();
+            self.dirty = true;
+            return "No longer a subtask".into();
+        };
+
+        let row = position.parse::<usize>().ok().filter(|&n| n >= 1);
+        let Some(parent) = row.and_then(|n| self.list.index_of_row(n - 1)) else {
+            return format!("No task at position {position}");
+        };
+
+        let id = self.list.items[i].id;
+        if self.list.links_to(&self.list.items[parent], id, |task| task.parent) {
+            return "A task can't be a subtask of itself or of its own subtasks".into();
         }
+
+        self.list.items[i].parent = Some(self.list.items[parent].id);
+        self.list.items[i].touch();
+        self.dirty = true;
+        format!("Now a subtask of \"{}\"", self.list.items[parent].title)
     }
-}
 
-impl Widget for &mut App {
-     fn render(self, area: Rect, buf: &mut Buffer) {
-         match self.mode {
-             Mode::View => self.render_view_mode(area, buf),
-             Mode::Edit => self.render_edit_mode(area, buf),
-             Mode::Help => self.render_help_mode(area, buf),
-         }
-     }
- }
- 
- impl Task {
-     fn new(mode: Status, title: &str, info: &str) -> Self {
-         Self {
-             mode,
-             title: title.to_string(),
-             info: info.to_string(),
-         }
-     }
- }
+    fn cycle_selection(&mut self, forward: bool) {
+        let len = self.list.items.len();
+        if let Some(i) = self.list.state.selected()
+            && len > 0
+        {
+            let step = if forward { 1 } else { len - 1 };
+            let next = (1..=len)
+                .map(|offset| (i + offset * step) % len)
+                .find(|&next| !self.list.items[next].is_archived);
+            self.list.state.select(next.or(Some(i)));
+        }
+    }
+
+    fn toggle_layout_mode(&mut self) {
+        self.layout_mode = match self.layout_mode {
+            LayoutMode::Vertical => LayoutMode::Horizontal,
+            LayoutMode::Horizontal => LayoutMode::Vertical,
+        }
+    }
+
+    fn toggle_editing_field(&mut self, forward: bool) {
+        const ORDER: [CurrentlyEditing; 4] = [
+            CurrentlyEditing::Title,
+            CurrentlyEditing::Url,
+            CurrentlyEditing::DueDate,
+            CurrentlyEditing::Info,
+        ];
+        let i = ORDER
+            .iter()
+            .position(|field| *field == self.currently_editing)
+            .unwrap_or_default();
+        let step = if forward { 1 } else { ORDER.len() - 1 };
+        self.currently_editing = ORDER[(i + step) % ORDER.len()];
+    }
+
+    fn editing_field_mut(&mut self) -> &mut HistoryTextField {
+        match self.currently_editing {
+            CurrentlyEditing::Title => &mut self.title_field,
+            CurrentlyEditing::Url => &mut self.url_field,
+            CurrentlyEditing::DueDate => &mut self.due_date_field,
+            CurrentlyEditing::Info => &mut self.info_field,
+        }
+    }
+
+    fn run_command(&mut self) {
+        self.load_all_notes();
+        self.command_field.remember();
+        let command = std::mem::take(&mut *self.command_field);
+        let args: Vec<&str> = command.split_whitespace().collect();
+        if args.first().is_some_and(|name| CHANGING_COMMANDS.contains(name))
+            && self.refuse_read_only()
+        {
+            return;
+        }
+
+        self.toast = match args.as_slice() {
+            [] => None,
+            ["lock"] => {
+                self.read_only = true;
+                Some("Locked, :unlock to make changes again".into())
+            }
+            ["unlock"] => {
+                self.read_only = false;
+                Some("Unlocked".into())
+            }
+            ["export", "todotxt"] => Some(self.run_export_todotxt(DEFAULT_TODOTXT_PATH)),
+            ["export", "todotxt", path] => Some(self.run_export_todotxt(path)),
+            ["export", "ical", path] => Some(self.run_export_ical(path)),
+            ["export", "org", path] => Some(match fs::write(path, self.export_org()) {
+                Ok(()) => format!("Exported {} tasks to {path}", self.list.items.len()),
+                Err(error) => self.log_error(format!("Export failed: {error}")),
+            }),
+            ["export", "ndjson", path] => Some(match fs::write(path, self.export_json_lines()) {
+                Ok(()) => format!("Exported {} tasks to {path}", self.list.items.len()),
+                Err(error) => self.log_error(format!("Export failed: {error}")),
+            }),
+            ["import"] => {
+                self.mode = Mode::ImportWizard(WizardStep::Format { selected: 0 });
+                None
+            }
+            ["import", "ndjson", path] => match export::import_json_lines(Path::new(path)) {
+                Ok(tasks) => self.preview_import(tasks),
+                Err(error) => Some(self.log_error(format!("Import failed: {error}"))),
+            },
+            ["import", "json", path] => match TodoList::from_json_file(Path::new(path)) {
+                Ok(list) => self.preview_import(list.into()),
+                Err(error) => Some(self.log_error(format!("Import failed: {error}"))),
+            },
+            [command @ ("encrypt" | "decrypt"), path] => {
+                self.mode = Mode::Encrypt {
+                    path: PathBuf::from(path),
+                    decrypt: *command == "decrypt",
+                    passphrase: PasswordField::default(),
+                };
+                None
+            }
+            ["copy"] => Some(self.run_copy_as_markdown()),
+            ["stats"] => {
+                let completed =
+                    self.list.items.iter().filter(|task| task.mode == Status::Completed).count();
+                Some(format!(
+                    "{} tasks, {completed} completed, {} words",
+                    self.list.items.len(),
+                    self.list.total_word_count()
+                ))
+            }
+            ["check"] => {
+                self.integrity_errors = self.check_integrity();
+                if self.integrity_errors.is_empty() {
+                    Some("No problems found".into())
+                } else {
+                    self.mode = Mode::IntegrityReport;
+                    None
+                }
+            }
+            ["minimap"] => {
+                let cursor = self.list.state.selected().unwrap_or(0);
+                self.mode = Mode::Minimap { cursor };
+                None
+            }
+            ["tags"] => {
+                self.mode = Mode::TagManager { selected: 0, edit: None };
+                None
+            }
+            ["timeline"] => {
+                self.mode = Mode::Timeline { offset: 0, selected: 0 };
+                None
+            }
+            ["gantt"] => {
+                self.mode = Mode::Gantt { offset: 0, selected: 0 };
+                None
+            }
+            ["logs"] => {
+                self.log_scroll = 0;
+                self.mode = Mode::Logs;
+                None
+            }
+            ["inspect"] => {
+                self.open_inspector();
+                None
+            }
+            ["theme"] => {
+                let theme = self.config.theme;
+                self.mode = Mode::ThemePicker {
+                    selected: Theme::ALL.iter().position(|&t| t == theme).unwrap_or(0),
+                };
+                self.preview_theme = Some(theme);
+                None
+            }
+            ["tab"] => {
+                self.detail_tab = self.detail_tab.next();
+                None
+            }
+            ["group-by-tag"] => {
+                self.group_by_tag = !self.group_by_tag;
+                Some(if self.group_by_tag { "Grouped by tag" } else { "Ungrouped" }.into())
+            }
+            ["columns"] => {
+                self.open_column_editor();
+                None
+            }
+            ["move", from, to] => Some(self.run_move(from, to)),
+            [command @ ("bulk-status" | "bulk-due" | "bulk-tag"), value, rows] => {
+                Some(self.run_bulk(command, value, rows))
+            }
+            ["swap-groups", a, b] => Some(self.run_swap_status_groups(a, b)),
+            ["clean"] => {
+                let selected = self
+                    .list
+                    .state
+                    .selected()
+                    .and_then(|i| self.list.items.get(i))
+                    .map(|task| task.id);
+                self.list.move_completed_to_bottom();
+                if let Some(id) = selected {
+                    self.list.state.select(self.list.position_of(id));
+                }
+                self.dirty = true;
+                Some("Moved completed tasks to the bottom".into())
+            }
+            ["move-to", project] => Some(self.run_move_to_project(project)),
+            ["split"] => self.open_split(None),
+            ["split", project] => self.open_split(Some(project)),
+            ["start"] => Some(self.set_start_date(None)),
+            ["start", date] => Some(self.set_start_date(Some(date))),
+            ["depends"] => Some(self.set_dependency(None)),
+            ["depends", position] => Some(self.set_dependency(Some(position))),
+            ["parent"] => Some(self.set_parent(None)),
+            ["parent", position] => Some(self.set_parent(Some(position))),
+            ["import", "todotxt", path] => Some(match self.import_todotxt(Path::new(path)) {
+                Ok(count) => {
+                    self.dirty = true;
+                    format!("Imported {count} tasks from {path}")
+                }
+                Err(error) => self.log_error(format!("Import failed: {error}")),
+            }),
+            _ => Some(format!("Unknown command: {command}")),
+        };
+    }
+
+    fn run_move(&mut self, from: &str, to: &str) -> String {
+        let len = self.list.shown_count();
+        let position = |arg: &str| {
+            let row = arg.parse::<usize>().ok().filter(|&n| n >= 1)?;
+            self.list.index_of_row(row - 1)
+        };
+        let (Some(from_index), Some(to_index)) = (position(from), position(to)) else {
+            return format!("Positions must be between 1 and {len}");
+        };
+
+        self.move_task(from_index, to_index);
+        format!("Moved task {from} to {to}")
+    }
+
+    /// Moves a task to another position, undoably
+    pub fn move_task(&mut self, from: usize, to: usize) {
+        self.list.reorder(from, to);
+        self.list.state.select(Some(to));
+        self.push_undo(UndoAction::Reorder { from: to, to: from });
+        self.dirty = true;
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    fn run_copy_as_markdown(&mut self) -> String {
+        match self.export_to_clipboard() {
+            Ok(()) => format!("Copied {} tasks as Markdown", self.list.shown_count()),
+            Err(error) => self.log_error(format!("Clipboard unavailable: {error}")),
+        }
+    }
+
+    fn run_swap_status_groups(&mut self, a: &str, b: &str) -> String {
+        let (Some(a), Some(b)) = (Status::from_name(a), Status::from_name(b)) else {
+            return "Usage: swap-groups upcoming|active|completed upcoming|active|completed".into();
+        };
+
+        // Keep the same task selected even though it has probably moved
+        let selected = self
+            .list
+            .state
+            .selected()
+            .and_then(|i| self.list.items.get(i))
+            .map(|task| task.id);
+        self.list.swap_status_group(a, b);
+        if let Some(id) = selected {
+            self.list.state.select(self.list.position_of(id));
+        }
+        self.dirty = true;
+        format!("Moved {a:?} tasks below {b:?} tasks")
+    }
+
+    fn run_move_to_project(&mut self, project_name: &str) -> String {
+        let Some(i) = self.list.state.selected() else {
+            return "No task selected".into();
+        };
+        let (path, mut project) = match self.load_project(project_name) {
+            Ok(project) => project,
+            Err(error) => return self.log_error(error),
+        };
+
+        self.list.move_to_project(i, &mut project);
+        if let Err(error) = project.save_json_file(&path) {
+            // Put it back rather than lose it
+            if let Some(task) = project.items.pop() {
+                self.list.insert(i, task);
+                self.list.state.select(Some(i));
+            }
+            return self.log_error(format!("Move failed: {error}"));
+        }
+
+        // Save straight away too, or a crash would leave the task in both files
+        match self.save() {
+            Ok(()) => format!("Moved task to {project_name}"),
+            Err(error) => self.log_error(format!(
+                "Moved task to {project_name}, but saving failed: {error}"
+            )),
+        }
+    }
+
+    /// Projects are task files of their own, kept next to the main one
+    fn projects_dir(&self) -> PathBuf {
+        self.storage.path().with_file_name("projects")
+    }
+
+    /// Reads the project called `name`, which is empty if it hasn't been saved yet
+    fn load_project(&self, name: &str) -> Result<(PathBuf, TodoList), String> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(format!("Invalid project name: {name}"));
+        }
+        let path = self.projects_dir().join(format!("{name}.json"));
+        let project = match path.try_exists() {
+            Ok(true) => TodoList::from_json_file(&path),
+            Ok(false) => Ok(TodoList::from_iter([])),
+            Err(error) => Err(error),
+        };
+        project
+            .map(|project| (path, project))
+            .map_err(|error| format!("Couldn't open {name}: {error}"))
+    }
+
+    /// The first project by name, for opening the split view without naming one
+    fn first_project_name(&self) -> Option<String> {
+        let mut names: Vec<String> = fs::read_dir(self.projects_dir())
+            .ok()?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let is_json = path.extension().is_some_and(|extension| extension == "json");
+                is_json.then(|| path.file_stem()?.to_str().map(String::from))?
+            })
+            .collect();
+        names.sort();
+        names.into_iter().next()
+    }
+
+    /// Shows the tasks next to the project `name`, or the one shown last time
+    fn open_split(&mut self, name: Option<&str>) -> Option<String> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => match self.last_split_project.clone().or_else(|| self.first_project_name()) {
+                Some(name) => name,
+                None => return Some("No projects yet, open one with :split <name>".into()),
+            },
+        };
+        let (path, mut project) = match self.load_project(&name) {
+            Ok(project) => project,
+            Err(error) => return Some(self.log_error(error)),
+        };
+
+        if !project.items.is_empty() {
+            project.state.select(Some(0));
+        }
+        self.last_split_project = Some(name.clone());
+        self.mode = Mode::Split {
+            name,
+            path,
+            project,
+            focus_right: false,
+        };
+        None
+    }
+
+    /// Both panes of the split view and whether the right one has focus, while it's open
+    pub fn split_panes(&mut self) -> Option<(&mut TodoList, &str, &mut TodoList, bool)> {
+        match &mut self.mode {
+            Mode::Split {
+                name,
+                project,
+                focus_right,
+                ..
+            } => Some((&mut self.list, name.as_str(), project, *focus_right)),
+            _ => None,
+        }
+    }
+
+    fn handle_split_input(&mut self, key_event: KeyEvent) {
+        if key_event.code == KeyCode::Char('x') && self.refuse_read_only() {
+            return;
+        }
+        let Mode::Split {
+            name,
+            project,
+            focus_right,
+            ..
+        } = &mut self.mode
+        else {
+            return;
+        };
+        let (focused, other) = if *focus_right {
+            (project, &mut self.list)
+        } else {
+            (&mut self.list, project)
+        };
+        let moved_to = match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q' | '\\') => {
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Char('h') | KeyCode::Left => {
+                *focus_right = false;
+                return;
+            }
+            KeyCode::Char('l') | KeyCode::Right => {
+                *focus_right = true;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                focused.state.select_next();
+                return;
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                focused.state.select_previous();
+                return;
+            }
+            KeyCode::Char('x') => {
+                let Some(i) = focused.state.selected().filter(|&i| i < focused.items.len())
+                else {
+                    return;
+                };
+                focused.move_to_project(i, other);
+                if other.state.selected().is_none() {
+                    other.state.select(Some(0));
+                }
+                if *focus_right { "your tasks".to_string() } else { name.clone() }
+            }
+            _ => return,
+        };
+
+        // Save both straight away, like :move-to, so a crash can't leave the task in both
+        let saved = match &self.mode {
+            Mode::Split { path, project, .. } => project.save_json_file(path),
+            _ => Ok(()),
+        };
+        self.toast = Some(match saved.and_then(|()| self.save()) {
+            Ok(()) => format!("Moved task to {moved_to}"),
+            Err(error) => self.log_error(format!(
+                "Moved task to {moved_to}, but saving failed: {error}"
+            )),
+        });
+    }
+
+    /// Shows what importing `tasks` would change and waits for confirmation
+    fn preview_import(&mut self, tasks: Vec<Task>) -> Option<String> {
+        let (new_tasks, updated_tasks) = self.list.diff_import(tasks);
+        if new_tasks.is_empty() && updated_tasks.is_empty() {
+            return Some("Nothing to import, every task is already here".into());
+        }
+        self.mode = Mode::DiffImport {
+            new_tasks,
+            updated_tasks,
+        };
+        None
+    }
+
+    /// The import waiting for confirmation, if any
+    pub fn pending_import(&self) -> Option<(&[Task], &[TaskUpdate])> {
+        match &self.mode {
+            Mode::DiffImport {
+                new_tasks,
+                updated_tasks,
+            } => Some((new_tasks, updated_tasks)),
+            _ => None,
+        }
+    }
+
+    fn handle_diff_import_input(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Char('y' | 'Y') => {
+                if let Mode::DiffImport {
+                    new_tasks,
+                    updated_tasks,
+                } = std::mem::replace(&mut self.mode, Mode::View)
+                {
+                    let count = new_tasks.len() + updated_tasks.len();
+                    self.list.apply_import(new_tasks, updated_tasks);
+                    self.dirty = true;
+                    self.notify(&format!("Imported {count} tasks"), NOTIFICATION_DURATION);
+                }
+            }
+            KeyCode::Char('n' | 'N') | KeyCode::Esc => {
+                self.mode = Mode::View;
+                self.toast = Some("Import cancelled".into());
+            }
+            _ => {}
+        }
+    }
+
+    /// Where the import wizard is up to, while it's open
+    pub fn import_wizard(&self) -> Option<&WizardStep> {
+        match &self.mode {
+            Mode::ImportWizard(step) => Some(step),
+            _ => None,
+        }
+    }
+
+    fn handle_import_wizard_input(&mut self, key_event: KeyEvent) {
+        let Mode::ImportWizard(step) = std::mem::replace(&mut self.mode, Mode::View) else {
+            return;
+        };
+        let down = matches!(key_event.code, KeyCode::Char('j') | KeyCode::Down);
+        let up = matches!(key_event.code, KeyCode::Char('k') | KeyCode::Up);
+        let step = match (step, key_event.code) {
+            (WizardStep::Format { .. }, KeyCode::Esc) => return,
+            (WizardStep::Format { selected }, _) if down || up => WizardStep::Format {
+                selected: match down {
+                    true => (selected + 1).min(ImportFormat::ALL.len() - 1),
+                    false => selected.saturating_sub(1),
+                },
+            },
+            (WizardStep::Format { selected }, KeyCode::Enter) => WizardStep::Path {
+                format: ImportFormat::ALL[selected],
+                field: HistoryTextField::default(),
+            },
+            (WizardStep::Path { format, .. }, KeyCode::Esc) => WizardStep::Format {
+                selected: ImportFormat::ALL.iter().position(|&f| f == format).unwrap_or(0),
+            },
+            (WizardStep::Path { format, mut field }, KeyCode::Tab) => {
+                if let Some(completed) = import::complete_path(&field) {
+                    field.set(completed);
+                }
+                WizardStep::Path { format, field }
+            }
+            (WizardStep::Path { format, field }, KeyCode::Enter) => {
+                let path = PathBuf::from(field.trim());
+                match format.read(&path) {
+                    Ok(tasks) if !tasks.is_empty() => WizardStep::Preview { format, path, tasks },
+                    Ok(_) => {
+                        self.toast = Some(format!("No tasks in {}", path.display()));
+                        WizardStep::Path { format, field }
+                    }
+                    Err(error) => {
+                        let message = format!("Couldn't read {}: {error}", path.display());
+                        self.toast = Some(self.log_error(message));
+                        WizardStep::Path { format, field }
+                    }
+                }
+            }
+            (WizardStep::Path { format, mut field }, code) => {
+                if !field.handle_cursor_key(key_event) {
+                    match code {
+                        KeyCode::Backspace => field.backspace(),
+                        KeyCode::Char(c) => field.insert_char(c),
+                        _ => {}
+                    }
+                }
+                WizardStep::Path { format, field }
+            }
+            (WizardStep::Preview { format, path, .. }, KeyCode::Esc) => {
+                let mut field = HistoryTextField::default();
+                field.set(path.display().to_string());
+                WizardStep::Path { format, field }
+            }
+            (WizardStep::Preview { format, path, tasks }, KeyCode::Enter) => {
+                WizardStep::Strategy { format, path, tasks, selected: 0 }
+            }
+            (WizardStep::Strategy { format, path, tasks, .. }, KeyCode::Esc) => {
+                WizardStep::Preview { format, path, tasks }
+            }
+            (WizardStep::Strategy { format, path, tasks, selected }, _) if down || up => {
+                let selected = match down {
+                    true => (selected + 1).min(MergeStrategy::ALL.len() - 1),
+                    false => selected.saturating_sub(1),
+                };
+                WizardStep::Strategy { format, path, tasks, selected }
+            }
+            (WizardStep::Strategy { path, tasks, selected, .. }, KeyCode::Enter) => {
+                let strategy = MergeStrategy::ALL[selected];
+                let (added, updated) = self.list.import_with(tasks, strategy);
+                if strategy == MergeStrategy::Replace {
+                    // What's left to undo was done to tasks that are gone
+                    self.undo_stack.clear();
+                }
+                self.list.snap_selection();
+                if self.list.state.selected().is_none() {
+                    self.list.select_shown_end(false);
+                }
+                self.mark_dirty();
+                let message = match updated {
+                    0 => format!("Imported {added} tasks from {}", path.display()),
+                    _ => format!(
+                        "Imported {added} tasks from {} and updated {updated}",
+                        path.display()
+                    ),
+                };
+                self.notify(&message, NOTIFICATION_DURATION);
+                return;
+            }
+            (step, _) => step,
+        };
+        self.mode = Mode::ImportWizard(step);
+    }
+
+    fn open_column_editor(&mut self) {
+        let visible = &self.config.visible_columns;
+        let mut columns: Vec<(Column, bool)> =
+            visible.iter().map(|&column| (column, true)).collect();
+        let hidden = Column::ALL.into_iter().filter(|column| !visible.contains(column));
+        columns.extend(hidden.map(|column| (column, false)));
+        self.mode = Mode::ColumnEditor { columns, selected: 0 };
+    }
+
+    /// Every column and whether it's shown, plus the highlighted one, while editing them
+    pub fn column_editor(&self) -> Option<(&[(Column, bool)], usize)> {
+        match &self.mode {
+            Mode::ColumnEditor { columns, selected } => Some((columns, *selected)),
+            _ => None,
+        }
+    }
+
+    fn handle_column_editor_input(&mut self, key_event: KeyEvent) {
+        let Mode::ColumnEditor { columns, selected } = &mut self.mode else {
+            return;
+        };
+        let last = columns.len() - 1;
+        match key_event.code {
+            KeyCode::Esc => self.mode = Mode::View,
+            KeyCode::Char('j') | KeyCode::Down => *selected = (*selected + 1).min(last),
+            KeyCode::Char('k') | KeyCode::Up => *selected = selected.saturating_sub(1),
+            KeyCode::Char('J') if *selected < last => {
+                columns.swap(*selected, *selected + 1);
+                *selected += 1;
+            }
+            KeyCode::Char('K') if *selected > 0 => {
+                columns.swap(*selected, *selected - 1);
+                *selected -= 1;
+            }
+            KeyCode::Char(' ') => columns[*selected].1 = !columns[*selected].1,
+            KeyCode::Enter => {
+                let visible: Vec<Column> =
+                    columns.iter().filter(|(_, shown)| *shown).map(|(column, _)| *column).collect();
+                if visible.is_empty() {
+                    self.toast = Some("Pick at least one column".into());
+                    return;
+                }
+                self.config.visible_columns = visible;
+                self.mode = Mode::View;
+                if let Err(error) = self.config.save(&self.config_path) {
+                    self.toast = Some(self.log_error(format!("Couldn't save the config: {error}")));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The task under the minimap's cursor, while it's open
+    pub fn minimap_cursor(&self) -> Option<usize> {
+        match self.mode {
+            Mode::Minimap { cursor } => Some(cursor),
+            _ => None,
+        }
+    }
+
+    /// Cells per minimap row, no more than 80 and no more than fit
+    pub fn minimap_width(&self) -> usize {
+        match self.minimap_grid.width {
+            0 => 80,
+            width => usize::from(width).min(80),
+        }
+    }
+
+    fn handle_minimap_input(&mut self, key_event: KeyEvent) {
+        let Mode::Minimap { cursor } = self.mode else {
+            return;
+        };
+        let last = self.list.items.len().saturating_sub(1);
+        let width = self.minimap_width();
+        let cursor = match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Enter => {
+                self.jump_to_minimap_cell(cursor);
+                return;
+            }
+            KeyCode::Char('h') | KeyCode::Left => cursor.saturating_sub(1),
+            KeyCode::Char('l') | KeyCode::Right => cursor + 1,
+            KeyCode::Char('k') | KeyCode::Up => cursor.checked_sub(width).unwrap_or(cursor),
+            KeyCode::Char('j') | KeyCode::Down if cursor + width <= last => cursor + width,
+            KeyCode::Home => 0,
+            KeyCode::End => last,
+            _ => cursor,
+        };
+        self.mode = Mode::Minimap { cursor: cursor.min(last) };
+    }
+
+    fn handle_minimap_mouse(&mut self, mouse_event: MouseEvent) {
+        if self.minimap_cursor().is_none()
+            || mouse_event.kind != MouseEventKind::Down(MouseButton::Left)
+        {
+            return;
+        }
+        let grid = self.minimap_grid;
+        if !grid.contains((mouse_event.column, mouse_event.row).into()) {
+            return;
+        }
+        let row = usize::from(mouse_event.row - grid.y) + self.minimap_scroll;
+        let i = row * self.minimap_width() + usize::from(mouse_event.column - grid.x);
+        if i < self.list.items.len() {
+            self.jump_to_minimap_cell(i);
+        }
+    }
+
+    /// Every task with a due date as how many days from `today` it's due, negative when
+    /// it's overdue, and its index in the list. Soonest first, in list order within a day
+    pub fn timeline(&self, today: NaiveDate) -> Vec<(i64, usize)> {
+        let mut tasks: Vec<(i64, usize)> = self
+            .list
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| !task.is_archived)
+            .filter_map(|(i, task)| Some(((task.due_date? - today).num_days(), i)))
+            .collect();
+        tasks.sort_by_key(|&(day, _)| day);
+        tasks
+    }
+
+    pub fn timeline_state(&self) -> Option<(usize, usize)> {
+        match self.mode {
+            Mode::Timeline { offset, selected } => Some((offset, selected)),
+            _ => None,
+        }
+    }
+
+    pub fn gantt_state(&self) -> Option<(usize, usize)> {
+        match self.mode {
+            Mode::Gantt { offset, selected } => Some((offset, selected)),
+            _ => None,
+        }
+    }
+
+    fn handle_gantt_input(&mut self, key_event: KeyEvent) {
+        let Mode::Gantt { mut offset, mut selected } = self.mode else {
+            return;
+        };
+        let rows = self.list.gantt_rows();
+        let days = self.gantt_days.max(1);
+        let last_offset = self.list.gantt_range().map_or(0, |(first, last)| {
+            usize::try_from((last - first).num_days() + 1).unwrap_or(0).saturating_sub(days)
+        });
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Enter => {
+                if let Some(&(i, ..)) = rows.get(selected) {
+                    self.list.state.select(Some(i));
+                }
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Char('h') | KeyCode::Left => offset = offset.saturating_sub(1),
+            KeyCode::Char('l') | KeyCode::Right => offset = (offset + 1).min(last_offset),
+            KeyCode::Char('H') => offset = offset.saturating_sub(days),
+            KeyCode::Char('L') => offset = (offset + days).min(last_offset),
+            KeyCode::Char('j') | KeyCode::Down => {
+                selected = (selected + 1).min(rows.len().saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => selected = selected.saturating_sub(1),
+            _ => {}
+        }
+
+        // Bring the newly selected task's start into view if it's off screen
+        if matches!(key_event.code, KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up)
+            && let Some(&(_, start, _)) = rows.get(selected)
+            && let Some((first, _)) = self.list.gantt_range()
+            && let Ok(day) = usize::try_from((start - first).num_days())
+        {
+            offset = offset.clamp((day + 1).saturating_sub(days), day);
+        }
+        self.mode = Mode::Gantt { offset, selected };
+    }
+
+    fn handle_timeline_input(&mut self, key_event: KeyEvent) {
+        let Mode::Timeline { mut offset, mut selected } = self.mode else {
+            return;
+        };
+        let timeline = self.timeline(Local::now().date_naive());
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Enter => {
+                if let Some(&(_, i)) = timeline.get(selected) {
+                    self.list.state.select(Some(i));
+                }
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Char('h') | KeyCode::Left => offset = offset.saturating_sub(1),
+            KeyCode::Char('l') | KeyCode::Right => offset += 1,
+            KeyCode::Char('j') | KeyCode::Down => {
+                selected = (selected + 1).min(timeline.len().saturating_sub(1))
+            }
+            KeyCode::Char('k') | KeyCode::Up => selected = selected.saturating_sub(1),
+            _ => {}
+        }
+
+        // Scroll to the newly selected task's day if it's off screen. Overdue ones are
+        // always shown
+        if matches!(key_event.code, KeyCode::Char('j' | 'k') | KeyCode::Down | KeyCode::Up)
+            && let Some(&(day, _)) = timeline.get(selected)
+            && let Ok(day) = usize::try_from(day)
+        {
+            let days = self.timeline_days.max(1);
+            offset = offset.clamp((day + 1).saturating_sub(days), day);
+        }
+        self.mode = Mode::Timeline { offset, selected };
+    }
+
+    /// The list indices of the tasks that are past due, then of those due on each of the
+    /// next `FORECAST_DAYS` days, in list order within each
+    pub fn forecast(&self, today: NaiveDate) -> Vec<Vec<usize>> {
+        let mut columns = vec![vec![]; FORECAST_DAYS + 1];
+        for (day, i) in self.timeline(today) {
+            match usize::try_from(day + 1) {
+                Ok(column) if column <= FORECAST_DAYS => columns[column].push(i),
+                Ok(_) => {}
+                Err(_) => columns[0].push(i),
+            }
+        }
+        columns
+    }
+
+    pub fn forecast_cell(&self) -> Option<(usize, usize)> {
+        match self.mode {
+            Mode::Forecast { column, row } => Some((column, row)),
+            _ => None,
+        }
+    }
+
+    fn handle_forecast_input(&mut self, key_event: KeyEvent) {
+        let Mode::Forecast { mut column, mut row } = self.mode else {
+            return;
+        };
+        let forecast = self.forecast(Local::now().date_naive());
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Enter => {
+                if let Some(&i) = forecast[column].get(row) {
+                    self.list.state.select(Some(i));
+                    self.mode = Mode::View;
+                }
+                return;
+            }
+            KeyCode::Char('h') | KeyCode::Left => column = column.saturating_sub(1),
+            KeyCode::Char('l') | KeyCode::Right => column = (column + 1).min(FORECAST_DAYS),
+            KeyCode::Char('k') | KeyCode::Up => row = row.saturating_sub(1),
+            KeyCode::Char('j') | KeyCode::Down => row += 1,
+            _ => {}
+        }
+        // Moving to a shorter day lands on its last task
+        row = row.min(forecast[column].len().saturating_sub(1));
+        self.mode = Mode::Forecast { column, row };
+    }
+
+    fn jump_to_minimap_cell(&mut self, i: usize) {
+        if i < self.list.items.len() {
+            self.list.state.select(Some(i));
+        }
+        self.mode = Mode::View;
+    }
+
+    /// Clicks are only wanted on the minimap. Capturing the mouse anywhere else would stop
+    /// the terminal from selecting text
+    fn sync_mouse_capture(&mut self) -> io::Result<()> {
+        let wanted = self.minimap_cursor().is_some();
+        if wanted != self.mouse_captured {
+            if wanted {
+                crossterm::execute!(io::stdout(), EnableMouseCapture)?;
+            } else {
+                crossterm::execute!(io::stdout(), DisableMouseCapture)?;
+            }
+            self.mouse_captured = wanted;
+        }
+        Ok(())
+    }
+
+    /// A blinking I-beam while typing in the edit form, and the user's own cursor otherwise
+    fn sync_cursor_style(&mut self) -> io::Result<()> {
+        let wanted = matches!(self.mode, Mode::Edit);
+        if wanted != self.cursor_bar {
+            if wanted {
+                crossterm::execute!(io::stdout(), SetCursorStyle::BlinkingBar)?;
+            } else {
+                crossterm::execute!(io::stdout(), SetCursorStyle::DefaultUserShape)?;
+            }
+            self.cursor_bar = wanted;
+        }
+        Ok(())
+    }
+
+    /// Which of the recent files is highlighted, while picking one
+    pub fn recent_files_selection(&self) -> Option<usize> {
+        match self.mode {
+            Mode::RecentFiles { selected } => Some(selected),
+            _ => None,
+        }
+    }
+
+    /// Which of `Config::task_files` is highlighted, while picking one
+    pub fn list_picker_selection(&self) -> Option<usize> {
+        match self.mode {
+            Mode::ListPicker { selected } => Some(selected),
+            _ => None,
+        }
+    }
+
+    /// The theme the screen is drawn with, which may be one the theme picker is previewing
+    pub fn theme(&self) -> Theme {
+        self.preview_theme.unwrap_or(self.config.theme)
+    }
+
+    /// Which of `Theme::ALL` is highlighted, while picking one
+    pub fn theme_picker_selection(&self) -> Option<usize> {
+        match self.mode {
+            Mode::ThemePicker { selected } => Some(selected),
+            _ => None,
+        }
+    }
+
+    /// The tasks file currently open
+    pub fn tasks_path(&self) -> &Path {
+        self.storage.path()
+    }
+
+    pub fn storage(&self) -> &StorageBackend {
+        &self.storage
+    }
+
+    /// Which tag is highlighted in the tag manager
+    pub fn tag_manager_selection(&self) -> Option<usize> {
+        match self.mode {
+            Mode::TagManager { selected, .. } => Some(selected),
+            _ => None,
+        }
+    }
+
+    fn handle_tag_manager_input(&mut self, key_event: KeyEvent) {
+        if matches!(self.mode, Mode::TagManager { edit: None, .. })
+            && matches!(key_event.code, KeyCode::Char('r' | 'm' | 'd'))
+            && self.refuse_read_only()
+        {
+            return;
+        }
+        let Mode::TagManager { selected, ref mut edit } = self.mode else {
+            return;
+        };
+        let tags = self.list.tag_counts();
+        let Some((tag, _)) = tags.get(selected) else {
+            if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q')) {
+                self.mode = Mode::View;
+            }
+            return;
+        };
+
+        if let Some((action, name)) = edit {
+            match key_event.code {
+                KeyCode::Esc => *edit = None,
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                // A tag is one word, and the `#` is already there
+                KeyCode::Char(value) if !value.is_whitespace() && value != '#' => {
+                    name.push(value)
+                }
+                KeyCode::Enter if name == tag => *edit = None,
+                KeyCode::Enter if !name.is_empty() => {
+                    let (action, name) = (*action, std::mem::take(name));
+                    *edit = None;
+                    let report = self.list.replace_tag(tag, Some(&name));
+                    let verb = match action {
+                        TagEdit::Rename => "Renamed",
+                        TagEdit::Merge => "Merged",
+                    };
+                    self.finish_tag_change(format!("{verb} #{tag} into #{name}"), report);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        let last = tags.len() - 1;
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::View,
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.mode = Mode::TagManager { selected: (selected + 1).min(last), edit: None }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.mode = Mode::TagManager { selected: selected.saturating_sub(1), edit: None }
+            }
+            KeyCode::Char('r') => {
+                *edit = Some((TagEdit::Rename, tag.clone()));
+                self.toast = None;
+            }
+            KeyCode::Char('m') => {
+                *edit = Some((TagEdit::Merge, String::new()));
+                self.toast = None;
+            }
+            KeyCode::Char('d') => {
+                let report = self.list.replace_tag(tag, None);
+                self.finish_tag_change(format!("Removed #{tag}"), report);
+            }
+            _ => {}
+        }
+    }
+
+    /// Saves and reports a change made from the tag manager, keeping the selection on the
+    /// list of tags as it is now
+    fn finish_tag_change(&mut self, done: String, report: TagReport) {
+        if report.changed > 0 {
+            self.mark_dirty();
+        }
+        self.toast = Some(match report.skipped {
+            0 => format!("{done} on {} tasks", report.changed),
+            skipped => format!(
+                "{done} on {} tasks, {skipped} would have had too long a title",
+                report.changed
+            ),
+        });
+        if let Mode::TagManager { selected, .. } = &mut self.mode {
+            *selected = (*selected).min(self.list.tag_counts().len().saturating_sub(1));
+        }
+    }
+
+    fn handle_recent_files_input(&mut self, key_event: KeyEvent) {
+        let Mode::RecentFiles { selected } = self.mode else {
+            return;
+        };
+        let last = self.state.recent_files.len().saturating_sub(1);
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::View,
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.mode = Mode::RecentFiles { selected: (selected + 1).min(last) }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.mode = Mode::RecentFiles { selected: selected.saturating_sub(1) }
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::View;
+                if let Some(path) = self.state.recent_files.get(selected).cloned() {
+                    self.toast = Some(self.open_file(path));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Which archived task is highlighted in the archive view
+    pub fn archive_selection(&self) -> Option<usize> {
+        match self.mode {
+            Mode::Archive { selected } => Some(selected),
+            _ => None,
+        }
+    }
+
+    fn handle_archive_input(&mut self, key_event: KeyEvent) {
+        let Mode::Archive { selected } = self.mode else {
+            return;
+        };
+        let archived = self.list.archived();
+        let last = archived.len().saturating_sub(1);
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::View,
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.mode = Mode::Archive { selected: (selected + 1).min(last) }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.mode = Mode::Archive { selected: selected.saturating_sub(1) }
+            }
+            KeyCode::Char('r') | KeyCode::Enter if !self.refuse_read_only() => {
+                if let Some(&i) = archived.get(selected) {
+                    self.toast = Some(self.restore_archived(i));
+                    self.mode = Mode::Archive { selected: selected.min(last.saturating_sub(1)) };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_list_picker_input(&mut self, key_event: KeyEvent) {
+        let Mode::ListPicker { selected } = self.mode else {
+            return;
+        };
+        let last = self.config.task_files.len().saturating_sub(1);
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.mode = Mode::View,
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.mode = Mode::ListPicker { selected: (selected + 1).min(last) }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.mode = Mode::ListPicker { selected: selected.saturating_sub(1) }
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::View;
+                if let Some(path) = self.config.task_files.get(selected).cloned() {
+                    self.toast = Some(self.open_file(path));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_theme_picker_input(&mut self, key_event: KeyEvent) {
+        let Mode::ThemePicker { selected } = self.mode else {
+            return;
+        };
+        let selected = match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.preview_theme = None;
+                self.mode = Mode::View;
+                return;
+            }
+            KeyCode::Enter => {
+                self.config.theme = Theme::ALL[selected];
+                self.preview_theme = None;
+                self.mode = Mode::View;
+                self.toast = Some(match self.config.save(&self.config_path) {
+                    Ok(()) => format!("Switched to the {} theme", self.config.theme.name()),
+                    Err(error) => self.log_error(format!("Couldn't save the config: {error}")),
+                });
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => (selected + 1).min(Theme::ALL.len() - 1),
+            KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+            _ => return,
+        };
+        self.mode = Mode::ThemePicker { selected };
+        self.preview_theme = Some(Theme::ALL[selected]);
+    }
+
+    /// Saves the current tasks file, then loads `path` in its place
+    fn open_file(&mut self, path: PathBuf) -> String {
+        if path == self.storage.path() {
+            return format!("{} is already open", path.display());
+        }
+        let storage = StorageBackend::for_path(path);
+        match storage.exists() {
+            Ok(true) => {}
+            Ok(false) => return format!("{} no longer exists", storage.path().display()),
+            Err(error) => {
+                let message = format!("Couldn't open {}: {error}", storage.path().display());
+                return self.log_error(message);
+            }
+        }
+        if let Err(error) = self.save() {
+            return self.log_error(format!("Couldn't save the current file: {error}"));
+        }
+        let list = match storage.load() {
+            Ok(list) => list,
+            Err(error) => {
+                let message = format!("Couldn't open {}: {error}", storage.path().display());
+                return self.log_error(message);
+            }
+        };
+
+        self.list = list;
+        self.storage = storage;
+        // Undoing into a different file's tasks would make no sense
+        self.undo_stack.clear();
+        self.mark_notes_unread();
+        self.mark_synced();
+        self.state.remember_file(self.storage.path());
+        self.state.last_opened = Some(self.storage.path().to_path_buf());
+        let message = format!("Opened {}", self.storage.path().display());
+        match self.state.save(&self.state_path) {
+            Ok(()) => message,
+            Err(error) => {
+                self.log_error(format!("{message}, but remembering it failed: {error}"))
+            }
+        }
+    }
+
+    fn run_export_todotxt(&mut self, path: &str) -> String {
+        match self.export_todotxt(Path::new(path)) {
+            Ok(()) => format!("Exported {} tasks to {path}", self.list.items.len()),
+            Err(error) => self.log_error(format!("Export failed: {error}")),
+        }
+    }
+
+    fn run_export_ical(&mut self, path: &str) -> String {
+        let count = self.list.items.iter().filter(|task| task.due_date.is_some()).count();
+        match fs::write(path, self.export_ical()) {
+            Ok(()) => format!("Exported {count} tasks with due dates to {path}"),
+            Err(error) => self.log_error(format!("Export failed: {error}")),
+        }
+    }
+
+    fn handle_command_input(&mut self, key_event: KeyEvent) {
+        if self.command_field.handle_history_key(key_event) {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc => {
+                self.command_field.clear();
+                self.mode = Mode::View;
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::View;
+                self.run_command();
+            }
+            KeyCode::Backspace => {
+                self.command_field.pop();
+            }
+            KeyCode::Char(value) => self.command_field.push(value),
+            _ => {}
+        }
+    }
+
+    fn open_inspector(&mut self) {
+        if self.list.state.selected().is_some() {
+            self.inspector_scroll = 0;
+            self.mode = Mode::Inspector;
+        }
+    }
+
+    fn open_json_editor(&mut self) {
+        let Some(task) = self.list.state.selected().and_then(|i| self.list.items.get(i)) else {
+            return;
+        };
+        match serde_json::to_string_pretty(task) {
+            Ok(json) => {
+                let mut field = HistoryTextField::default();
+                field.set(json);
+                field.move_home();
+                self.mode = Mode::JsonEditor { field, error: None };
+            }
+            Err(error) => self.toast = Some(format!("Couldn't serialize task: {error}")),
+        }
+    }
+
+    /// The JSON being edited, with why it last failed to save
+    pub fn json_editor(&self) -> Option<(&HistoryTextField, Option<&str>)> {
+        match &self.mode {
+            Mode::JsonEditor { field, error } => Some((field, error.as_deref())),
+            _ => None,
+        }
+    }
+
+    fn handle_json_editor_input(&mut self, key_event: KeyEvent) {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let Mode::JsonEditor { field, error } = &mut self.mode else {
+            return;
+        };
+        if field.handle_cursor_key(key_event) {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc => self.mode = Mode::Inspector,
+            KeyCode::Char('s') if control => {
+                let Some(i) = self.list.state.selected() else {
+                    self.mode = Mode::Inspector;
+                    return;
+                };
+                let mut task = match serde_json::from_str::<Task>(field) {
+                    Ok(task) => task,
+                    Err(parse_error) => {
+                        *error = Some(parse_error.to_string());
+                        return;
+                    }
+                };
+                // Notes and dependencies find the task by its id
+                if task.id != self.list.items[i].id {
+                    *error = Some("The id can't be changed".into());
+                    return;
+                }
+                if let Some(title_error) = TitleError::check(&task.title) {
+                    *error = Some(title_error.message().into());
+                    return;
+                }
+                task.touch();
+                self.list.items[i] = task;
+                self.dirty = true;
+                self.toast = Some("Task saved".into());
+                self.mode = Mode::Inspector;
+            }
+            KeyCode::Enter => field.insert_char('\n'),
+            KeyCode::Tab => {
+                field.insert_char(' ');
+                field.insert_char(' ');
+            }
+            KeyCode::Up => field.move_line(true),
+            KeyCode::Down => field.move_line(false),
+            KeyCode::Backspace => field.backspace(),
+            KeyCode::Char(value) if !control => field.insert_char(value),
+            _ => {}
+        }
+    }
+
+    fn handle_encrypt_input(&mut self, key_event: KeyEvent) {
+        self.toast = None;
+        let Mode::Encrypt { passphrase, .. } = &mut self.mode else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Esc => self.mode = Mode::View,
+            KeyCode::Enter if passphrase.is_empty() => {
+                self.toast = Some("The passphrase can't be empty".into());
+            }
+            KeyCode::Enter => {
+                let Mode::Encrypt { path, decrypt, passphrase } =
+                    std::mem::replace(&mut self.mode, Mode::View)
+                else {
+                    return;
+                };
+                self.toast = if decrypt {
+                    match encrypt::decrypt_list(&path, passphrase) {
+                        Ok(list) => self.preview_import(list.into()),
+                        Err(error) => Some(self.log_error(format!("Decrypting failed: {error}"))),
+                    }
+                } else {
+                    Some(match encrypt::encrypt_list(&self.list, &path, passphrase) {
+                        Ok(()) => format!(
+                            "Encrypted {} tasks to {}",
+                            self.list.items.len(),
+                            path.display()
+                        ),
+                        Err(error) => self.log_error(format!("Encrypting failed: {error}")),
+                    })
+                };
+            }
+            KeyCode::Backspace => passphrase.pop(),
+            KeyCode::Char(value) => passphrase.push(value),
+            _ => {}
+        }
+    }
+
+    /// A title-only prompt on the command line, for adding tasks without the edit form
+    fn handle_quick_add_input(&mut self, key_event: KeyEvent) {
+        self.toast = None;
+        if self.quick_add_field.handle_history_key(key_event) {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc => {
+                self.quick_add_field.clear();
+                self.mode = Mode::View;
+            }
+            KeyCode::Enter => {
+                let title = self.quick_add_field.trim();
+                if let Some(error) = TitleError::check(title) {
+                    self.toast = Some(error.message().into());
+                    return;
+                }
+                self.list.push(Task::new(self.new_task_status(), title, ""));
+                self.list.state.select(Some(self.list.items.len() - 1));
+                self.quick_add_field.remember();
+                self.quick_add_field.clear();
+                self.dirty = true;
+                self.mode = Mode::View;
+            }
+            KeyCode::Backspace => {
+                self.quick_add_field.pop();
+            }
+            KeyCode::Char(value) => self.quick_add_field.push(value),
+            _ => {}
+        }
+    }
+
+    fn handle_search_input(&mut self, key_event: KeyEvent) {
+        if self.search_field.handle_history_key(key_event) {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc => {
+                self.search_field.remember();
+                self.search_field.clear();
+                self.mode = Mode::View;
+            }
+            KeyCode::Enter => {
+                self.search_field.remember();
+                self.mode = Mode::View;
+                self.toast = self.select_next_match();
+            }
+            KeyCode::Up => self.search_field.step_history(true),
+            KeyCode::Down => self.search_field.step_history(false),
+            KeyCode::Backspace => {
+                self.search_field.pop();
+            }
+            KeyCode::Char(value) => self.search_field.push(value),
+            _ => {}
+        }
+    }
+
+    /// Selects the next task after the selection whose title or info contains the query,
+    /// wrapping around. Queries with fields in, like `status:active`, are matched clause by
+    /// clause instead. Returns a toast when nothing matches
+    fn select_next_match(&mut self) -> Option<String> {
+        self.load_all_notes();
+        let query = std::mem::take(&mut *self.search_field).to_lowercase();
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        let structured = if Query::is_structured(query) {
+            match Query::parse(query) {
+                Ok(structured) => Some(structured),
+                Err(error) => return Some(error),
+            }
+        } else {
+            None
+        };
+
+        let len = self.list.items.len();
+        let start = self.list.state.selected().map_or(0, |i| i + 1);
+        let found = (0..len).map(|offset| (start + offset) % len).find(|&i| {
+            let task = &self.list.items[i];
+            match &structured {
+                _ if task.is_archived => false,
+                Some(structured) => structured.matches(task),
+                None => {
+                    task.title.to_lowercase().contains(query)
+                        || task.info.to_lowercase().contains(query)
+                }
+            }
+        });
+
+        match found {
+            Some(i) => {
+                self.list.state.select(Some(i));
+                None
+            }
+            None => Some(format!("No tasks match \"{query}\"")),
+        }
+    }
+
+    /// Renames the selected task in place, without filling in the edit form
+    fn handle_inline_edit_input(&mut self, key_event: KeyEvent) {
+        if self.inline_title_field.handle_history_key(key_event) {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc => {
+                self.inline_title_field.clear();
+                self.mode = Mode::View;
+            }
+            KeyCode::Enter => {
+                let Some(i) = self.list.state.selected() else {
+                    self.mode = Mode::View;
+                    return;
+                };
+                if let Some(error) = TitleError::check(&self.inline_title_field) {
+                    self.toast = Some(error.message().into());
+                    return;
+                }
+                self.inline_title_field.remember();
+                self.list.items[i].title = std::mem::take(&mut *self.inline_title_field);
+                self.list.items[i].touch();
+                self.dirty = true;
+                self.toast = None;
+                self.mode = Mode::View;
+            }
+            KeyCode::Backspace => {
+                self.inline_title_field.pop();
+            }
+            KeyCode::Char(value) => self.inline_title_field.push(value),
+            _ => {}
+        }
+    }
+
+    fn handle_edit_input(&mut self, key_event: KeyEvent) {
+        // Not Ctrl+P, which already steps back through the field's history
+        if key_event.code == KeyCode::F(3) {
+            self.show_preview = !self.show_preview;
+            return;
+        }
+        let format = self.field_format(self.currently_editing);
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let field = self.editing_field_mut();
+        if field.handle_history_key(key_event)
+            || (format == FieldFormat::Date
+                && field.handle_date_key(key_event, Local::now().date_naive()))
+            || field.handle_cursor_key(key_event)
+        {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc => {
+                self.mode = Mode::View;
+                if self.quick_entry {
+                    self.exit();
+                }
+            }
+            KeyCode::Tab | KeyCode::Down => self.toggle_editing_field(true),
+            KeyCode::BackTab | KeyCode::Up => self.toggle_editing_field(false),
+            KeyCode::Backspace => self.editing_field_mut().backspace(),
+            // Save and start on the next one, from any field
+            KeyCode::Enter if control && self.form_is_valid() => self.new_task(),
+            KeyCode::Enter if control => {}
+            KeyCode::Enter => match self.currently_editing {
+                CurrentlyEditing::Info => {
+                    if self.form_is_valid() {
+                        self.new_task();
+                        self.mode = Mode::View;
+                        if self.quick_entry {
+                            self.exit();
+                        }
+                    }
+                }
+                // Don't move on from a URL that won't parse, the error line says why
+                _ if format == FieldFormat::Url && self.url_error().is_some() => {}
+                _ => self.toggle_editing_field(true),
+            },
+            KeyCode::Char(value) => self.editing_field_mut().insert_char(value),
+            _ => {}
+        }
+    }
+}
+
+impl Widget for &mut App {
+     fn render(self, area: Rect, buf: &mut Buffer) {
+         match self.mode {
+             Mode::Notification { .. } => {
+                 // Draw the screen underneath by briefly restoring its mode, then put the
+                 // notification back on top
+                 if let Mode::Notification { message, expires_at, previous_mode } =
+                     std::mem::replace(&mut self.mode, Mode::View)
+                 {
+                     self.mode = *previous_mode;
+                     Widget::render(&mut *self, area, buf);
+                     let previous_mode = Box::new(std::mem::replace(&mut self.mode, Mode::View));
+                     self.mode = Mode::Notification { message, expires_at, previous_mode };
+                 }
+                 self.render_notification(area, buf);
+             }
+             Mode::View
+             | Mode::Command
+             | Mode::InlineEdit
+             | Mode::Search
+             | Mode::QuickAdd
+             | Mode::Encrypt { .. } => self.render_view_mode(area, buf),
+             Mode::Edit => self.render_edit_mode(area, buf),
+             Mode::Splash => self.render_splash(area, buf),
+             Mode::Help => self.render_help_mode(area, buf),
+             Mode::TaskDetail => self.render_task_detail_mode(area, buf),
+             Mode::Welcome => self.render_welcome_mode(area, buf),
+             Mode::Inspector => self.render_inspector_mode(area, buf),
+             Mode::JsonEditor { .. } => self.render_json_editor(area, buf),
+             Mode::Logs => self.render_logs_mode(area, buf),
+             Mode::Minimap { .. } => self.render_minimap(area, buf),
+             Mode::Timeline { .. } => self.render_timeline(area, buf),
+             Mode::Gantt { .. } => self.render_gantt(area, buf),
+             Mode::Forecast { .. } => self.render_forecast(area, buf),
+             Mode::Split { .. } => self.render_split(area, buf),
+             Mode::IntegrityReport => {
+                 self.render_view_mode(area, buf);
+                 self.render_integrity_report(area, buf);
+             }
+             Mode::WelcomeBack => {
+                 self.render_view_mode(area, buf);
+                 self.render_welcome_back(area, buf);
+             }
+             Mode::DiffImport { .. } => {
+                 self.render_view_mode(area, buf);
+                 self.render_diff_import(area, buf);
+             }
+             Mode::ImportWizard(_) => {
+                 self.render_view_mode(area, buf);
+                 self.render_import_wizard(area, buf);
+             }
+             Mode::ColumnEditor { .. } => {
+                 self.render_view_mode(area, buf);
+                 self.render_column_editor(area, buf);
+             }
+             Mode::RecentFiles { .. } => {
+                 self.render_view_mode(area, buf);
+                 self.render_recent_files(area, buf);
+             }
+             Mode::ListPicker { .. } => {
+                 self.render_view_mode(area, buf);
+                 self.render_list_picker(area, buf);
+             }
+             Mode::ThemePicker { .. } => {
+                 self.render_view_mode(area, buf);
+                 self.render_theme_picker(area, buf);
+             }
+             Mode::Archive { .. } => self.render_archive(area, buf),
+             Mode::TagManager { .. } => {
+                 self.render_view_mode(area, buf);
+                 self.render_tag_manager(area, buf);
+             }
+             Mode::About => {
+                 self.render_view_mode(area, buf);
+                 self.render_about(area, buf);
+             }
+             Mode::Focus => {
+                 self.render_view_mode(area, buf);
+                 self.render_focus_mode(area, buf);
+             }
+         }
+     }
+ }
+ 
+ fn parse_due_date(field: &str) -> Result<Option<NaiveDate>, chrono::ParseError> {
+     let field = field.trim();
+     if field.is_empty() {
+         Ok(None)
+     } else {
+         NaiveDate::parse_from_str(field, DUE_DATE_FORMAT).map(Some)
+     }
+ }
+
+ impl TitleError {
+     pub fn check(title: &str) -> Option<TitleError> {
+         if title.trim().is_empty() {
+             Some(TitleError::Blank)
+         } else if title.chars().count() > MAX_TITLE_LEN {
+             Some(TitleError::TooLong)
+         } else {
+             None
+         }
+     }
+
+     pub fn message(&self) -> &'static str {
+         match self {
+             TitleError::TooLong => "⚠ Title too long",
+             TitleError::Blank => "⚠ Title cannot be blank",
+         }
+     }
+ }
+ 
+ impl Task {
+     pub fn new(mode: Status, title: &str, info: &str) -> Self {
+         Self {
+             id: Uuid::new_v4(),
+             depends_on: None,
+             parent: None,
+             mode,
+             title: title.to_string(),
+             info: info.to_string(),
+             url: None,
+             start_date: None,
+             due_date: None,
+             last_modified: Some(Utc::now()),
+             is_archived: false,
+         }
+     }
+
+     /// A copy to start another task from, under a new id, for `D`. The title, info, URL,
+     /// due date, dependency and parent carry over, so the copy waits on the same task as the
+     /// original. It starts upcoming, unarchived and modified just now
+     pub fn clone_as_new(&self) -> Task {
+         Task {
+             id: Uuid::new_v4(),
+             mode: Status::Upcoming,
+             last_modified: Some(Utc::now()),
+             is_archived: false,
+             ..self.clone()
+         }
+     }
+
+     pub fn touch(&mut self) {
+         self.last_modified = Some(Utc::now());
+     }
+
+     /// Whitespace-separated words across the title and info
+     pub fn word_count(&self) -> usize {
+         self.title.split_whitespace().count() + self.info.split_whitespace().count()
+     }
+
+     /// The first line of the info with anything on it, for previews that only have room
+     /// for one
+     pub fn summary_line(&self) -> &str {
+         self.info.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default()
+     }
+
+     /// Words in the title written as `#tag`, without the `#`, each only once
+     pub fn tags(&self) -> BTreeSet<&str> {
+         self.title
+             .split_whitespace()
+             .filter_map(|word| word.strip_prefix('#'))
+             .filter(|tag| !tag.is_empty())
+             .collect()
+     }
+
+     /// The task's row in the list, made of `columns` in order with `marker` as the status
+     pub fn list_text(&self, columns: &[Column], marker: &str) -> String {
+         let mut text = String::new();
+         for column in columns {
+             let value = match column {
+                 Column::Status => marker.to_string(),
+                 Column::Title => self.title.clone(),
+                 Column::DueDate => match self.due_date {
+                     Some(date) => date.format(DUE_DATE_FORMAT).to_string(),
+                     None => continue,
+                 },
+                 Column::Tags => match self.tags() {
+                     tags if tags.is_empty() => continue,
+                     tags => tags.iter().map(|tag| format!("#{tag}")).collect::<Vec<_>>().join(" "),
+                 },
+             };
+             text.push(' ');
+             text.push_str(&value);
+         }
+         text
+     }
+
+     pub fn to_line(&self, columns: &[Column]) -> Line<'static> {
+         let text = self.list_text(columns, &self.mode.marker().to_string());
+         match self.mode {
+             Status::Completed => Line::styled(text, COMPLETED_TEXT_FG_COLOR),
+             Status::Upcoming | Status::Active => Line::raw(text),
+         }
+     }
+ }
  
+ impl From<&Task> for Line<'_> {
+     fn from(value: &Task) -> Self {
+         value.to_line(&[Column::Status, Column::Title])
+     }
+ }
+
  impl From<&Task> for ListItem<'_> {
      fn from(value: &Task) -> Self {
-         let line = match value.mode {
-             Status::Upcoming => Line::raw(format!(" _ {}", value.title)),
-             Status::Active => Line::raw(format!(" ☐ {}", value.title)),
-             Status::Completed => {
-                 Line::styled(format!(" ✓ {}", value.title), COMPLETED_TEXT_FG_COLOR)
+         ListItem::new(Line::from(value))
+     }
+ }
+ 
+ impl Status {
+     /// The symbol shown before a task's title in the list
+     pub fn marker(self) -> char {
+         match self {
+             Status::Upcoming => '_',
+             Status::Active => '☐',
+             Status::Completed => '✓',
+         }
+     }
+
+     pub fn from_name(name: &str) -> Option<Status> {
+         match name.to_lowercase().as_str() {
+             "upcoming" => Some(Status::Upcoming),
+             "active" => Some(Status::Active),
+             "completed" => Some(Status::Completed),
+             _ => None,
+         }
+     }
+ }
+
+ impl Default for App {
+     fn default() -> Self {
+         Self {
+             exit: false,
+             list: TodoList::from_iter([]),
+             mode: Mode::View,
+             title_field: HistoryTextField::default(),
+             info_field: HistoryTextField::default(),
+             url_field: HistoryTextField::default(),
+             due_date_field: HistoryTextField::default(),
+             command_field: HistoryTextField::default(),
+             inline_title_field: HistoryTextField::default(),
+             search_field: HistoryTextField::default(),
+             quick_add_field: HistoryTextField::default(),
+             undo_stack: vec![],
+             toast: None,
+             inspector_scroll: 0,
+             log_buffer: VecDeque::new(),
+             log_scroll: 0,
+             notes_unread: HashSet::new(),
+             watcher: None,
+             synced_hash: None,
+             synced_at: Utc::now(),
+             nudge: None,
+             minimap_grid: Rect::default(),
+             minimap_scroll: 0,
+             timeline_days: 0,
+             gantt_days: 0,
+             preview_theme: None,
+             mouse_captured: false,
+             cursor_bar: false,
+             keymap: keymap::default_keymap(),
+             #[cfg(debug_assertions)]
+             debug_overlay: false,
+             #[cfg(debug_assertions)]
+             last_render: Duration::ZERO,
+             edit_cursor: None,
+             quick_entry: false,
+             last_split_project: None,
+             last_window_title: String::new(),
+             layout_mode: LayoutMode::Vertical,
+             detail_tab: DetailTab::Overview,
+             status_changes: HashMap::new(),
+             group_by_tag: false,
+             read_only: false,
+             show_preview: false,
+             integrity_errors: vec![],
+             changed_since_last_session: vec![],
+             loading: None,
+             dirty: false,
+             last_save: Instant::now(),
+             last_tick: Instant::now(),
+             spinner_state: 0,
+             page_size: 1,
+             config: Config::default(),
+             status_bar: StatusBarSegment::parse(&Config::default().status_bar_format),
+             config_path: config::config_path(),
+             state: State::default(),
+             state_path: config::state_path(),
+             storage: StorageBackend::File(config::tasks_path()),
+             currently_editing: CurrentlyEditing::Title,
+             editing_existing_item: Index { index: None },
+         }
+     }
+ }
+ 
+ impl TodoList {
+     /// Moves the task at `index` to the end of `project`, keeping a task selected if there is
+     /// one left. Returns `index` so the move can be undone
+     pub fn move_to_project(&mut self, index: usize, project: &mut TodoList) -> usize {
+         let task = self.remove(index);
+         project.push(task);
+         if self.items.is_empty() {
+             self.state.select(None);
+         } else {
+             self.state.select(Some(index.min(self.items.len() - 1)));
+         }
+         index
+     }
+
+     /// Moves the task at `from` to `to`, shifting the ones in between along by one
+     pub fn reorder(&mut self, from: usize, to: usize) {
+         let task = self.items.remove(from);
+         self.items.splice(to..to, [task]);
+         self.reindex_from(from.min(to));
+     }
+
+     /// The tasks at the indices in `selection`, in index order, for the bulk edits. Indices
+     /// past the end are skipped
+     pub fn iter_selected<'a>(
+         &'a self,
+         selection: &'a BTreeSet<usize>,
+     ) -> impl Iterator<Item = &'a Task> {
+         selection.iter().map_while(|&i| self.items.get(i))
+     }
+
+     pub fn iter_selected_mut<'a>(
+         &'a mut self,
+         selection: &'a BTreeSet<usize>,
+     ) -> impl Iterator<Item = &'a mut Task> {
+         self.items
+             .iter_mut()
+             .enumerate()
+             .filter(|(i, _)| selection.contains(i))
+             .map(|(_, task)| task)
+     }
+
+     pub fn find_by_id(&self, id: Uuid) -> Option<&Task> {
+         self.position_of(id).map(|i| &self.items[i])
+     }
+
+     pub fn find_by_id_mut(&mut self, id: Uuid) -> Option<&mut Task> {
+         self.position_of(id).map(|i| &mut self.items[i])
+     }
+
+     /// Looks the id up in the index, falling back to a scan if `items` was changed behind
+     /// its back
+     pub fn position_of(&self, id: Uuid) -> Option<usize> {
+         match self.index.get(&id) {
+             Some(&i) if self.items.get(i).is_some_and(|task| task.id == id) => Some(i),
+             _ => self.items.iter().position(|task| task.id == id),
+         }
+     }
+
+     pub fn push(&mut self, task: Task) {
+         self.index.insert(task.id, self.items.len());
+         self.items.push(task);
+     }
+
+     pub fn insert(&mut self, index: usize, task: Task) {
+         self.items.insert(index, task);
+         self.reindex_from(index);
+     }
+
+     pub fn remove(&mut self, index: usize) -> Task {
+         let task = self.items.remove(index);
+         self.index.remove(&task.id);
+         self.reindex_from(index);
+         task
+     }
+
+     pub fn swap(&mut self, a: usize, b: usize) {
+         self.items.swap(a, b);
+         self.index.insert(self.items[a].id, a);
+         self.index.insert(self.items[b].id, b);
+     }
+
+     /// An empty list with room for `n` tasks, and their ids in the index, before it has to
+     /// grow
+     pub fn with_capacity(n: usize) -> Self {
+         Self {
+             items: Vec::with_capacity(n),
+             state: ListState::default(),
+             last_opened: None,
+             default_status: None,
+             collapsed: HashSet::new(),
+             index: HashMap::with_capacity(n),
+         }
+     }
+
+     pub fn rebuild_index(&mut self) {
+         self.index.clear();
+         self.reindex_from(0);
+     }
+
+     fn reindex_from(&mut self, start: usize) {
+         for (i, task) in self.items.iter().enumerate().skip(start) {
+             self.index.insert(task.id, i);
+         }
+     }
+
+     /// Moves every task with status `a` to just after the last task with status `b`, keeping
+     /// the order within each group. Does nothing if there are no `b` tasks
+     pub fn swap_status_group(&mut self, a: Status, b: Status) {
+         if a == b || !self.items.iter().any(|task| task.mode == b) {
+             return;
+         }
+         let (group, mut rest): (Vec<Task>, Vec<Task>) =
+             std::mem::take(&mut self.items).into_iter().partition(|task| task.mode == a);
+         let at = rest.iter().rposition(|task| task.mode == b).map_or(0, |i| i + 1);
+         rest.splice(at..at, group);
+         self.items = rest;
+         self.rebuild_index();
+     }
+
+     pub fn random_active(&self) -> Option<&Task> {
+         let active: Vec<&Task> = self
+             .items
+             .iter()
+             .filter(|task| task.mode == Status::Active && !task.is_archived)
+             .collect();
+         active.choose(&mut rand::thread_rng()).copied()
+     }
+
+     /// Moves every completed task to the end, keeping the order within both groups
+     pub fn move_completed_to_bottom(&mut self) {
+         let (completed, mut rest): (Vec<Task>, Vec<Task>) = std::mem::take(&mut self.items)
+             .into_iter()
+             .partition(|task| task.mode == Status::Completed);
+         rest.extend(completed);
+         self.items = rest;
+         self.rebuild_index();
+     }
+
+     pub fn total_word_count(&self) -> usize {
+         self.items.iter().map(Task::word_count).sum()
+     }
+
+     /// Tasks keyed by each of their tags, so a task with two tags is in two groups. Untagged
+     /// tasks go under `""`
+     pub fn group_by_tag(&self) -> BTreeMap<String, Vec<&Task>> {
+         let mut groups: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+         for task in self.items.iter().filter(|task| !task.is_archived) {
+             let tags = task.tags();
+             if tags.is_empty() {
+                 groups.entry(String::new()).or_default().push(task);
              }
-         };
-         ListItem::new(line)
+             for tag in tags {
+                 groups.entry(tag.to_string()).or_default().push(task);
+             }
+         }
+         groups
      }
- }
- 
- impl Default for App {
-     fn default() -> Self {
-         Self {
-             exit: false,
-             list: TodoList::from_iter([]),
-             mode: Mode::View,
-             title_field: "".into(),
-             info_field: "".into(),
-             currently_editing: CurrentlyEditing::Title,
-             editing_existing_item: Index { index: None },
+
+     /// The index of the first task that still needs doing
+     pub fn first_incomplete(&self) -> Option<usize> {
+         self.items.iter().position(|task| task.mode != Status::Completed && !task.is_archived)
+     }
+
+     /// Up to `count` of the tasks the list shows, from its row `start` on, with their
+     /// positions in `items`. Archived tasks and the subtasks of collapsed ones have no row
+     pub fn shown_window(&self, start: usize, count: usize) -> Vec<(usize, &Task)> {
+         self.shown_rows()
+             .into_iter()
+             .skip(start)
+             .take(count)
+             .map(|(_, i)| (i, &self.items[i]))
+             .collect()
+     }
+
+     /// How many rows the list has
+     pub fn shown_count(&self) -> usize {
+         self.shown_rows().len()
+     }
+
+     /// The row the selected task is on, `None` when it has none
+     pub fn selected_row(&self) -> Option<usize> {
+         let i = self.state.selected()?;
+         self.shown_rows().iter().position(|&(_, row)| row == i)
+     }
+
+     /// The tasks the list shows on `page`, counting from 0, with their positions in `items`.
+     /// Archived tasks are left out. Empty past the last page
+     pub fn paginate(&self, page: usize, page_size: usize) -> Vec<(usize, &Task)> {
+         self.shown_window(page * page_size, page_size)
+     }
+
+     pub fn page_count(&self, page_size: usize) -> usize {
+         self.shown_count().div_ceil(page_size)
+     }
+
+     /// The page the selected task is on, or the first page when nothing shown is selected
+     pub fn current_page(&self, page_size: usize) -> usize {
+         self.selected_row().map_or(0, |row| row / page_size)
+     }
+
+     /// Selects the first task of the next or previous page, for Page Down and Page Up. Stays
+     /// on the first or last page at the ends
+     pub fn select_page(&mut self, forward: bool, page_size: usize) {
+         let page = self.current_page(page_size);
+         let last = self.page_count(page_size).saturating_sub(1);
+         let page = if forward { (page + 1).min(last) } else { page.saturating_sub(1) };
+         if let Some(&(i, _)) = self.paginate(page, page_size).first() {
+             self.state.select(Some(i));
+         }
+     }
+
+     /// Whether following `link` from `task`, a task at a time, gets to the task with the id
+     /// `target`, `task` itself included. Loops that don't pass through it end the search
+     pub fn links_to(
+         &self,
+         task: &Task,
+         target: Uuid,
+         link: impl Fn(&Task) -> Option<Uuid>,
+     ) -> bool {
+         let mut seen = HashSet::new();
+         let mut current = task;
+         while current.id != target {
+             if !seen.insert(current.id) {
+                 return false;
+             }
+             match link(current).and_then(|id| self.find_by_id(id)) {
+                 Some(next) => current = next,
+                 None => return false,
+             }
          }
+         true
+     }
+
+     /// How many of this task's subtasks are completed, out of how many there are. `None`
+     /// when it has none
+     pub fn subtask_progress(&self, task: &Task) -> Option<(usize, usize)> {
+         let subtasks = self.items.iter().filter(|item| item.parent == Some(task.id));
+         let (done, total) = subtasks.fold((0, 0), |(done, total), item| {
+             (done + usize::from(item.mode == Status::Completed), total + 1)
+         });
+         (total > 0).then_some((done, total))
+     }
+
+     /// Returns the task this one depends on, as long as it isn't completed yet
+     pub fn blocking_task(&self, task: &Task) -> Option<&Task> {
+         self.find_by_id(task.depends_on?)
+             .filter(|item| item.mode != Status::Completed)
      }
  }
  
@@ -264,8 +3393,1138 @@ impl Widget for &mut App {
          let items = iter
              .into_iter()
              .map(|(mode, title, info)| Task::new(mode, title, info))
-             .collect();
-         let state = ListState::default();
-         Self { items, state }
+             .collect::<Vec<_>>();
+         Self::from(items)
+     }
+ }
+
+ impl From<Vec<Task>> for TodoList {
+     /// Every load and import ends up here. The tasks are kept where they are, only the
+     /// index is built, sized for them up front
+     fn from(items: Vec<Task>) -> Self {
+         let mut list = Self {
+             index: HashMap::with_capacity(items.len()),
+             items,
+             ..Self::with_capacity(0)
+         };
+         list.reindex_from(0);
+         list
+     }
+ }
+
+ impl From<TodoList> for Vec<Task> {
+     fn from(list: TodoList) -> Self {
+         list.items
+     }
+ }
+
+ impl IntoIterator for TodoList {
+     type Item = Task;
+     type IntoIter = std::vec::IntoIter<Task>;
+
+     fn into_iter(self) -> Self::IntoIter {
+         self.items.into_iter()
      }
  }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_path;
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key_events(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    /// Types `command` into the command line and runs it
+    fn run_command_line(app: &mut App, command: &str) {
+        press(app, KeyCode::Char(':'));
+        type_text(app, command);
+        press(app, KeyCode::Enter);
+    }
+
+    #[test]
+    fn parent_command_refuses_loops_of_subtasks() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Trip", ""),
+                (Status::Upcoming, "Flights", ""),
+                (Status::Upcoming, "Seats", ""),
+            ]),
+            ..App::default()
+        };
+        app.list.state.select(Some(1));
+        run_command_line(&mut app, "parent 1");
+        app.list.state.select(Some(2));
+        run_command_line(&mut app, "parent 2");
+        assert_eq!(app.toast.as_deref(), Some("Now a subtask of \"Flights\""));
+        assert_eq!(app.list.items[2].parent, Some(app.list.items[1].id));
+
+        app.list.state.select(Some(0));
+        run_command_line(&mut app, "parent 3");
+        assert_eq!(
+            app.toast.as_deref(),
+            Some("A task can't be a subtask of itself or of its own subtasks")
+        );
+        assert_eq!(app.list.items[0].parent, None);
+
+        app.list.state.select(Some(2));
+        run_command_line(&mut app, "parent");
+        assert_eq!(app.list.items[2].parent, None);
+    }
+
+    #[test]
+    fn depends_command_refuses_loops() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "A", ""),
+                (Status::Upcoming, "B", ""),
+                (Status::Upcoming, "C", ""),
+            ]),
+            ..App::default()
+        };
+        app.list.state.select(Some(0));
+        run_command_line(&mut app, "depends 2");
+        app.list.state.select(Some(1));
+        run_command_line(&mut app, "depends 3");
+
+        // C waiting on A would leave all three blocked for good
+        app.list.state.select(Some(2));
+        run_command_line(&mut app, "depends 1");
+        assert_eq!(app.toast.as_deref(), Some("\"A\" already depends on this task"));
+        assert_eq!(app.list.items[2].depends_on, None);
+        app.list.state.select(Some(1));
+        run_command_line(&mut app, "depends 1");
+        assert_eq!(app.list.items[1].depends_on, Some(app.list.items[2].id));
+    }
+
+    #[test]
+    fn renaming_a_task_keeps_dependents_resolved() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Parent", ""),
+                (Status::Upcoming, "Child", ""),
+                (Status::Upcoming, "Sibling", ""),
+            ]),
+            ..App::default()
+        };
+        let parent = app.list.items[0].id;
+        app.list.items[1].depends_on = Some(parent);
+        app.list.items[2].depends_on = Some(parent);
+
+        app.list.state.select(Some(0));
+        press(&mut app, KeyCode::Char('e'));
+        for _ in "Parent".chars() {
+            press(&mut app, KeyCode::Backspace);
+        }
+        type_text(&mut app, "Renamed parent");
+        for _ in 0..4 {
+            press(&mut app, KeyCode::Enter);
+        }
+
+        assert_eq!(app.list.items[0].title, "Renamed parent");
+        assert_eq!(app.list.items[0].id, parent);
+        for dependent in &app.list.items[1..] {
+            let blocker = app.list.blocking_task(dependent).expect("dependency should resolve");
+            assert_eq!(blocker.title, "Renamed parent");
+        }
+    }
+
+    #[test]
+    fn json_editor_saves_only_valid_json() {
+        let mut app = App {
+            list: TodoList::from_iter([(Status::Upcoming, "Old title", "")]),
+            ..App::default()
+        };
+        let ctrl = |app: &mut App, c| {
+            app.handle_key_events(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+        };
+        app.list.state.select(Some(0));
+        ctrl(&mut app, 'i');
+        ctrl(&mut app, 'j');
+        assert_eq!(app.mode_name(), "json-editor");
+
+        // A stray comma isn't JSON, so the editor stays open with the parser's complaint
+        press(&mut app, KeyCode::Char(','));
+        ctrl(&mut app, 's');
+        assert_eq!(app.mode_name(), "json-editor");
+        assert!(app.json_editor().and_then(|(_, error)| error).is_some());
+        press(&mut app, KeyCode::Backspace);
+
+        let (json, _) = app.json_editor().unwrap();
+        let edited = json.replace("Old title", "New title");
+        if let Mode::JsonEditor { field, .. } = &mut app.mode {
+            field.set(edited);
+        }
+        ctrl(&mut app, 's');
+        assert_eq!(app.mode_name(), "inspector");
+        assert_eq!(app.list.items[0].title, "New title");
+        assert!(app.is_dirty());
+    }
+
+    #[test]
+    fn status_changes_are_kept_for_the_history_tab() {
+        let mut app = App {
+            list: TodoList::from_iter([(Status::Upcoming, "Water plants", "")]),
+            ..App::default()
+        };
+        app.list.state.select(Some(0));
+        run_command_line(&mut app, "tab");
+        assert_eq!(app.detail_tab, DetailTab::Notes);
+
+        app.dispatch(Action::ToggleStatus);
+        app.dispatch(Action::ToggleStatus);
+        let id = app.list.items[0].id;
+        let changes: Vec<_> =
+            app.status_changes[&id].iter().map(|&(_, from, to)| (from, to)).collect();
+        assert_eq!(
+            changes,
+            [(Status::Upcoming, Status::Active), (Status::Active, Status::Completed)]
+        );
+    }
+
+    #[test]
+    fn archived_tasks_leave_the_list_until_restored() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Keep", ""),
+                (Status::Upcoming, "Old", ""),
+                (Status::Upcoming, "Also keep", ""),
+            ]),
+            ..App::default()
+        };
+        app.list.state.select(Some(1));
+        press(&mut app, KeyCode::Char('z'));
+        assert!(app.list.items[1].is_archived);
+        assert_eq!(app.list.state.selected(), Some(2));
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.list.state.selected(), Some(0));
+
+        // Still saved with the rest, just flagged
+        let saved = TodoList::from_json_str(&app.list.serialize_pretty(), Path::new("")).unwrap();
+        assert!(saved.items[1].is_archived);
+
+        press(&mut app, KeyCode::Char('Z'));
+        assert_eq!(app.archive_selection(), Some(0));
+        press(&mut app, KeyCode::Char('r'));
+        assert!(!app.list.items[1].is_archived);
+        assert_eq!(app.list.state.selected(), Some(1));
+        assert!(app.is_dirty());
+    }
+
+    #[test]
+    fn gantt_scrolls_within_the_dated_tasks() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Undated", ""),
+                (Status::Upcoming, "Plan", ""),
+                (Status::Upcoming, "Build", ""),
+            ]),
+            gantt_days: 3,
+            ..App::default()
+        };
+        app.list.items[1].due_date = NaiveDate::from_ymd_opt(2026, 3, 4);
+        app.list.items[2].due_date = NaiveDate::from_ymd_opt(2026, 3, 8);
+        app.list.state.select(Some(2));
+        run_command_line(&mut app, "start 2026-03-09");
+        assert_eq!(app.toast.as_deref(), Some("A task can't start after it's due"));
+        run_command_line(&mut app, "start 2026-03-02");
+        assert_eq!(app.list.items[2].start_date, NaiveDate::from_ymd_opt(2026, 3, 2));
+
+        // Seven days from the 2nd to the 8th, three at a time
+        run_command_line(&mut app, "gantt");
+        for _ in 0..10 {
+            press(&mut app, KeyCode::Char('l'));
+        }
+        assert_eq!(app.gantt_state(), Some((4, 0)));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.list.state.selected(), Some(2));
+    }
+
+    #[test]
+    fn search_history_cycles_like_a_shell() {
+        let mut app = App::default();
+        for query in ["first", "second", "second", ""] {
+            press(&mut app, KeyCode::Char('/'));
+            type_text(&mut app, query);
+            press(&mut app, KeyCode::Enter);
+        }
+        assert_eq!(app.search_field.history(), &["second", "first"]);
+
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Up);
+        assert_eq!(app.search_field.as_str(), "second");
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Up);
+        assert_eq!(app.search_field.as_str(), "first");
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.search_field.as_str(), "");
+    }
+
+    #[test]
+    fn swapping_status_groups_keeps_order_within_groups() {
+        let mut list = TodoList::from_iter([
+            (Status::Completed, "c1", ""),
+            (Status::Upcoming, "u1", ""),
+            (Status::Active, "a1", ""),
+            (Status::Completed, "c2", ""),
+            (Status::Upcoming, "u2", ""),
+            (Status::Active, "a2", ""),
+        ]);
+
+        list.swap_status_group(Status::Completed, Status::Active);
+        let titles: Vec<&str> = list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["u1", "a1", "u2", "a2", "c1", "c2"]);
+
+        list.swap_status_group(Status::Upcoming, Status::Active);
+        let titles: Vec<&str> = list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["a1", "a2", "u1", "u2", "c1", "c2"]);
+    }
+
+    #[test]
+    fn id_index_stays_consistent() {
+        fn assert_indexed(list: &TodoList) {
+            assert_eq!(list.index.len(), list.items.len());
+            for (i, task) in list.items.iter().enumerate() {
+                assert_eq!(list.index[&task.id], i, "{}", task.title);
+                assert_eq!(list.find_by_id(task.id).map(|found| found.id), Some(task.id));
+            }
+        }
+
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "1", ""),
+            (Status::Active, "2", ""),
+            (Status::Completed, "3", ""),
+        ]);
+        assert_indexed(&list);
+
+        list.push(Task::new(Status::Upcoming, "4", ""));
+        list.reorder(3, 0);
+        list.swap(1, 2);
+        assert_indexed(&list);
+
+        let removed = list.remove(1);
+        assert!(list.find_by_id(removed.id).is_none());
+        list.insert(0, removed);
+        list.swap_status_group(Status::Upcoming, Status::Completed);
+        assert_indexed(&list);
+
+        let id = list.items[2].id;
+        list.find_by_id_mut(id).unwrap().title = "renamed".into();
+        assert_eq!(list.items[2].title, "renamed");
+    }
+
+    #[test]
+    fn notifications_stack_and_expire() {
+        let mut app = App {
+            mode: Mode::Focus,
+            ..App::default()
+        };
+        app.notify("first", Duration::from_secs(60));
+        app.notify("second", Duration::ZERO);
+        assert_eq!(app.notification(), Some("second"));
+
+        app.expire_notifications();
+        assert_eq!(app.notification(), Some("first"));
+
+        press(&mut app, KeyCode::Char('x'));
+        assert!(matches!(app.mode, Mode::Focus));
+    }
+
+    #[test]
+    fn paginates_around_the_selection() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "1", ""),
+            (Status::Upcoming, "2", ""),
+            (Status::Upcoming, "3", ""),
+            (Status::Upcoming, "4", ""),
+            (Status::Upcoming, "5", ""),
+        ]);
+        assert_eq!(list.page_count(2), 3);
+        assert_eq!(list.current_page(2), 0);
+
+        list.state.select(Some(4));
+        assert_eq!(list.current_page(2), 2);
+        let titles: Vec<&str> =
+            list.paginate(2, 2).iter().map(|(_, task)| task.title.as_str()).collect();
+        assert_eq!(titles, ["5"]);
+        assert!(list.paginate(3, 2).is_empty());
+    }
+
+    #[test]
+    fn page_keys_jump_a_page_of_rows() {
+        let mut app =
+            App { list: TodoList::from_iter([(Status::Upcoming, "1", ""); 5]), ..App::default() };
+        app.list.items[2].is_archived = true;
+        app.page_size = 2;
+        app.list.state.select(Some(0));
+
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.list.state.selected(), Some(3));
+        press(&mut app, KeyCode::PageDown);
+        press(&mut app, KeyCode::PageDown);
+        assert_eq!(app.list.state.selected(), Some(3));
+        press(&mut app, KeyCode::PageUp);
+        assert_eq!(app.list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn windows_onto_the_shown_rows() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "1", ""),
+            (Status::Upcoming, "2", ""),
+            (Status::Upcoming, "3", ""),
+            (Status::Upcoming, "4", ""),
+            (Status::Upcoming, "5", ""),
+        ]);
+        list.items[1].is_archived = true;
+        assert_eq!(list.shown_count(), 4);
+        assert_eq!(list.selected_row(), None);
+
+        list.state.select(Some(4));
+        assert_eq!(list.selected_row(), Some(3));
+        let titles: Vec<&str> =
+            list.shown_window(2, 2).iter().map(|(_, task)| task.title.as_str()).collect();
+        assert_eq!(titles, ["4", "5"]);
+        assert!(list.shown_window(4, 2).is_empty());
+    }
+
+    #[test]
+    fn lists_tasks_changed_since_the_last_session() {
+        let dir = temp_path("");
+        let tasks_path = dir.join("tasks.json");
+        let last_opened = Utc::now() - chrono::Duration::hours(1);
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "Untouched", ""),
+            (Status::Upcoming, "Changed elsewhere", ""),
+            (Status::Upcoming, "From an old version", ""),
+        ]);
+        list.items[0].last_modified = Some(last_opened - chrono::Duration::hours(1));
+        list.items[2].last_modified = None;
+        list.save_json_file(&tasks_path).unwrap();
+
+        // Pretend the file was last written by this app before the second task changed
+        let contents = fs::read_to_string(&tasks_path).unwrap();
+        let mut raw: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        raw["last_opened"] = serde_json::json!(last_opened);
+        fs::write(&tasks_path, raw.to_string()).unwrap();
+
+        let mut app =
+            App::new(dir.join("config.toml"), dir.join("state.toml"), tasks_path).unwrap();
+        app.poll_loading(true).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(app.mode, Mode::WelcomeBack));
+        assert_eq!(app.changed_since_last_session, vec!["Changed elsewhere"]);
+    }
+
+    #[test]
+    fn list_picker_switches_files_and_reopens_the_last_one() {
+        let dir = temp_path("");
+        let (work, home) = (dir.join("work.json"), dir.join("home.json"));
+        TodoList::from_iter([(Status::Upcoming, "Write report", "")])
+            .save_json_file(&work)
+            .unwrap();
+        TodoList::from_iter([(Status::Upcoming, "Water plants", "")])
+            .save_json_file(&home)
+            .unwrap();
+        let (config_path, state_path) = (dir.join("config.toml"), dir.join("state.toml"));
+        let config = Config { task_files: vec![work.clone(), home.clone()], ..Config::default() };
+        config.save(&config_path).unwrap();
+        // Hand-edited, so nothing but the user should rewrite it
+        let written = format!("# My settings\n{}", fs::read_to_string(&config_path).unwrap());
+        fs::write(&config_path, &written).unwrap();
+
+        let mut app = App::new(config_path.clone(), state_path.clone(), work.clone()).unwrap();
+        app.poll_loading(true).unwrap();
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::CONTROL));
+        assert_eq!(app.list_picker_selection(), Some(0));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.list.items[0].title, "Water plants");
+
+        let mut app = App::new(config_path.clone(), state_path, work.clone()).unwrap();
+        app.poll_loading(true).unwrap();
+        assert_eq!(app.tasks_path(), home);
+        assert_eq!(app.list.items[0].title, "Water plants");
+        assert_eq!(app.state().recent_files, [home.clone(), work.clone()]);
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), written);
+
+        // Launching still works when the state can't be written, here under a file
+        let app = App::new(config_path, work.join("state.toml"), work).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(app.toast.is_some_and(|toast| toast.starts_with("Couldn't remember")));
+    }
+
+    #[test]
+    fn import_wizard_walks_through_each_step() {
+        let path = temp_path(".md");
+        fs::write(&path, "- [x] Pay rent\n- [ ] Call mum\n").unwrap();
+        let mut app = App::default();
+        app.list.push(Task::new(Status::Upcoming, "Pay rent", ""));
+
+        press(&mut app, KeyCode::Char('I'));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        type_text(&mut app, &path.display().to_string());
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.import_wizard().map(WizardStep::number), Some(3));
+        // Back to the path and forward again, which reads the file a second time
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Enter);
+        fs::remove_file(&path).unwrap();
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.import_wizard().is_none());
+        let titles: Vec<&str> = app.list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["Pay rent", "Call mum"]);
+        assert_eq!(app.list.items[0].mode, Status::Completed);
+        assert!(app.is_dirty());
+    }
+
+    #[test]
+    fn archived_tasks_are_left_out_of_every_view() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "First #home", ""),
+                (Status::Upcoming, "Archived #home", ""),
+                (Status::Upcoming, "Second", ""),
+                (Status::Upcoming, "Third", ""),
+            ]),
+            ..App::default()
+        };
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        for task in &mut app.list.items {
+            task.due_date = Some(today);
+        }
+        app.list.items[1].is_archived = true;
+
+        let home: Vec<&str> =
+            app.list.group_by_tag()["home"].iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(home, ["First #home"]);
+        assert_eq!(app.timeline(today), [(0, 0), (0, 2), (0, 3)]);
+        assert_eq!(app.forecast(today)[1], [0, 2, 3]);
+        assert!(!app.to_markdown_checklist().contains("Archived"));
+
+        // Positions count the rows the list shows, so 3 is "Third", not "Second"
+        app.list.state.select(Some(0));
+        run_command_line(&mut app, "depends 3");
+        assert_eq!(app.list.items[0].depends_on, Some(app.list.items[3].id));
+        run_command_line(&mut app, "move 3 1");
+        let titles: Vec<&str> = app.list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["Third", "First #home", "Archived #home", "Second"]);
+    }
+
+    #[test]
+    fn theme_picker_previews_until_a_theme_is_picked() {
+        let config_path = temp_path(".toml");
+        let mut app = App { config_path: config_path.clone(), ..App::default() };
+
+        run_command_line(&mut app, "theme");
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.theme(), Theme::SolarizedDark);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.theme(), Theme::Default);
+
+        run_command_line(&mut app, "theme");
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        let saved = Config::load(&config_path).unwrap();
+        fs::remove_file(&config_path).unwrap();
+        assert_eq!(app.theme(), Theme::Gruvbox);
+        assert!(app.preview_theme.is_none());
+        assert_eq!(saved.theme, Theme::Gruvbox);
+    }
+
+    #[test]
+    fn column_editor_toggles_and_reorders_columns() {
+        let config_path = temp_path(".toml");
+        let mut app = App { config_path: config_path.clone(), ..App::default() };
+        app.list.push(Task::new(Status::Upcoming, "Pay rent", ""));
+        app.list.items[0].due_date = NaiveDate::from_ymd_opt(2026, 11, 1);
+
+        run_command_line(&mut app, "columns");
+        // Status, Title, then the hidden due date: show it and move it to the top
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char('K'));
+        press(&mut app, KeyCode::Char('K'));
+        press(&mut app, KeyCode::Enter);
+        fs::remove_file(&config_path).unwrap();
+
+        let columns = [Column::DueDate, Column::Status, Column::Title];
+        assert_eq!(app.config.visible_columns, columns);
+        assert_eq!(app.list.items[0].list_text(&columns, "_"), " 2026-11-01 _ Pay rent");
+
+        // Tags can lead the row too, and take no room on tasks without any
+        app.list.push(Task::new(Status::Upcoming, "Call mum #family #weekend", ""));
+        let columns = [Column::Tags, Column::Status, Column::Title];
+        assert_eq!(app.list.items[0].list_text(&columns, "_"), " _ Pay rent");
+        assert_eq!(
+            app.list.items[1].list_text(&columns, "_"),
+            " #family #weekend _ Call mum #family #weekend"
+        );
+    }
+
+    #[test]
+    fn groups_tasks_under_each_of_their_tags() {
+        let list = TodoList::from_iter([
+            (Status::Upcoming, "Call the bank #home #money", ""),
+            (Status::Upcoming, "Budget #money #money", ""),
+            (Status::Upcoming, "Stretch", ""),
+        ]);
+        let groups = list.group_by_tag();
+
+        let titles = |tag: &str| -> Vec<&str> {
+            groups[tag].iter().map(|task| task.title.as_str()).collect()
+        };
+        assert_eq!(groups.keys().collect::<Vec<_>>(), ["", "home", "money"]);
+        assert_eq!(titles(""), ["Stretch"]);
+        assert_eq!(titles("home"), ["Call the bank #home #money"]);
+        assert_eq!(titles("money"), ["Call the bank #home #money", "Budget #money #money"]);
+    }
+
+    #[test]
+    fn completed_tasks_sink_in_order() {
+        let mut list = TodoList::from_iter([
+            (Status::Completed, "done 1", ""),
+            (Status::Upcoming, "todo 1", ""),
+            (Status::Completed, "done 2", ""),
+            (Status::Active, "todo 2", ""),
+        ]);
+        list.move_completed_to_bottom();
+
+        let titles: Vec<&str> = list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["todo 1", "todo 2", "done 1", "done 2"]);
+        assert_eq!(list.position_of(list.items[3].id), Some(3));
+    }
+
+    #[test]
+    fn summary_line_skips_blank_lines() {
+        let summary =
+            |info: &str| Task::new(Status::Upcoming, "Task", info).summary_line().to_string();
+        assert_eq!(summary("One line"), "One line");
+        assert_eq!(summary("\n  \n  First real line  \nSecond"), "First real line");
+        assert_eq!(summary(" \n"), "");
+    }
+
+    #[test]
+    fn with_capacity_leaves_room_for_the_tasks() {
+        let mut list = TodoList::with_capacity(1000);
+        assert!(list.items.is_empty() && list.items.capacity() >= 1000);
+        let task = Task::new(Status::Upcoming, "First", "");
+        let id = task.id;
+        list.push(task);
+        assert_eq!(list.position_of(id), Some(0));
+    }
+
+    #[test]
+    #[ignore = "a benchmark, run with `cargo test --release -- --ignored --nocapture`"]
+    fn sized_imports_are_no_slower_than_growing_the_list() {
+        use std::time::{Duration, Instant};
+
+        let tasks: Vec<Task> = (0..1000)
+            .map(|i| Task::new(Status::Upcoming, &format!("Task {i}"), ""))
+            .collect();
+        let time_imports = |import: &dyn Fn(Vec<Task>) -> TodoList| {
+            let started = Instant::now();
+            for _ in 0..200 {
+                assert_eq!(import(tasks.clone()).items.len(), 1000);
+            }
+            started.elapsed() / 200
+        };
+
+        let sized = time_imports(&TodoList::from);
+        let grown = time_imports(&|tasks| {
+            let mut list = TodoList::from(vec![]);
+            for task in tasks {
+                list.push(task);
+            }
+            list
+        });
+        println!("1000 tasks: {sized:?} sized up front, {grown:?} grown one at a time");
+        assert!(sized < grown + Duration::from_micros(50));
+    }
+
+    #[test]
+    fn iter_selected_yields_the_selected_tasks_in_order() {
+        let mut list = TodoList::from_iter(
+            ["a", "b", "c", "d"].map(|title| (Status::Upcoming, title, "")),
+        );
+        let titles = |list: &TodoList, selection: &[usize]| -> Vec<String> {
+            let selection = selection.iter().copied().collect();
+            list.iter_selected(&selection).map(|task| task.title.clone()).collect()
+        };
+
+        assert!(titles(&list, &[]).is_empty());
+        assert_eq!(titles(&list, &[3, 2, 1, 0]), ["a", "b", "c", "d"]);
+        assert_eq!(titles(&list, &[3, 1, 9]), ["b", "d"]);
+
+        let selection = BTreeSet::from([0, 2]);
+        for task in list.iter_selected_mut(&selection) {
+            task.mode = Status::Completed;
+        }
+        let modes: Vec<Status> = list.items.iter().map(|task| task.mode).collect();
+        assert_eq!(
+            modes,
+            [Status::Completed, Status::Upcoming, Status::Completed, Status::Upcoming]
+        );
+    }
+
+    #[test]
+    fn w_picks_and_highlights_an_active_task() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Later", ""),
+                (Status::Active, "Now", ""),
+                (Status::Completed, "Done", ""),
+            ]),
+            ..App::default()
+        };
+        for _ in 0..10 {
+            assert_eq!(app.list.random_active().unwrap().title, "Now");
+        }
+
+        press(&mut app, KeyCode::Char('w'));
+        assert_eq!(app.list.state.selected(), Some(1));
+        assert_eq!(app.nudged_task(), Some(app.list.items[1].id));
+
+        app.list.items[1].mode = Status::Completed;
+        assert!(app.list.random_active().is_none());
+        press(&mut app, KeyCode::Char('w'));
+        assert_eq!(app.toast.as_deref(), Some("No active tasks to pick from"));
+    }
+
+    #[test]
+    fn completing_a_task_sinks_it_when_configured() {
+        let mut app = App {
+            config: Config { auto_sink_completed: true, ..Config::default() },
+            ..App::default()
+        };
+        app.list = TodoList::from_iter([
+            (Status::Active, "first", ""),
+            (Status::Upcoming, "second", ""),
+        ]);
+        app.list.state.select(Some(0));
+
+        press(&mut app, KeyCode::Char('t'));
+        let titles: Vec<&str> = app.list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["second", "first"]);
+        assert_eq!(app.list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn searching_with_fields_matches_clauses() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Buy milk", "status:active"),
+                (Status::Active, "Write report", ""),
+            ]),
+            ..App::default()
+        };
+
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "status:active");
+        assert_eq!(app.prompt(), Some(("filter ", "status:active")));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.list.state.selected(), Some(1));
+
+        press(&mut app, KeyCode::Char('/'));
+        type_text(&mut app, "priority:high");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.toast.as_deref(), Some("Unknown field \"priority\""));
+    }
+
+    #[test]
+    fn minimap_moves_by_rows_and_jumps_to_clicked_tasks() {
+        let mut app = App {
+            list: (0..100).map(|_| (Status::Upcoming, "task", "")).collect(),
+            minimap_grid: Rect::new(2, 3, 10, 5),
+            ..App::default()
+        };
+
+        run_command_line(&mut app, "minimap");
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.minimap_cursor(), Some(11));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.list.state.selected(), Some(11));
+
+        app.mode = Mode::Minimap { cursor: 0 };
+        app.minimap_scroll = 2;
+        app.handle_minimap_mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 5,
+            row: 4,
+            modifiers: KeyModifiers::NONE,
+        });
+        // Row 1 on screen is row 3 of the grid, plus 3 cells in
+        assert_eq!(app.list.state.selected(), Some(33));
+        assert!(app.minimap_cursor().is_none());
+    }
+
+    #[test]
+    fn timeline_selects_tasks_by_due_date_and_jumps_to_them() {
+        let today = Local::now().date_naive();
+        let due = |days: i64| Some(today + chrono::Duration::days(days));
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Next week", ""),
+                (Status::Upcoming, "No date", ""),
+                (Status::Upcoming, "Yesterday", ""),
+                (Status::Upcoming, "Today", ""),
+            ]),
+            timeline_days: 3,
+            ..App::default()
+        };
+        app.list.items[0].due_date = due(7);
+        app.list.items[2].due_date = due(-1);
+        app.list.items[3].due_date = due(0);
+        assert_eq!(app.timeline(today), [(-1, 2), (0, 3), (7, 0)]);
+
+        run_command_line(&mut app, "timeline");
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.timeline_state(), Some((1, 0)));
+
+        // Selecting next week's task scrolls it into view, then back to today's
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.timeline_state(), Some((5, 2)));
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.timeline_state(), Some((0, 1)));
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.timeline_state().is_none());
+        assert_eq!(app.list.state.selected(), Some(3));
+    }
+
+    #[test]
+    fn forecast_moves_between_days_and_jumps_to_tasks() {
+        let today = Local::now().date_naive();
+        let due = |days: i64| Some(today + chrono::Duration::days(days));
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Past due", ""),
+                (Status::Upcoming, "Tomorrow", ""),
+                (Status::Upcoming, "Also tomorrow", ""),
+                (Status::Upcoming, "Next month", ""),
+            ]),
+            ..App::default()
+        };
+        app.list.items[0].due_date = due(-3);
+        app.list.items[1].due_date = due(1);
+        app.list.items[2].due_date = due(1);
+        app.list.items[3].due_date = due(30);
+        let forecast = app.forecast(today);
+        assert_eq!(forecast[0], [0]);
+        assert_eq!(forecast[2], [1, 2]);
+        assert_eq!(forecast.iter().map(Vec::len).sum::<usize>(), 3);
+
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL));
+        assert_eq!(app.forecast_cell(), Some((1, 0)));
+        press(&mut app, KeyCode::Char('l'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.forecast_cell(), Some((2, 1)));
+        press(&mut app, KeyCode::Char('h'));
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(app.forecast_cell(), Some((0, 0)));
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.forecast_cell().is_none());
+        assert_eq!(app.list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn quick_entry_quits_after_adding_the_task() {
+        let mut app = App::default();
+        app.start_quick_entry("Call mum");
+        type_text(&mut app, " back");
+        for _ in 0..4 {
+            press(&mut app, KeyCode::Enter);
+        }
+
+        assert!(app.exit);
+        assert_eq!(app.list.items.len(), 1);
+        assert_eq!(app.list.items[0].title, "Call mum back");
+    }
+
+    #[test]
+    fn edit_form_puts_the_terminal_cursor_in_the_focused_field() {
+        let mut app = App::default();
+        app.start_quick_entry("Call mum");
+        app.title_field.move_home();
+        app.title_field.move_right();
+
+        let backend = ratatui::backend::TestBackend::new(60, 20);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let position = app.edit_cursor.expect("the title field has a cursor");
+        assert_eq!(terminal.backend().buffer()[position].symbol(), "a");
+        terminal.backend_mut().assert_cursor_position(position);
+
+        app.mode = Mode::View;
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        assert_eq!(app.edit_cursor, None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn ctrl_d_toggles_the_debug_overlay_without_deleting() {
+        let mut app = App {
+            list: TodoList::from_iter([(Status::Upcoming, "Keep me", "")]),
+            ..App::default()
+        };
+        app.list.state.select(Some(0));
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+
+        app.handle_key_events(ctrl_d);
+        assert!(app.debug_overlay);
+        assert_eq!(app.list.items.len(), 1);
+        app.handle_key_events(ctrl_d);
+        assert!(!app.debug_overlay);
+    }
+
+    #[test]
+    fn url_fields_wont_move_on_until_they_parse() {
+        let mut app = App::default();
+        app.start_quick_entry("Read the docs");
+        press(&mut app, KeyCode::Enter);
+        type_text(&mut app, "docs.rs");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.currently_editing, CurrentlyEditing::Url);
+        assert_eq!(app.url_error(), Some("⚠ Not a valid URL"));
+
+        app.url_field.set("https://docs.rs");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.currently_editing, CurrentlyEditing::DueDate);
+        assert_eq!(app.url_error(), None);
+    }
+
+    #[test]
+    fn split_view_sends_tasks_between_the_list_and_a_project() {
+        let dir = temp_path("");
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Stay", ""),
+                (Status::Upcoming, "Go", ""),
+            ]),
+            storage: StorageBackend::File(dir.join("tasks.json")),
+            ..App::default()
+        };
+        app.list.state.select(Some(1));
+
+        press(&mut app, KeyCode::Char('\\'));
+        assert_eq!(app.toast.as_deref(), Some("No projects yet, open one with :split <name>"));
+        run_command_line(&mut app, "split work");
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.toast.as_deref(), Some("Moved task to work"));
+
+        let saved = TodoList::from_json_file(&dir.join("projects/work.json")).unwrap();
+        assert_eq!(saved.items[0].title, "Go");
+        assert_eq!(app.list.items.len(), 1);
+
+        // And back again from the project's side
+        press(&mut app, KeyCode::Char('l'));
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.list.items.len(), 2);
+        let (_, name, project, focus_right) = app.split_panes().unwrap();
+        assert_eq!((name, project.items.len(), focus_right), ("work", 0, true));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn pastes_into_the_field_or_as_tasks() {
+        let mut app = App::default();
+        app.handle_paste("Buy milk\r\n\r\nCall mum\n");
+        let titles: Vec<&str> = app.list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["Buy milk", "Call mum"]);
+
+        press(&mut app, KeyCode::Char('n'));
+        app.handle_paste("Write\nthe report");
+        assert_eq!(app.title_field.as_str(), "Write the report");
+        assert_eq!(app.list.items.len(), 2);
+
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::ALT));
+        assert_eq!(app.config.paste_mode, PasteMode::MultiTask);
+        // Out of the notification it shows, back to the form
+        press(&mut app, KeyCode::Esc);
+        app.title_field.set(String::new());
+        app.handle_paste("One line stays in the field");
+        assert_eq!(app.title_field.as_str(), "One line stays in the field");
+        app.handle_paste("Plan\nShip");
+        assert_eq!(app.list.items.len(), 4);
+    }
+
+    #[test]
+    fn pasted_tasks_come_back_out_in_one_undo_step() {
+        let mut app = App {
+            list: TodoList::from_iter([(Status::Upcoming, "Keep", "")]),
+            ..App::default()
+        };
+        app.add_clipboard_task("  Fix the login bug \n");
+        app.handle_paste("Buy milk\nCall mum");
+        assert_eq!(app.list.items.len(), 4);
+
+        assert_eq!(app.undo(), "Undone");
+        let titles: Vec<&str> = app.list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["Keep", "Fix the login bug"]);
+        assert_eq!(app.undo(), "Undone");
+        assert_eq!(app.list.items.len(), 1);
+
+        // Nothing is added, or left to undo, for text that isn't a title
+        app.add_clipboard_task("   ");
+        assert_eq!(app.undo(), "Nothing to undo");
+    }
+
+    #[test]
+    fn tag_manager_renames_merges_and_removes() {
+        let mut app = App {
+            list: TodoList::from_iter([
+                (Status::Upcoming, "Milk #shop", ""),
+                (Status::Upcoming, "Bread #food #shop", ""),
+            ]),
+            ..App::default()
+        };
+        let titles = |app: &App| -> Vec<String> {
+            app.list.items.iter().map(|task| task.title.clone()).collect()
+        };
+        run_command_line(&mut app, "tags");
+        assert_eq!(app.tag_manager_selection(), Some(0));
+
+        // Onto #shop, renamed to #errands
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.prompt(), Some(("rename to #", "shop")));
+        for _ in 0..4 {
+            press(&mut app, KeyCode::Backspace);
+        }
+        type_text(&mut app, "errands");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(titles(&app), ["Milk #errands", "Bread #food #errands"]);
+        assert_eq!(app.prompt(), None);
+
+        // Still on the second tag, now #food after #errands
+        press(&mut app, KeyCode::Char('m'));
+        type_text(&mut app, "errands");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(titles(&app), ["Milk #errands", "Bread #errands"]);
+
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(titles(&app), ["Milk", "Bread"]);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.tag_manager_selection(), None);
+    }
+
+    #[test]
+    fn read_only_refuses_changes_until_unlocked() {
+        let mut app = App {
+            list: TodoList::from_iter([(Status::Upcoming, "Review me", "")]),
+            ..App::default()
+        };
+        app.list.state.select(Some(0));
+
+        run_command_line(&mut app, "lock");
+        press(&mut app, KeyCode::Char('t'));
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('n'));
+        run_command_line(&mut app, "clean");
+        app.handle_paste("Sneaked in");
+        assert_eq!(app.list.items.len(), 1);
+        assert_eq!(app.list.items[0].mode, Status::Upcoming);
+        assert_eq!(app.mode_name(), "view");
+
+        // Looking around still works
+        press(&mut app, KeyCode::Char('/'));
+        assert_eq!(app.mode_name(), "search");
+        press(&mut app, KeyCode::Esc);
+
+        run_command_line(&mut app, "unlock");
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.list.items.is_empty());
+    }
+
+    #[test]
+    fn ctrl_enter_saves_and_starts_another() {
+        let mut app = App::default();
+        let ctrl_enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL);
+        press(&mut app, KeyCode::Char('n'));
+        type_text(&mut app, "First");
+        press(&mut app, KeyCode::Tab);
+        app.handle_key_events(ctrl_enter);
+        assert_eq!(app.mode_name(), "edit");
+        assert_eq!(app.currently_editing, CurrentlyEditing::Title);
+        assert_eq!(app.title_field.as_str(), "");
+
+        type_text(&mut app, "Second");
+        app.handle_key_events(ctrl_enter);
+        // A blank title isn't saved
+        app.handle_key_events(ctrl_enter);
+        let titles: Vec<&str> = app.list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["First", "Second"]);
+        assert_eq!(app.mode_name(), "edit");
+
+        // Each saved task is undone on its own
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('u'));
+        let titles: Vec<&str> = app.list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["First"]);
+        press(&mut app, KeyCode::Char('u'));
+        assert!(app.list.items.is_empty());
+    }
+
+    #[test]
+    fn clone_as_new_resets_status_and_id() {
+        let mut task = Task::new(Status::Completed, "Water plants", "the ones upstairs");
+        task.url = Some("https://example.com".into());
+        task.due_date = NaiveDate::from_ymd_opt(2024, 2, 1);
+        task.depends_on = Some(Uuid::new_v4());
+        task.last_modified = None;
+
+        let copy = task.clone_as_new();
+        assert_ne!(copy.id, task.id);
+        assert_eq!(copy.mode, Status::Upcoming);
+        assert!(copy.last_modified.is_some());
+        assert_eq!(
+            (&copy.title, &copy.info, &copy.url, copy.due_date, copy.depends_on),
+            (&task.title, &task.info, &task.url, task.due_date, task.depends_on)
+        );
+    }
+
+    #[test]
+    fn duplicating_a_task_keeps_what_it_depends_on() {
+        let first = Task::new(Status::Completed, "Buy paint", "");
+        let mut second = Task::new(Status::Active, "Paint the fence", "");
+        second.depends_on = Some(first.id);
+        let (first_id, second_id) = (first.id, second.id);
+        let mut app = App { list: TodoList::from(vec![first, second]), ..App::default() };
+        app.list.state.select(Some(1));
+
+        press(&mut app, KeyCode::Char('D'));
+        assert_eq!(app.list.items.len(), 3);
+        assert_eq!(app.list.state.selected(), Some(2));
+        let copy = &app.list.items[2];
+        assert_ne!(copy.id, second_id);
+        assert_eq!(copy.title, "Paint the fence");
+        // The copy waits on the same task, and nothing is made to wait on the copy
+        assert_eq!(copy.depends_on, Some(first_id));
+        assert_eq!(app.list.items[1].depends_on, Some(first_id));
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.list.items.len(), 2);
+    }
+}
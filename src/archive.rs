@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use crate::app::{App, TodoList};
+
+impl TodoList {
+    /// Positions in `items` of the archived tasks, which the archive view lists
+    pub fn archived(&self) -> Vec<usize> {
+        (0..self.items.len())
+            .filter(|&i| self.items[i].is_archived)
+            .collect()
+    }
+
+    /// The position in `items` of the task on the list's `row`, counting from 0, for commands
+    /// that take positions as the list shows them
+    pub fn index_of_row(&self, row: usize) -> Option<usize> {
+        self.shown_rows().get(row).map(|&(_, i)| i)
+    }
+
+    /// Moves the selection to the next or previous row of the list, staying put at either end
+    pub fn select_shown(&mut self, forward: bool) {
+        let rows = self.shown_rows();
+        let Some(i) = self.state.selected() else {
+            return self.select_shown_end(!forward);
+        };
+        let Some(row) = rows.iter().position(|&(_, row)| row == i) else {
+            return self.snap_selection();
+        };
+        let next = if forward {
+            row.checked_add(1)
+        } else {
+            row.checked_sub(1)
+        };
+        if let Some(&(_, next)) = next.and_then(|row| rows.get(row)) {
+            self.state.select(Some(next));
+        }
+    }
+
+    /// Selects the task on the first or last row of the list
+    pub fn select_shown_end(&mut self, last: bool) {
+        let rows = self.shown_rows();
+        let end = if last { rows.last() } else { rows.first() };
+        self.state.select(end.map(|&(_, i)| i));
+    }
+
+    /// Moves a selection the list doesn't show to where it would be. That's the collapsed task
+    /// a subtask is tucked away under, or for archived tasks and selections past the end, the
+    /// nearest shown task after it, or before it when there's none after
+    pub fn snap_selection(&mut self) {
+        let Some(i) = self.state.selected() else {
+            return;
+        };
+        let shown: HashSet<usize> = self.shown_rows().into_iter().map(|(_, i)| i).collect();
+        if shown.contains(&i) {
+            return;
+        }
+        let mut ancestor = None;
+        let mut seen = HashSet::new();
+        let mut parent = self.items.get(i).and_then(|task| task.parent);
+        while let Some(j) = parent.and_then(|id| self.position_of(id)) {
+            if !seen.insert(j) || shown.contains(&j) {
+                ancestor = Some(j).filter(|j| shown.contains(j));
+                break;
+            }
+            parent = self.items[j].parent;
+        }
+        let after = (i..self.items.len()).find(|i| shown.contains(i));
+        let before = (0..i.min(self.items.len()))
+            .rev()
+            .find(|i| shown.contains(i));
+        self.state.select(ancestor.or(after).or(before));
+    }
+}
+
+impl App {
+    /// Moves the selected task out of the list and into the archive
+    pub fn archive_selected(&mut self) -> Option<String> {
+        let i = self.list.state.selected()?;
+        let task = &mut self.list.items[i];
+        task.is_archived = true;
+        task.touch();
+        let message = format!("Archived \"{}\", Z to see the archive", task.title);
+        self.list.snap_selection();
+        self.mark_dirty();
+        Some(message)
+    }
+
+    /// Puts the task at `i` in `items` back in the list, selecting it there
+    pub fn restore_archived(&mut self, i: usize) -> String {
+        let task = &mut self.list.items[i];
+        task.is_archived = false;
+        task.touch();
+        let message = format!("Restored \"{}\"", task.title);
+        self.list.state.select(Some(i));
+        self.mark_dirty();
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::{Status, TodoList};
+
+    #[test]
+    fn selection_skips_archived_tasks() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "First", ""),
+            (Status::Upcoming, "Archived", ""),
+            (Status::Upcoming, "Third", ""),
+            (Status::Upcoming, "Also archived", ""),
+        ]);
+        list.items[1].is_archived = true;
+        list.items[3].is_archived = true;
+        assert_eq!(list.archived(), [1, 3]);
+
+        list.select_shown(true);
+        assert_eq!(list.state.selected(), Some(0));
+        list.select_shown(true);
+        assert_eq!(list.state.selected(), Some(2));
+        list.select_shown(true);
+        assert_eq!(list.state.selected(), Some(2));
+        list.select_shown(false);
+        assert_eq!(list.state.selected(), Some(0));
+        list.select_shown_end(true);
+        assert_eq!(list.state.selected(), Some(2));
+
+        list.state.select(Some(3));
+        list.snap_selection();
+        assert_eq!(list.state.selected(), Some(2));
+        let shown: Vec<usize> = list.shown_window(0, 10).iter().map(|&(i, _)| i).collect();
+        assert_eq!(shown, [0, 2]);
+        assert_eq!(list.selected_row(), Some(1));
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+
+use crate::{
+    app::{App, DUE_DATE_FORMAT, Status, Task, TitleError},
+    undo::UndoAction,
+};
+
+impl App {
+    /// Gives every task at `indices` the same status, as one undo step. Returns how many
+    /// were changed, which leaves out tasks blocked by an unfinished dependency the way
+    /// toggling them one at a time would
+    pub fn bulk_set_status(&mut self, status: Status, indices: &[usize]) -> usize {
+        let selection: BTreeSet<usize> = indices.iter().copied().collect();
+        let previous: Vec<Task> = self.list.iter_selected(&selection).cloned().collect();
+        let mut changed = 0;
+        let len = self.list.items.len();
+        for &i in selection.iter().filter(|&&i| i < len) {
+            if self.list.items[i].mode != status && self.set_status(i, status) {
+                changed += 1;
+            }
+        }
+        self.push_bulk_undo(previous, changed);
+        if changed > 0 && status == Status::Completed && self.config().auto_sink_completed {
+            self.list.move_completed_to_bottom();
+        }
+        changed
+    }
+
+    /// Gives every task at `indices` the same due date, or clears it, as one undo step. Tasks
+    /// that would then be due before they start are left alone
+    pub fn bulk_set_due_date(&mut self, due_date: Option<NaiveDate>, indices: &[usize]) -> usize {
+        self.bulk_edit(indices, |task| {
+            if let (Some(start), Some(due)) = (task.start_date, due_date)
+                && start > due
+            {
+                return false;
+            }
+            task.due_date = due_date;
+            true
+        })
+    }
+
+    /// Adds `#tag` to the titles of the tasks at `indices` that don't have it yet, as one undo
+    /// step. Titles it would make invalid, too long say, are left alone
+    pub fn bulk_set_tag(&mut self, tag: &str, indices: &[usize]) -> usize {
+        self.bulk_edit(indices, |task| {
+            let title = format!("{} #{tag}", task.title);
+            if task.tags().contains(tag) || TitleError::check(&title).is_some() {
+                return false;
+            }
+            task.title = title;
+            true
+        })
+    }
+
+    /// Applies `edit` to each task at `indices`, which says whether it changed the task, then
+    /// pushes the tasks as they were before as a single undo step
+    fn bulk_edit(&mut self, indices: &[usize], mut edit: impl FnMut(&mut Task) -> bool) -> usize {
+        let selection: BTreeSet<usize> = indices.iter().copied().collect();
+        let previous: Vec<Task> = self.list.iter_selected(&selection).cloned().collect();
+        let mut changed = 0;
+        for task in self.list.iter_selected_mut(&selection) {
+            if edit(task) {
+                task.touch();
+                changed += 1;
+            }
+        }
+        self.push_bulk_undo(previous, changed);
+        changed
+    }
+
+    /// Puts `previous`, the tasks as they were before a bulk edit, on the undo stack as one
+    /// step, unless the edit didn't change any of them
+    fn push_bulk_undo(&mut self, previous: Vec<Task>, changed: usize) {
+        if changed > 0 {
+            self.push_undo(UndoAction::Restore(previous));
+            self.mark_dirty();
+        }
+    }
+
+    /// `:bulk-status`, `:bulk-due` and `:bulk-tag`, with `rows` as list positions like
+    /// `1,3-5`
+    pub fn run_bulk(&mut self, command: &str, value: &str, rows: &str) -> String {
+        let Some(indices) = self.parse_rows(rows) else {
+            return format!(
+                "Rows must be like 1,3-5, between 1 and {}",
+                self.list.shown_count()
+            );
+        };
+        let changed = match command {
+            "bulk-status" => match Status::from_name(value) {
+                Some(status) => self.bulk_set_status(status, &indices),
+                None => return format!("Unknown status \"{value}\""),
+            },
+            "bulk-due" if value == "none" => self.bulk_set_due_date(None, &indices),
+            "bulk-due" => match NaiveDate::parse_from_str(value, DUE_DATE_FORMAT) {
+                Ok(date) => self.bulk_set_due_date(Some(date), &indices),
+                Err(_) => return "Due date must be YYYY-MM-DD or none".into(),
+            },
+            _ => self.bulk_set_tag(value.trim_start_matches('#'), &indices),
+        };
+        match indices.len() - changed {
+            0 => format!("Changed {changed} tasks"),
+            skipped => format!("Changed {changed} tasks, {skipped} left as they were"),
+        }
+    }
+
+    /// The positions in `items` of the list rows in `spec`, counting from 1, e.g. `1,3-5`.
+    /// Rows given more than once are only counted once
+    fn parse_rows(&self, spec: &str) -> Option<Vec<usize>> {
+        let mut indices = vec![];
+        for part in spec.split(',') {
+            let (first, last) = part.split_once('-').unwrap_or((part, part));
+            let (first, last) = (first.parse::<usize>().ok()?, last.parse::<usize>().ok()?);
+            if first == 0 || last < first {
+                return None;
+            }
+            for row in first..=last {
+                indices.push(self.list.index_of_row(row - 1)?);
+            }
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        Some(indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::{App, Status, Task},
+        testing::app_with,
+    };
+    use chrono::NaiveDate;
+
+    fn app_titled(titles: [&str; 4]) -> App {
+        app_with(
+            titles
+                .map(|title| Task::new(Status::Upcoming, title, ""))
+                .into(),
+        )
+    }
+
+    #[test]
+    fn bulk_edits_undo_in_one_step() {
+        let mut app = app_titled(["a", "b", "c", "d"]);
+        let date = NaiveDate::from_ymd_opt(2026, 5, 1);
+
+        assert_eq!(app.bulk_set_status(Status::Completed, &[0, 2]), 2);
+        assert_eq!(app.bulk_set_due_date(date, &[1, 2, 3]), 3);
+        let modes: Vec<Status> = app.list.items.iter().map(|task| task.mode).collect();
+        assert_eq!(
+            modes,
+            [
+                Status::Completed,
+                Status::Upcoming,
+                Status::Completed,
+                Status::Upcoming
+            ]
+        );
+
+        app.undo();
+        assert!(app.list.items.iter().all(|task| task.due_date.is_none()));
+        assert_eq!(app.list.items[2].mode, Status::Completed);
+        app.undo();
+        assert!(
+            app.list
+                .items
+                .iter()
+                .all(|task| task.mode == Status::Upcoming)
+        );
+        assert_eq!(app.undo(), "Nothing to undo");
+    }
+
+    #[test]
+    fn bulk_status_goes_through_the_same_checks_as_toggling() {
+        let mut app = app_titled(["a", "b", "c", "d"]);
+        app.list.items[1].depends_on = Some(app.list.items[3].id);
+        app.config_mut().auto_sink_completed = true;
+
+        // The blocked second task is left alone, and the repeated row only counts once
+        assert_eq!(
+            app.run_bulk("bulk-status", "completed", "1,1,2-3"),
+            "Changed 2 tasks, 1 left as they were"
+        );
+        let titles: Vec<&str> = app
+            .list
+            .items
+            .iter()
+            .map(|task| task.title.as_str())
+            .collect();
+        assert_eq!(titles, ["b", "d", "a", "c"]);
+        assert_eq!(app.status_changes.len(), 2);
+        assert!(app.status_changes.contains_key(&app.list.items[2].id));
+    }
+
+    #[test]
+    fn bulk_tags_skip_tasks_already_tagged() {
+        let mut app = app_titled(["a #home", "b", "c", "d"]);
+        app.list.items[3].is_archived = true;
+
+        assert_eq!(
+            app.run_bulk("bulk-tag", "#home", "1-3"),
+            "Changed 2 tasks, 1 left as they were"
+        );
+        let titles: Vec<&str> = app
+            .list
+            .items
+            .iter()
+            .map(|task| task.title.as_str())
+            .collect();
+        assert_eq!(titles, ["a #home", "b #home", "c #home", "d"]);
+        // The fourth row would be past the end, the archived task having none
+        assert!(
+            app.run_bulk("bulk-status", "active", "1,4")
+                .starts_with("Rows must be")
+        );
+        assert_eq!(
+            app.run_bulk("bulk-due", "2026-13-01", "1"),
+            "Due date must be YYYY-MM-DD or none"
+        );
+    }
+}
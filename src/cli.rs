@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: ratatodo [OPTIONS]
+
+Options:
+  --import <FILE>  Merge the tasks in FILE into the tasks file and exit
+  --dedup          With --import, skip tasks whose title is already in the list
+  --batch          Add a task for each line read from stdin, then start as usual
+  --infer-status   With --batch, mark tasks whose title says they're done as completed
+  --dump           Print the tasks as a tree and exit
+  --quick [TITLE]  Open straight into a new task, titled TITLE, and exit once it's added
+  --read-only      Start with the tasks locked against changes, :unlock lifts it
+  -h, --help       Print this help";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    pub import: Option<PathBuf>,
+    pub dedup: bool,
+    pub batch: bool,
+    pub infer_status: bool,
+    pub dump: bool,
+    pub quick: bool,
+    pub quick_title: Option<String>,
+    pub read_only: bool,
+    pub help: bool,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--import" => match args.next() {
+                    Some(path) => parsed.import = Some(PathBuf::from(path)),
+                    None => return Err("--import needs a file to import".into()),
+                },
+                "--dedup" => parsed.dedup = true,
+                "--batch" => parsed.batch = true,
+                "--infer-status" => parsed.infer_status = true,
+                "--dump" => parsed.dump = true,
+                "--quick" => {
+                    parsed.quick = true;
+                    parsed.quick_title = args.next_if(|arg| !arg.starts_with('-'));
+                }
+                "--read-only" => parsed.read_only = true,
+                "-h" | "--help" => parsed.help = true,
+                _ => return Err(format!("unknown argument: {arg}")),
+            }
+        }
+
+        if parsed.dedup && parsed.import.is_none() {
+            return Err("--dedup only applies to --import".into());
+        }
+        if parsed.infer_status && !parsed.batch {
+            return Err("--infer-status only applies to --batch".into());
+        }
+        if parsed.batch && parsed.import.is_some() {
+            return Err("--batch can't be combined with --import".into());
+        }
+        if parsed.quick && (parsed.import.is_some() || parsed.batch || parsed.dump) {
+            return Err("--quick can't be combined with --import, --batch or --dump".into());
+        }
+        if parsed.read_only && (parsed.import.is_some() || parsed.batch || parsed.quick) {
+            return Err("--read-only can't be combined with --import, --batch or --quick".into());
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_no_arguments() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn parses_import_with_dedup() {
+        let args = parse(&["--dedup", "--import", "other.json"]).unwrap();
+        assert_eq!(args.import, Some(PathBuf::from("other.json")));
+        assert!(args.dedup);
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["--import"]).is_err());
+        assert!(parse(&["--dedup"]).is_err());
+        assert!(parse(&["--infer-status"]).is_err());
+        assert!(parse(&["--frobnicate"]).is_err());
+        assert!(parse(&["--batch", "--import", "other.json"]).is_err());
+        assert!(parse(&["--quick", "Call mum", "--batch"]).is_err());
+        assert!(parse(&["--read-only", "--quick"]).is_err());
+    }
+
+    #[test]
+    fn parses_quick_with_and_without_a_title() {
+        let args = parse(&["--quick", "Call mum"]).unwrap();
+        assert!(args.quick);
+        assert_eq!(args.quick_title.as_deref(), Some("Call mum"));
+
+        let args = parse(&["--quick", "--help"]).unwrap();
+        assert!(args.quick && args.help);
+        assert_eq!(args.quick_title, None);
+    }
+}
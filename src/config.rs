@@ -0,0 +1,563 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use ratatui::widgets::HighlightSpacing;
+use serde::{Deserialize, Serialize};
+
+use crate::{app::Status, theme::Theme};
+
+const APP_NAME: &str = "ratatodo";
+/// The shortest tick the config file can ask for. A 0 would have the event loop spin
+const MIN_TICK_RATE: Duration = Duration::from_millis(16);
+
+/// The comment written above each key in the config file, so it documents itself
+const KEY_COMMENTS: &[(&str, &str)] = &[
+    (
+        "first_launch",
+        "Show the welcome screen when there are no tasks yet. Options: true, false",
+    ),
+    (
+        "auto_save_interval",
+        "Seconds between saves of unsaved changes, 0 to only save on exit",
+    ),
+    (
+        "tick_rate",
+        "Milliseconds between redraws while nothing else happens, for the animations. At least 16",
+    ),
+    (
+        "status_cycle_direction",
+        "Which status toggling a task moves it to next. Options: \"full\" (upcoming, active,\n\
+         completed), \"two-state\" (upcoming, completed), \"reverse\" (completed, active, upcoming)",
+    ),
+    (
+        "wrap_text_in_list",
+        "Wrap long titles onto extra lines in the list. Options: true, false",
+    ),
+    (
+        "highlight_spacing",
+        "Whether the list keeps a column free for the > cursor.\n\
+         Options: \"always\", \"when_selected\", \"never\"",
+    ),
+    (
+        "render_density",
+        "Show the start of each task's info under its title. Options: true, false",
+    ),
+    (
+        "progress_bars_in_list",
+        "Show how many of a task's subtasks are done as a small bar after it. Options: true, false",
+    ),
+    (
+        "auto_sink_completed",
+        "Move tasks to the bottom of the list once they're completed. Options: true, false",
+    ),
+    (
+        "default_status",
+        "Default status for new tasks. Options: \"upcoming\", \"active\", \"completed\"",
+    ),
+    (
+        "preview_format",
+        "Which export the edit form's preview (F3) shows.\n\
+         Options: \"markdown\", \"todo-txt\", \"ical\", \"json\", \"org\"",
+    ),
+    (
+        "visible_columns",
+        "What each row in the list shows, in order.\n\
+         Options: \"status\", \"title\", \"due-date\", \"tags\"",
+    ),
+    (
+        "task_files",
+        "Tasks files to switch between with Ctrl+O, e.g. [\"/path/to/work.json\"]",
+    ),
+    (
+        "notes_storage",
+        "Where each task's info is kept. Options: \"inline\" in the tasks file, or\n\
+         { files = \"/path/to/dir\" } for a Markdown file per task",
+    ),
+    (
+        "status_bar_format",
+        "What the bottom right of the list shows. {mode}, {selected}, {total} and\n\
+         {completed} (a percentage) are filled in, anything else is shown as written",
+    ),
+    (
+        "sync_mode",
+        "Pick up changes another ratatodo makes to the same tasks file.\n\
+         Options: \"off\", \"file-watch\" (merged task by task, the latest edit wins)",
+    ),
+    (
+        "theme",
+        "Colours to draw with, also picked with :theme.\n\
+         Options: \"default\", \"solarized-dark\", \"gruvbox\", \"nord\", \"monochrome\"",
+    ),
+    (
+        "paste_mode",
+        "What pasting several lines of text does, Alt+P switches while running.\n\
+         Options: \"literal\" into the field being typed in, \"multi-task\" for a task per line",
+    ),
+    (
+        "field_format",
+        "What each edit form field holds.\n\
+         Options: \"plain-text\", \"markdown\", \"url\" (checked), \"date\" (+/- to change)",
+    ),
+    (
+        "storage",
+        "Where tasks are kept, tasks.json in the data directory when unset.\n\
+         Options: file = \"/path/to/tasks.json\" or sqlite = \"/path/to/tasks.db\"",
+    ),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub first_launch: bool,
+    /// How often unsaved changes are written, `None` (0 in the file) to only save on exit
+    #[serde(with = "duration_secs")]
+    pub auto_save_interval: Option<Duration>,
+    /// How often the UI wakes up without input to animate, in milliseconds in the file. Shorter
+    /// than `MIN_TICK_RATE` reads as that
+    #[serde(with = "duration_millis")]
+    pub tick_rate: Duration,
+    /// Which status toggling a task moves it to next
+    pub status_cycle_direction: CycleMode,
+    /// Wrap long titles onto extra lines in the list instead of cutting them off
+    pub wrap_text_in_list: bool,
+    /// Whether the list keeps a column free for the `>` cursor: `always`, `when_selected`
+    /// or `never`
+    #[serde(with = "highlight_spacing")]
+    pub highlight_spacing: HighlightSpacing,
+    /// Show the start of each task's info under its title in the list
+    pub render_density: bool,
+    /// Follow tasks with subtasks by a bar of how many of them are completed
+    pub progress_bars_in_list: bool,
+    /// Move tasks to the bottom of the list as soon as they're completed
+    pub auto_sink_completed: bool,
+    /// The status new tasks start with, unless the tasks file sets its own
+    pub default_status: Status,
+    /// What kind of text each edit form field holds
+    pub field_format: FieldFormats,
+    /// Which export format the edit form's preview shows the task in
+    pub preview_format: PreviewFormat,
+    /// What each row in the list shows, in order
+    pub visible_columns: Vec<Column>,
+    /// Tasks files to pick from with Ctrl+O, in the order they're listed
+    pub task_files: Vec<PathBuf>,
+    /// Where each task's info is kept
+    pub notes_storage: NotesStorage,
+    /// Where tasks are kept, the JSON tasks file in the data directory when unset
+    pub storage: Option<StorageBackend>,
+    /// What the bottom right of the list shows. `{mode}`, `{selected}`, `{total}` and
+    /// `{completed}` (a percentage) are filled in, anything else is shown as written
+    pub status_bar_format: String,
+    /// What pasting text with several lines in does
+    pub paste_mode: PasteMode,
+    /// Whether changes other instances write to the tasks file are picked up
+    pub sync_mode: SyncMode,
+    /// Which of the built-in colour schemes to draw with
+    pub theme: Theme,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PasteMode {
+    /// Into the field being typed in, lines and all
+    #[default]
+    Literal,
+    /// A new task for each line
+    MultiTask,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    File(PathBuf),
+    Sqlite(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PreviewFormat {
+    #[default]
+    Markdown,
+    TodoTxt,
+    Ical,
+    Json,
+    Org,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncMode {
+    #[default]
+    Off,
+    /// Watch the tasks file and pick up changes other instances make to it
+    FileWatch,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotesStorage {
+    /// In the tasks file, with the rest of the task
+    #[default]
+    Inline,
+    /// As `<uuid>.md` files in this directory, which can be edited outside the app
+    Files(PathBuf),
+}
+
+/// How an edit form field treats its text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldFormat {
+    #[default]
+    PlainText,
+    /// Plain text too, but labelled so it's clear Markdown is welcome
+    Markdown,
+    /// Checked with `url::Url::parse` before Enter moves on
+    Url,
+    /// `YYYY-MM-DD`, with Left/Right moving between its parts and `+`/`-` stepping them
+    Date,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldFormats {
+    pub title: FieldFormat,
+    pub url: FieldFormat,
+    pub due_date: FieldFormat,
+    pub info: FieldFormat,
+}
+
+impl Default for FieldFormats {
+    fn default() -> Self {
+        Self {
+            title: FieldFormat::PlainText,
+            url: FieldFormat::Url,
+            due_date: FieldFormat::Date,
+            info: FieldFormat::PlainText,
+        }
+    }
+}
+
+/// Something about a task that can be shown on its row in the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Status,
+    Title,
+    DueDate,
+    Tags,
+}
+
+impl Column {
+    pub const ALL: [Column; 4] = [Column::Status, Column::Title, Column::DueDate, Column::Tags];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Status => "Status",
+            Column::Title => "Title",
+            Column::DueDate => "Due date",
+            Column::Tags => "Tags",
+        }
+    }
+}
+
+impl StorageBackend {
+    /// Picks the backend by extension, for paths that don't say which they are
+    pub fn for_path(path: PathBuf) -> StorageBackend {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("db" | "sqlite" | "sqlite3") => StorageBackend::Sqlite(path),
+            _ => StorageBackend::File(path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CycleMode {
+    /// Upcoming → Active → Completed → Upcoming
+    #[default]
+    Full,
+    /// Upcoming → Completed → Upcoming, for people who don't track what's in progress
+    TwoState,
+    /// Completed → Active → Upcoming → Completed
+    Reverse,
+}
+
+impl CycleMode {
+    pub fn next(self, status: Status) -> Status {
+        match (self, status) {
+            (CycleMode::Full, Status::Upcoming) => Status::Active,
+            (CycleMode::Full, Status::Active) => Status::Completed,
+            (CycleMode::Full, Status::Completed) => Status::Upcoming,
+            (CycleMode::TwoState, Status::Upcoming | Status::Active) => Status::Completed,
+            (CycleMode::TwoState, Status::Completed) => Status::Upcoming,
+            (CycleMode::Reverse, Status::Upcoming) => Status::Completed,
+            (CycleMode::Reverse, Status::Active) => Status::Upcoming,
+            (CycleMode::Reverse, Status::Completed) => Status::Active,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            first_launch: true,
+            auto_save_interval: Some(Duration::from_secs(30)),
+            tick_rate: Duration::from_millis(250),
+            status_cycle_direction: CycleMode::Full,
+            wrap_text_in_list: false,
+            render_density: false,
+            progress_bars_in_list: false,
+            highlight_spacing: HighlightSpacing::WhenSelected,
+            auto_sink_completed: false,
+            default_status: Status::Upcoming,
+            field_format: FieldFormats::default(),
+            preview_format: PreviewFormat::Markdown,
+            visible_columns: vec![Column::Status, Column::Title],
+            task_files: vec![],
+            notes_storage: NotesStorage::Inline,
+            storage: None,
+            status_bar_format: "{selected}/{total}".into(),
+            paste_mode: PasteMode::Literal,
+            sync_mode: SyncMode::Off,
+            theme: Theme::Default,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file, falling back to the defaults when it doesn't exist yet
+    pub fn load(path: &Path) -> io::Result<Config> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The configured storage, or the JSON file at `tasks_path` if none is set
+    pub fn storage_or(&self, tasks_path: PathBuf) -> StorageBackend {
+        self.storage
+            .clone()
+            .unwrap_or(StorageBackend::File(tasks_path))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_creating_dirs(path, self.to_commented_toml()?)
+    }
+
+    /// Writes every setting with its default value, each with a comment saying what it does
+    pub fn write_default(path: &Path) -> io::Result<()> {
+        Config::default().save(path)
+    }
+
+    /// The config as TOML with the comment from `KEY_COMMENTS` above each key. Keys that are
+    /// unset still get their comment, with an example left commented out
+    fn to_commented_toml(&self) -> io::Result<String> {
+        let contents = toml::to_string(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let comment = |key: &str| {
+            KEY_COMMENTS
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, comment)| {
+                    comment
+                        .lines()
+                        .map(|line| format!("# {line}\n"))
+                        .collect::<String>()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut commented = String::new();
+        let mut written = vec![];
+        let mut in_table = false;
+        for line in contents.lines() {
+            // Keys inside a table belong to it, only the table's header gets a comment
+            let key = match line.strip_prefix('[') {
+                Some(header) => {
+                    in_table = true;
+                    header.strip_suffix(']')
+                }
+                None if in_table => None,
+                None => line.split_once(" = ").map(|(key, _)| key),
+            };
+            if let Some(key) = key {
+                if !commented.is_empty() {
+                    commented.push('\n');
+                }
+                commented.push_str(&comment(key));
+                written.push(key.to_string());
+            }
+            commented.push_str(line);
+            commented.push('\n');
+        }
+
+        for (key, _) in KEY_COMMENTS {
+            if !written.iter().any(|written| written == key) {
+                commented.push('\n');
+                commented.push_str(&comment(key));
+                commented.push_str(&format!("# [{key}]\n"));
+            }
+        }
+        Ok(commented)
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    base_dir("XDG_CONFIG_HOME", ".config")
+        .join(APP_NAME)
+        .join("config.toml")
+}
+
+/// Where `State` is kept, apart from the config since it's rewritten whenever a file is
+/// opened
+pub fn state_path() -> PathBuf {
+    base_dir("XDG_STATE_HOME", ".local/state")
+        .join(APP_NAME)
+        .join("state.toml")
+}
+
+pub fn tasks_path() -> PathBuf {
+    base_dir("XDG_DATA_HOME", ".local/share")
+        .join(APP_NAME)
+        .join("tasks.json")
+}
+
+pub fn write_creating_dirs(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+fn base_dir(xdg_var: &str, home_fallback: &str) -> PathBuf {
+    match env::var_os(xdg_var) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(home_fallback))
+            .unwrap_or_default(),
+    }
+}
+
+/// Stores an optional `Duration` as whole seconds, with 0 standing in for `None` since TOML
+/// has no null
+mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        duration
+            .map_or(0, |duration| duration.as_secs())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        let secs = u64::deserialize(deserializer)?;
+        Ok((secs > 0).then(|| Duration::from_secs(secs)))
+    }
+}
+
+mod highlight_spacing {
+    use ratatui::widgets::HighlightSpacing;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(
+        spacing: &HighlightSpacing,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match spacing {
+            HighlightSpacing::Always => "always",
+            HighlightSpacing::WhenSelected => "when_selected",
+            HighlightSpacing::Never => "never",
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HighlightSpacing, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "always" => Ok(HighlightSpacing::Always),
+            "when_selected" => Ok(HighlightSpacing::WhenSelected),
+            "never" => Ok(HighlightSpacing::Never),
+            other => Err(D::Error::unknown_variant(
+                other,
+                &["always", "when_selected", "never"],
+            )),
+        }
+    }
+}
+
+mod duration_millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::MIN_TICK_RATE;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        (duration.as_millis() as u64).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?).max(MIN_TICK_RATE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let config = Config {
+            storage: Some(StorageBackend::Sqlite("/tmp/tasks.db".into())),
+            highlight_spacing: HighlightSpacing::Always,
+            ..Config::default()
+        };
+
+        let loaded: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(loaded.storage, config.storage);
+        assert_eq!(loaded.highlight_spacing, HighlightSpacing::Always);
+    }
+
+    #[test]
+    fn tick_rate_has_a_floor() {
+        let loaded: Config = toml::from_str("tick_rate = 0").unwrap();
+        assert_eq!(loaded.tick_rate, MIN_TICK_RATE);
+        let loaded: Config = toml::from_str("tick_rate = 100").unwrap();
+        assert_eq!(loaded.tick_rate, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn default_config_documents_every_key() {
+        let path = temp_path(".toml");
+        Config::write_default(&path).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(contents.contains(
+            "# Default status for new tasks. Options: \"upcoming\", \"active\", \"completed\"\n\
+             default_status = \"upcoming\""
+        ));
+        for (key, _) in KEY_COMMENTS {
+            assert!(contents.contains(key), "{key} isn't in the default config");
+        }
+        let loaded: Config = toml::from_str(&contents).unwrap();
+        assert_eq!(
+            loaded.status_bar_format,
+            Config::default().status_bar_format
+        );
+        assert_eq!(loaded.storage, None);
+    }
+}
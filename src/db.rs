@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use uuid::Uuid;
+
+use crate::app::{Status, Task, TodoList};
+
+/// Tasks are kept as their JSON form next to the columns needed to find and order them, so
+/// the table doesn't need a migration every time `Task` gains a field
+pub fn init_db(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tasks (
+             id TEXT PRIMARY KEY,
+             position INTEGER NOT NULL,
+             data TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS meta (
+             key TEXT PRIMARY KEY,
+             value TEXT NOT NULL
+         );",
+    )
+}
+
+pub fn load_tasks(conn: &Connection) -> rusqlite::Result<Vec<Task>> {
+    let mut statement = conn.prepare("SELECT data FROM tasks ORDER BY position")?;
+    statement
+        .query_map([], |row| {
+            let data: String = row.get(0)?;
+            serde_json::from_str(&data).map_err(|error| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    error.into(),
+                )
+            })
+        })?
+        .collect()
+}
+
+/// Inserts the task, or updates it if its id is already stored
+pub fn save_task(conn: &Connection, task: &Task, position: usize) -> rusqlite::Result<()> {
+    let data = serde_json::to_string(task).expect("tasks serialize to JSON");
+    conn.execute(
+        "INSERT INTO tasks (id, position, data) VALUES (?1, ?2, ?3)
+         ON CONFLICT (id) DO UPDATE SET position = excluded.position, data = excluded.data",
+        params![task.id.to_string(), position as i64, data],
+    )?;
+    Ok(())
+}
+
+pub fn delete_task(conn: &Connection, id: Uuid) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM tasks WHERE id = ?1", params![id.to_string()])?;
+    Ok(())
+}
+
+/// Reads a whole list back, including what the JSON file keeps alongside its tasks
+pub fn load_list(conn: &Connection) -> rusqlite::Result<TodoList> {
+    let mut list = TodoList::from(load_tasks(conn)?);
+    list.last_opened =
+        get_meta(conn, "last_opened")?.and_then(|value| value.parse::<DateTime<Utc>>().ok());
+    list.default_status = get_meta(conn, "default_status")?
+        .and_then(|value| serde_json::from_str::<Status>(&value).ok());
+    Ok(list)
+}
+
+/// Makes the database match `list` in one transaction, deleting tasks that are gone from it
+pub fn save_list(conn: &mut Connection, list: &TodoList) -> rusqlite::Result<()> {
+    let transaction = conn.transaction()?;
+
+    let kept: HashSet<Uuid> = list.items.iter().map(|task| task.id).collect();
+    let stored = {
+        let mut statement = transaction.prepare("SELECT id FROM tasks")?;
+        statement
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+    };
+    for id in stored.iter().filter_map(|id| id.parse::<Uuid>().ok()) {
+        if !kept.contains(&id) {
+            delete_task(&transaction, id)?;
+        }
+    }
+    for (position, task) in list.items.iter().enumerate() {
+        save_task(&transaction, task, position)?;
+    }
+
+    set_meta(&transaction, "last_opened", Some(Utc::now().to_rfc3339()))?;
+    let default_status = list
+        .default_status
+        .map(|status| serde_json::to_string(&status).expect("statuses serialize to JSON"));
+    set_meta(&transaction, "default_status", default_status)?;
+
+    transaction.commit()
+}
+
+fn get_meta(conn: &Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM meta WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+fn set_meta(conn: &Connection, key: &str, value: Option<String>) -> rusqlite::Result<()> {
+    match value {
+        Some(value) => conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?,
+        None => conn.execute("DELETE FROM meta WHERE key = ?1", params![key])?,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn round_trips_a_list() {
+        let mut conn = memory_db();
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "First", "some info"),
+            (Status::Completed, "Second", ""),
+        ]);
+        list.default_status = Some(Status::Active);
+        save_list(&mut conn, &list).unwrap();
+
+        let loaded = load_list(&conn).unwrap();
+        assert_eq!(loaded.items.len(), 2);
+        for (loaded, original) in loaded.items.iter().zip(&list.items) {
+            assert_eq!(loaded.id, original.id);
+            assert!(loaded.same_content(original));
+        }
+        assert_eq!(loaded.default_status, Some(Status::Active));
+        assert!(loaded.last_opened.is_some());
+    }
+
+    #[test]
+    fn saving_removes_deleted_tasks_and_keeps_order() {
+        let mut conn = memory_db();
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "a", ""),
+            (Status::Upcoming, "b", ""),
+            (Status::Upcoming, "c", ""),
+        ]);
+        save_list(&mut conn, &list).unwrap();
+
+        list.remove(0);
+        list.reorder(1, 0);
+        save_list(&mut conn, &list).unwrap();
+
+        let titles: Vec<String> = load_tasks(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|task| task.title)
+            .collect();
+        assert_eq!(titles, ["c", "b"]);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{fmt, fs, io, path::Path};
+
+use age::secrecy::SecretString;
+
+use crate::{app::TodoList, config};
+
+/// A passphrase being typed. Only ever shown as `*`s, and the only way to get the text back
+/// out is to hand it over to `encrypt_list` or `decrypt_list`
+#[derive(Default)]
+pub struct PasswordField {
+    passphrase: String,
+    masked: String,
+}
+
+impl PasswordField {
+    pub fn push(&mut self, value: char) {
+        self.passphrase.push(value);
+        self.masked.push('*');
+    }
+
+    pub fn pop(&mut self) {
+        self.passphrase.pop();
+        self.masked.pop();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passphrase.is_empty()
+    }
+
+    /// A `*` for each character typed
+    pub fn masked(&self) -> &str {
+        &self.masked
+    }
+
+    fn into_secret(self) -> SecretString {
+        SecretString::from(self.passphrase)
+    }
+}
+
+impl fmt::Debug for PasswordField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("PasswordField").field(&self.masked).finish()
+    }
+}
+
+/// Writes the list to `path` as a tasks file, encrypted with age using the passphrase
+pub fn encrypt_list(list: &TodoList, path: &Path, passphrase: PasswordField) -> io::Result<()> {
+    let recipient = age::scrypt::Recipient::new(passphrase.into_secret());
+    let encrypted =
+        age::encrypt(&recipient, list.serialize_pretty().as_bytes()).map_err(io::Error::other)?;
+    config::write_creating_dirs(path, encrypted)
+}
+
+/// Reads a tasks file written by `encrypt_list`
+pub fn decrypt_list(path: &Path, passphrase: PasswordField) -> io::Result<TodoList> {
+    let identity = age::scrypt::Identity::new(passphrase.into_secret());
+    let decrypted = age::decrypt(&identity, &fs::read(path)?)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let contents = String::from_utf8(decrypted)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    TodoList::from_json_str(&contents, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Status;
+    use crate::testing::temp_path;
+
+    fn password(text: &str) -> PasswordField {
+        let mut field = PasswordField::default();
+        text.chars().for_each(|value| field.push(value));
+        field
+    }
+
+    #[test]
+    fn round_trips_with_the_right_passphrase_only() {
+        let path = temp_path(".json.age");
+        let list = TodoList::from_iter([(Status::Active, "Secret plans", "shh")]);
+        let field = password("hunter2");
+        assert_eq!(field.masked(), "*******");
+        assert!(!format!("{field:?}").contains("hunter2"));
+
+        encrypt_list(&list, &path, field).unwrap();
+        assert!(
+            !fs::read_to_string(&path)
+                .unwrap_or_default()
+                .contains("Secret plans")
+        );
+        assert!(decrypt_list(&path, password("hunter3")).is_err());
+        let decrypted = decrypt_list(&path, password("hunter2")).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(decrypted.items[0].id, list.items[0].id);
+        assert_eq!(decrypted.items[0].info, "shh");
+    }
+}
@@ -0,0 +1,519 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use chrono::Utc;
+
+use uuid::Uuid;
+
+use crate::{
+    app::{App, DUE_DATE_FORMAT, Status, Task, TodoList},
+    config::PreviewFormat,
+};
+
+// RFC 5545 lines are limited to 75 octets, excluding the CRLF
+const ICAL_MAX_LINE_LEN: usize = 75;
+
+impl App {
+    /// Renders every task with a due date as a VTODO inside a single VCALENDAR
+    pub fn export_ical(&self) -> String {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let mut out = String::new();
+
+        push_ical_line(&mut out, "BEGIN:VCALENDAR");
+        push_ical_line(&mut out, "VERSION:2.0");
+        push_ical_line(&mut out, "PRODID:-//ratatodo//ratatodo//EN");
+        for task in self
+            .list
+            .items
+            .iter()
+            .filter(|task| task.due_date.is_some())
+        {
+            push_vtodo(&mut out, task, &stamp);
+        }
+        push_ical_line(&mut out, "END:VCALENDAR");
+
+        out
+    }
+
+    /// The list as it's shown, archived tasks left out, as a Markdown checklist with a line
+    /// per task, for pasting elsewhere
+    pub fn to_markdown_checklist(&self) -> String {
+        self.list
+            .items
+            .iter()
+            .filter(|task| !task.is_archived)
+            .map(|task| task.markdown_line() + "\n")
+            .collect()
+    }
+
+    /// Copies `to_markdown_checklist` to the clipboard
+    pub fn export_to_clipboard(&self) -> Result<(), arboard::Error> {
+        arboard::Clipboard::new()?.set_text(self.to_markdown_checklist())
+    }
+
+    /// The list as an Emacs Org-mode outline, with subtasks as subheadings under their parent
+    pub fn export_org(&self) -> String {
+        // Org only knows TODO and DONE unless the file says otherwise
+        let mut out = String::from("#+TODO: TODO IN-PROGRESS | DONE\n");
+        for (depth, i) in self.list.flatten(&HashSet::new()) {
+            out.push_str(&self.list.items[i].to_org(depth + 1));
+        }
+        out
+    }
+
+    /// Writes one task per line as JSON, for tools that stream rather than parse a whole array
+    pub fn export_json_lines(&self) -> String {
+        let mut out = String::new();
+        for task in &self.list.items {
+            // Tasks only hold strings, dates and ids, so serializing can't fail
+            out.push_str(&serde_json::to_string(task).expect("tasks serialize to JSON"));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl Task {
+    /// A Markdown checklist item, with the info indented underneath
+    pub fn to_markdown(&self) -> String {
+        let mut out = self.markdown_line();
+        out.push('\n');
+        for line in self.info.lines() {
+            out.push_str(&format!("  {line}\n"));
+        }
+        out
+    }
+
+    /// The checklist item on its own, without the info or a newline
+    fn markdown_line(&self) -> String {
+        let check = match self.mode {
+            Status::Completed => 'x',
+            Status::Upcoming | Status::Active => ' ',
+        };
+        let mut out = format!("- [{check}] {}", self.title);
+        if let Some(due_date) = self.due_date {
+            out.push_str(&format!(" (due {})", due_date.format(DUE_DATE_FORMAT)));
+        }
+        if let Some(url) = &self.url {
+            out.push_str(&format!(" <{url}>"));
+        }
+        out
+    }
+
+    /// An Org heading with `level` stars, then its deadline, a drawer with its id and the
+    /// info as the heading's body
+    fn to_org(&self, level: usize) -> String {
+        let keyword = match self.mode {
+            Status::Upcoming => "TODO",
+            Status::Active => "IN-PROGRESS",
+            Status::Completed => "DONE",
+        };
+        let mut out = format!("{} {keyword} {}\n", "*".repeat(level), self.title);
+        if let Some(due_date) = self.due_date {
+            out.push_str(&format!("DEADLINE: <{}>\n", due_date.format("%Y-%m-%d %a")));
+        }
+        out.push_str(":PROPERTIES:\n");
+        out.push_str(&format!(":ID: {}\n", self.id));
+        if let Some(start_date) = self.start_date {
+            out.push_str(&format!(":START: {}\n", start_date.format(DUE_DATE_FORMAT)));
+        }
+        if let Some(url) = &self.url {
+            out.push_str(&format!(":URL: {url}\n"));
+        }
+        out.push_str(":END:\n");
+        for line in self.info.lines() {
+            // A line starting with a star would be read as another heading
+            if line.starts_with('*') {
+                out.push(' ');
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// This task on its own in `format`, as exporting it would write it
+    pub fn export_preview(&self, format: PreviewFormat) -> String {
+        match format {
+            PreviewFormat::Markdown => self.to_markdown(),
+            PreviewFormat::TodoTxt => self.to_todotxt(),
+            PreviewFormat::Ical => {
+                let mut out = String::new();
+                push_vtodo(
+                    &mut out,
+                    self,
+                    &Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+                );
+                out
+            }
+            PreviewFormat::Json => {
+                serde_json::to_string_pretty(self).expect("tasks serialize to JSON")
+            }
+            PreviewFormat::Org => self.to_org(1),
+        }
+    }
+}
+
+impl TodoList {
+    /// Formats the list as an indented tree, with each subtask nested under its parent, e.g.
+    /// `- _ Parent\n  - ✓ Child\n`
+    pub fn to_tree_string(&self) -> String {
+        self.flatten(&HashSet::new())
+            .into_iter()
+            .map(|(depth, i)| {
+                let task = &self.items[i];
+                format!(
+                    "{}- {} {}\n",
+                    "  ".repeat(depth),
+                    task.mode.marker(),
+                    task.title
+                )
+            })
+            .collect()
+    }
+
+    /// The tasks a row each, as they're shown with subtasks under their parent, paired with
+    /// how many levels down they are. Subtasks of the tasks in `collapsed` are left out
+    pub fn as_flat_with_subtasks(&self) -> Vec<(usize, &Task)> {
+        self.flatten(&self.collapsed)
+            .into_iter()
+            .map(|(depth, i)| (depth, &self.items[i]))
+            .collect()
+    }
+
+    /// The rows the list shows, as depths and positions in `items`: `as_flat_with_subtasks`
+    /// without the archived tasks
+    pub fn shown_rows(&self) -> Vec<(usize, usize)> {
+        let mut rows = self.flatten(&self.collapsed);
+        rows.retain(|&(_, i)| !self.items[i].is_archived);
+        rows
+    }
+
+    fn flatten(&self, collapsed: &HashSet<Uuid>) -> Vec<(usize, usize)> {
+        // Most lists have no subtasks, and they're drawn every frame
+        if self.items.iter().all(|task| task.parent.is_none()) {
+            return (0..self.items.len()).map(|i| (0, i)).collect();
+        }
+
+        let mut children: HashMap<Uuid, Vec<usize>> = HashMap::new();
+        let mut roots = vec![];
+        for (i, task) in self.items.iter().enumerate() {
+            match task
+                .parent
+                .filter(|&parent| self.position_of(parent).is_some())
+            {
+                Some(parent) => children.entry(parent).or_default().push(i),
+                None => roots.push(i),
+            }
+        }
+
+        let mut rows = vec![];
+        let mut visited = vec![false; self.items.len()];
+        // Tasks in a loop of parents have no root, so whatever is left goes at the top level
+        for top in roots.into_iter().chain(0..self.items.len()) {
+            let mut stack = vec![(0, top, true)];
+            while let Some((depth, i, shown)) = stack.pop() {
+                if std::mem::replace(&mut visited[i], true) {
+                    continue;
+                }
+                if shown {
+                    rows.push((depth, i));
+                }
+                // Tasks inside a collapsed one are still visited, just not shown, so they
+                // don't turn up again at the top level like a loop would
+                let shown = shown && !collapsed.contains(&self.items[i].id);
+                if let Some(children) = children.get(&self.items[i].id) {
+                    stack.extend(
+                        children
+                            .iter()
+                            .rev()
+                            .map(|&child| (depth + 1, child, shown)),
+                    );
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// Reads tasks written by [`App::export_json_lines`], skipping blank lines
+pub fn import_json_lines(path: &Path) -> io::Result<Vec<Task>> {
+    let mut tasks = vec![];
+    for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let task = serde_json::from_str(&line).map_err(|error| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {error}", i + 1),
+            )
+        })?;
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+fn push_vtodo(out: &mut String, task: &Task, stamp: &str) {
+    let status = match task.mode {
+        Status::Upcoming => "NEEDS-ACTION",
+        Status::Active => "IN-PROCESS",
+        Status::Completed => "COMPLETED",
+    };
+
+    push_ical_line(out, "BEGIN:VTODO");
+    push_ical_line(out, &format!("UID:{}", task.id));
+    push_ical_line(out, &format!("DTSTAMP:{stamp}"));
+    push_ical_line(out, &format!("SUMMARY:{}", escape_ical_text(&task.title)));
+    if !task.info.is_empty() {
+        push_ical_line(
+            out,
+            &format!("DESCRIPTION:{}", escape_ical_text(&task.info)),
+        );
+    }
+    if let Some(due_date) = task.due_date {
+        push_ical_line(
+            out,
+            &format!("DUE;VALUE=DATE:{}", due_date.format("%Y%m%d")),
+        );
+    }
+    push_ical_line(out, &format!("STATUS:{status}"));
+    push_ical_line(out, "END:VTODO");
+}
+
+fn escape_ical_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folding it onto continuation lines that start with a space
+fn push_ical_line(out: &mut String, line: &str) {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > ICAL_MAX_LINE_LEN {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{app_with, temp_path};
+    use chrono::NaiveDate;
+
+    fn due(mode: Status, title: &str, info: &str) -> Task {
+        let mut task = Task::new(mode, title, info);
+        task.due_date = NaiveDate::from_ymd_opt(2024, 2, 1);
+        task
+    }
+
+    #[test]
+    fn only_exports_tasks_with_due_dates() {
+        let app = app_with(vec![
+            due(Status::Upcoming, "Pay rent", ""),
+            Task::new(Status::Upcoming, "Someday", ""),
+        ]);
+        let ical = app.export_ical();
+
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ical.matches("BEGIN:VTODO").count(), 1);
+        assert!(ical.contains("SUMMARY:Pay rent\r\n"));
+        assert!(ical.contains("DUE;VALUE=DATE:20240201\r\n"));
+        assert!(!ical.contains("Someday"));
+    }
+
+    #[test]
+    fn maps_each_status() {
+        let app = app_with(vec![
+            due(Status::Upcoming, "a", ""),
+            due(Status::Active, "b", ""),
+            due(Status::Completed, "c", ""),
+        ]);
+        let ical = app.export_ical();
+
+        assert!(ical.contains("STATUS:NEEDS-ACTION\r\n"));
+        assert!(ical.contains("STATUS:IN-PROCESS\r\n"));
+        assert!(ical.contains("STATUS:COMPLETED\r\n"));
+    }
+
+    #[test]
+    fn escapes_text_values() {
+        let app = app_with(vec![due(Status::Upcoming, "Milk, eggs; bread", "a\\b\nc")]);
+        let ical = app.export_ical();
+
+        assert!(ical.contains("SUMMARY:Milk\\, eggs\\; bread\r\n"));
+        assert!(ical.contains("DESCRIPTION:a\\\\b\\nc\r\n"));
+    }
+
+    #[test]
+    fn org_nests_subtasks_as_subheadings() {
+        let mut app = app_with(vec![
+            due(Status::Active, "Plan trip", "Book early\n* not a heading"),
+            Task::new(Status::Completed, "Flights", ""),
+            Task::new(Status::Upcoming, "Hotel", ""),
+        ]);
+        app.list.items[1].parent = Some(app.list.items[0].id);
+        app.list.items[2].parent = Some(app.list.items[0].id);
+        let (parent, flights) = (app.list.items[0].id, app.list.items[1].id);
+
+        assert_eq!(
+            app.export_org(),
+            format!(
+                "#+TODO: TODO IN-PROGRESS | DONE\n\
+                 * IN-PROGRESS Plan trip\nDEADLINE: <2024-02-01 Thu>\n\
+                 :PROPERTIES:\n:ID: {parent}\n:END:\nBook early\n * not a heading\n\
+                 ** DONE Flights\n:PROPERTIES:\n:ID: {flights}\n:END:\n\
+                 ** TODO Hotel\n:PROPERTIES:\n:ID: {}\n:END:\n",
+                app.list.items[2].id
+            )
+        );
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        let mut task = due(Status::Active, "Multi\nline", "{\"json\": true}");
+        task.url = Some("https://example.com".into());
+        let app = app_with(vec![task, Task::new(Status::Completed, "Done", "")]);
+
+        let ndjson = app.export_json_lines();
+        assert_eq!(ndjson.lines().count(), 2);
+
+        let path = temp_path(".ndjson");
+        std::fs::write(&path, format!("{ndjson}\n")).unwrap();
+        let imported = import_json_lines(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(imported.len(), 2);
+        for (imported, original) in imported.iter().zip(&app.list.items) {
+            assert_eq!(imported.id, original.id);
+            assert!(imported.same_content(original));
+        }
+    }
+
+    #[test]
+    fn tree_nests_subtasks_under_their_parents() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "Grandchild", ""),
+            (Status::Active, "Parent", ""),
+            (Status::Completed, "Child", ""),
+            (Status::Upcoming, "Sibling", ""),
+        ]);
+        list.items[0].parent = Some(list.items[2].id);
+        list.items[2].parent = Some(list.items[1].id);
+
+        assert_eq!(
+            list.to_tree_string(),
+            "- ☐ Parent\n  - ✓ Child\n    - _ Grandchild\n- _ Sibling\n"
+        );
+    }
+
+    #[test]
+    fn flattening_leaves_out_collapsed_subtasks() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "Parent", ""),
+            (Status::Upcoming, "Child", ""),
+            (Status::Upcoming, "Grandchild", ""),
+            (Status::Upcoming, "Other", ""),
+        ]);
+        list.items[1].parent = Some(list.items[0].id);
+        list.items[2].parent = Some(list.items[1].id);
+        let rows = |list: &TodoList| -> Vec<(usize, String)> {
+            list.as_flat_with_subtasks()
+                .into_iter()
+                .map(|(depth, task)| (depth, task.title.clone()))
+                .collect()
+        };
+
+        assert_eq!(
+            rows(&list),
+            [
+                (0, "Parent".to_string()),
+                (1, "Child".to_string()),
+                (2, "Grandchild".to_string()),
+                (0, "Other".to_string()),
+            ]
+        );
+
+        list.collapsed.insert(list.items[1].id);
+        assert_eq!(rows(&list).len(), 3);
+        list.collapsed.insert(list.items[0].id);
+        assert_eq!(
+            rows(&list),
+            [(0, "Parent".to_string()), (0, "Other".to_string())]
+        );
+    }
+
+    #[test]
+    fn tree_still_lists_loops_of_parents() {
+        let mut list =
+            TodoList::from_iter([(Status::Upcoming, "A", ""), (Status::Upcoming, "B", "")]);
+        list.items[0].parent = Some(list.items[1].id);
+        list.items[1].parent = Some(list.items[0].id);
+
+        assert_eq!(list.to_tree_string(), "- _ A\n  - _ B\n");
+    }
+
+    #[test]
+    fn markdown_checks_off_completed_tasks() {
+        let mut task = due(Status::Completed, "Ship it", "line one\nline two");
+        task.url = Some("https://example.com".into());
+
+        assert_eq!(
+            task.to_markdown(),
+            "- [x] Ship it (due 2024-02-01) <https://example.com>\n  line one\n  line two\n"
+        );
+        assert!(
+            Task::new(Status::Active, "Later", "")
+                .to_markdown()
+                .starts_with("- [ ] ")
+        );
+    }
+
+    #[test]
+    fn copies_the_list_as_a_checklist_without_info() {
+        let mut app = App::default();
+        app.list = TodoList::from_iter([
+            (Status::Completed, "Ship it", "with notes"),
+            (Status::Active, "Tell everyone", ""),
+        ]);
+        app.list.items[0].due_date = NaiveDate::from_ymd_opt(2024, 2, 1);
+
+        assert_eq!(
+            app.to_markdown_checklist(),
+            "- [x] Ship it (due 2024-02-01)\n- [ ] Tell everyone\n"
+        );
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let mut out = String::new();
+        push_ical_line(&mut out, &format!("SUMMARY:{}", "é".repeat(60)));
+
+        for line in out.split("\r\n").filter(|line| !line.is_empty()) {
+            assert!(line.len() <= ICAL_MAX_LINE_LEN, "{line:?}");
+        }
+        let unfolded = out.replace("\r\n ", "");
+        assert_eq!(unfolded, format!("SUMMARY:{}\r\n", "é".repeat(60)));
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::NaiveDate;
+
+use crate::app::{App, DUE_DATE_FORMAT, TodoList};
+
+impl TodoList {
+    /// The list index, first day and last day of each task the Gantt chart has a bar for, in
+    /// list order. A task with only one of a start or due date gets a bar one day long
+    pub fn gantt_rows(&self) -> Vec<(usize, NaiveDate, NaiveDate)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| !task.is_archived)
+            .filter_map(|(i, task)| {
+                let start = task.start_date.or(task.due_date)?;
+                let end = task.due_date.unwrap_or(start).max(start);
+                Some((i, start, end))
+            })
+            .collect()
+    }
+
+    /// The earliest and latest day any bar covers, which the chart can scroll between
+    pub fn gantt_range(&self) -> Option<(NaiveDate, NaiveDate)> {
+        let rows = self.gantt_rows();
+        let first = rows.iter().map(|&(_, start, _)| start).min()?;
+        let last = rows.iter().map(|&(_, _, end)| end).max()?;
+        Some((first, last))
+    }
+}
+
+impl App {
+    /// Sets when the selected task starts, from `:start`, or clears it without a date
+    pub fn set_start_date(&mut self, date: Option<&str>) -> String {
+        let Some(i) = self.list.state.selected() else {
+            return "No task selected".into();
+        };
+        let start_date = match date.map(|date| NaiveDate::parse_from_str(date, DUE_DATE_FORMAT)) {
+            None => None,
+            Some(Ok(date)) => Some(date),
+            Some(Err(_)) => return "Start date must be YYYY-MM-DD".into(),
+        };
+        let task = &mut self.list.items[i];
+        if let (Some(start), Some(due)) = (start_date, task.due_date)
+            && start > due
+        {
+            return "A task can't start after it's due".into();
+        }
+
+        task.start_date = start_date;
+        task.touch();
+        self.mark_dirty();
+        match start_date {
+            Some(date) => format!("Starts {}", date.format(DUE_DATE_FORMAT)),
+            None => "Start date cleared".into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Status;
+
+    #[test]
+    fn bars_span_from_start_to_due() {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "Both", ""),
+            (Status::Upcoming, "Due only", ""),
+            (Status::Upcoming, "Neither", ""),
+            (Status::Upcoming, "Start only", ""),
+        ]);
+        list.items[0].start_date = Some(date(2));
+        list.items[0].due_date = Some(date(9));
+        list.items[1].due_date = Some(date(12));
+        list.items[3].start_date = Some(date(1));
+
+        assert_eq!(
+            list.gantt_rows(),
+            [
+                (0, date(2), date(9)),
+                (1, date(12), date(12)),
+                (3, date(1), date(1))
+            ]
+        );
+        assert_eq!(list.gantt_range(), Some((date(1), date(12))));
+    }
+}
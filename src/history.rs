@@ -0,0 +1,345 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use chrono::{Days, Months, NaiveDate};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::DUE_DATE_FORMAT;
+
+/// How many past values each field remembers
+pub const HISTORY_LEN: usize = 20;
+
+/// Where the year, month and day start in a `YYYY-MM-DD` date
+const DATE_SEGMENTS: [usize; 3] = [0, 5, 8];
+
+/// A text input that remembers what was submitted through it. Derefs to the current value
+#[derive(Debug, Default)]
+pub struct HistoryTextField {
+    value: String,
+    /// Character position of the cursor, `None` meaning the end. Editing the value through
+    /// `DerefMut` doesn't move it, so it's clamped wherever it's read
+    cursor: Option<usize>,
+    /// Most recent first
+    history: VecDeque<String>,
+    history_pos: Option<usize>,
+}
+
+impl HistoryTextField {
+    /// Replaces the value, e.g. when starting to edit an existing task
+    pub fn set(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = None;
+        self.history_pos = None;
+    }
+
+    pub fn cursor(&self) -> usize {
+        let len = self.value.chars().count();
+        self.cursor.map_or(len, |cursor| cursor.min(len))
+    }
+
+    /// The value split at the cursor
+    pub fn split_at_cursor(&self) -> (&str, &str) {
+        self.value.split_at(self.byte_offset(self.cursor()))
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        let cursor = self.cursor();
+        if cursor == self.value.chars().count() {
+            self.value.push(c);
+            self.cursor = None;
+        } else {
+            self.value.insert(self.byte_offset(cursor), c);
+            self.cursor = Some(cursor + 1);
+        }
+    }
+
+    /// Deletes the character before the cursor
+    pub fn backspace(&mut self) {
+        let cursor = self.cursor();
+        if cursor > 0 {
+            self.value.remove(self.byte_offset(cursor - 1));
+            self.cursor = self.cursor.map(|_| cursor - 1);
+        }
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = Some(0);
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = None;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = Some(self.cursor().saturating_sub(1));
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = Some(self.cursor() + 1).filter(|&cursor| cursor < self.value.chars().count());
+    }
+
+    /// Moves to the same column on the line above or below, or its end when it's shorter,
+    /// for values with several lines
+    pub fn move_line(&mut self, up: bool) {
+        let chars: Vec<char> = self.value.chars().collect();
+        let cursor = self.cursor();
+        let line_start = |end: usize| {
+            chars[..end]
+                .iter()
+                .rposition(|&c| c == '\n')
+                .map_or(0, |i| i + 1)
+        };
+        let start = line_start(cursor);
+        let target = match up {
+            true if start == 0 => return,
+            true => line_start(start - 1),
+            false => match chars[cursor..].iter().position(|&c| c == '\n') {
+                Some(i) => cursor + i + 1,
+                None => return,
+            },
+        };
+        let target_len = chars[target..]
+            .iter()
+            .position(|&c| c == '\n')
+            .unwrap_or(chars.len() - target);
+        self.cursor = Some(target + (cursor - start).min(target_len));
+    }
+
+    /// Handles the cursor movement keys, including readline's Ctrl+A and Ctrl+E, returning
+    /// whether the key was one of them
+    pub fn handle_cursor_key(&mut self, key_event: KeyEvent) -> bool {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Home => self.move_home(),
+            KeyCode::End => self.move_end(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Char('a') if ctrl => self.move_home(),
+            KeyCode::Char('e') if ctrl => self.move_end(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Treats the value as a `YYYY-MM-DD` date: Left and Right jump between the year, month
+    /// and day, wrapping around, and `+`/`-` step the one under the cursor. Stepping a field
+    /// that isn't a date yet starts it at `today`. Returns whether the key was one of these
+    pub fn handle_date_key(&mut self, key_event: KeyEvent, today: NaiveDate) -> bool {
+        let segment = DATE_SEGMENTS
+            .iter()
+            .rposition(|&start| self.cursor() >= start)
+            .unwrap_or(0);
+        match key_event.code {
+            KeyCode::Left => self.cursor = Some(DATE_SEGMENTS[(segment + 2) % 3]),
+            KeyCode::Right => self.cursor = Some(DATE_SEGMENTS[(segment + 1) % 3]),
+            KeyCode::Char(sign @ ('+' | '-')) => {
+                let Ok(date) = NaiveDate::parse_from_str(self.value.trim(), DUE_DATE_FORMAT) else {
+                    self.value = today.format(DUE_DATE_FORMAT).to_string();
+                    self.cursor = Some(DATE_SEGMENTS[2]);
+                    return true;
+                };
+                let forward = sign == '+';
+                let stepped = match (segment, forward) {
+                    (0, true) => date.checked_add_months(Months::new(12)),
+                    (0, false) => date.checked_sub_months(Months::new(12)),
+                    (1, true) => date.checked_add_months(Months::new(1)),
+                    (1, false) => date.checked_sub_months(Months::new(1)),
+                    (_, true) => date.checked_add_days(Days::new(1)),
+                    (_, false) => date.checked_sub_days(Days::new(1)),
+                };
+                self.value = stepped.unwrap_or(date).format(DUE_DATE_FORMAT).to_string();
+                self.cursor = Some(DATE_SEGMENTS[segment]);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn byte_offset(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    pub fn history(&self) -> &VecDeque<String> {
+        &self.history
+    }
+
+    /// Records the current value, skipping blanks and repeats of the latest entry
+    pub fn remember(&mut self) {
+        self.history_pos = None;
+        let value = self.value.trim();
+        if value.is_empty() || self.history.front().is_some_and(|last| last == value) {
+            return;
+        }
+        self.history.push_front(value.to_string());
+        self.history.truncate(HISTORY_LEN);
+    }
+
+    /// Replaces the value with an older (`back`) or newer history entry, like a shell.
+    /// Stepping forward past the newest entry clears the value
+    pub fn step_history(&mut self, back: bool) {
+        let pos = match (self.history_pos, back) {
+            (None, true) if !self.history.is_empty() => Some(0),
+            (Some(pos), true) => Some((pos + 1).min(self.history.len() - 1)),
+            (Some(pos), false) => pos.checked_sub(1),
+            (pos, _) => pos,
+        };
+
+        self.value = pos
+            .and_then(|pos| self.history.get(pos))
+            .cloned()
+            .unwrap_or_default();
+        self.cursor = None;
+        self.history_pos = pos;
+    }
+
+    /// Handles Ctrl+P and Ctrl+N, returning whether the key was one of them
+    pub fn handle_history_key(&mut self, key_event: KeyEvent) -> bool {
+        if !key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match key_event.code {
+            KeyCode::Char('p') => self.step_history(true),
+            KeyCode::Char('n') => self.step_history(false),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Deref for HistoryTextField {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.value
+    }
+}
+
+impl DerefMut for HistoryTextField {
+    fn deref_mut(&mut self) -> &mut String {
+        &mut self.value
+    }
+}
+
+impl fmt::Display for HistoryTextField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field_with_history(values: &[&str]) -> HistoryTextField {
+        let mut field = HistoryTextField::default();
+        for value in values {
+            field.set(*value);
+            field.remember();
+        }
+        field.clear();
+        field
+    }
+
+    #[test]
+    fn remembers_distinct_values_up_to_the_limit() {
+        let field = field_with_history(&["first", "second", "second", "  "]);
+        assert_eq!(field.history(), &["second", "first"]);
+
+        let values: Vec<String> = (0..30).map(|i| format!("value {i}")).collect();
+        let values: Vec<&str> = values.iter().map(String::as_str).collect();
+        let field = field_with_history(&values);
+        assert_eq!(field.history().len(), HISTORY_LEN);
+        assert_eq!(field.history()[0], "value 29");
+    }
+
+    #[test]
+    fn ctrl_p_and_ctrl_n_step_through_history() {
+        let mut field = field_with_history(&["first", "second"]);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        assert!(field.handle_history_key(ctrl('p')));
+        assert_eq!(field.as_str(), "second");
+        field.handle_history_key(ctrl('p'));
+        field.handle_history_key(ctrl('p'));
+        assert_eq!(field.as_str(), "first");
+        field.handle_history_key(ctrl('n'));
+        field.handle_history_key(ctrl('n'));
+        assert_eq!(field.as_str(), "");
+
+        assert!(!field.handle_history_key(KeyEvent::from(KeyCode::Char('p'))));
+    }
+
+    #[test]
+    fn edits_at_the_cursor() {
+        let mut field = HistoryTextField::default();
+        field.set("héllo");
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        assert!(field.handle_cursor_key(ctrl('a')));
+        field.insert_char('¡');
+        assert_eq!(field.as_str(), "¡héllo");
+        assert_eq!(field.split_at_cursor(), ("¡", "héllo"));
+
+        field.handle_cursor_key(KeyEvent::from(KeyCode::Right));
+        field.handle_cursor_key(KeyEvent::from(KeyCode::Right));
+        field.backspace();
+        assert_eq!(field.as_str(), "¡hllo");
+
+        assert!(field.handle_cursor_key(ctrl('e')));
+        field.insert_char('!');
+        assert_eq!(field.as_str(), "¡hllo!");
+        assert_eq!(field.cursor(), 6);
+
+        field.handle_cursor_key(KeyEvent::from(KeyCode::Home));
+        field.backspace();
+        assert_eq!(field.as_str(), "¡hllo!");
+    }
+
+    #[test]
+    fn moves_between_lines_keeping_the_column() {
+        let mut field = HistoryTextField::default();
+        field.set("{\n  \"a\": 1\n}");
+        field.move_line(true);
+        assert_eq!(field.split_at_cursor().1, " \"a\": 1\n}");
+
+        field.move_right();
+        field.move_right();
+        field.move_right();
+        field.move_line(false);
+        // The last line is shorter, so its end
+        assert_eq!(field.split_at_cursor(), ("{\n  \"a\": 1\n}", ""));
+        field.move_line(true);
+        field.move_line(true);
+        assert_eq!(field.split_at_cursor(), ("{", "\n  \"a\": 1\n}"));
+        field.move_line(true);
+        assert_eq!(field.cursor(), 1);
+    }
+
+    #[test]
+    fn steps_date_segments() {
+        let today = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut field = HistoryTextField::default();
+
+        assert!(field.handle_date_key(key(KeyCode::Char('+')), today));
+        assert_eq!(field.as_str(), "2026-01-31");
+        field.handle_date_key(key(KeyCode::Char('+')), today);
+        assert_eq!(field.as_str(), "2026-02-01");
+
+        // Day, then round to the year and on to the month
+        field.handle_date_key(key(KeyCode::Right), today);
+        field.handle_date_key(key(KeyCode::Right), today);
+        field.handle_date_key(key(KeyCode::Char('-')), today);
+        assert_eq!(field.as_str(), "2026-01-01");
+        field.handle_date_key(key(KeyCode::Left), today);
+        field.handle_date_key(key(KeyCode::Char('-')), today);
+        assert_eq!(field.as_str(), "2025-01-01");
+        assert!(!field.handle_date_key(key(KeyCode::Char('1')), today));
+    }
+}
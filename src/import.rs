@@ -0,0 +1,499 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{MAIN_SEPARATOR, Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::{
+    app::{DUE_DATE_FORMAT, Status, Task, TitleError, TodoList},
+    export,
+    history::HistoryTextField,
+};
+
+/// How many of the tasks read the wizard shows before asking how to import them
+pub const PREVIEW_LEN: usize = 5;
+
+/// The kinds of file the import wizard reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Json,
+    Csv,
+    Markdown,
+    TodoTxt,
+    Ndjson,
+}
+
+impl ImportFormat {
+    pub const ALL: [ImportFormat; 5] = [
+        ImportFormat::Json,
+        ImportFormat::Csv,
+        ImportFormat::Markdown,
+        ImportFormat::TodoTxt,
+        ImportFormat::Ndjson,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ImportFormat::Json => "JSON",
+            ImportFormat::Csv => "CSV",
+            ImportFormat::Markdown => "Markdown",
+            ImportFormat::TodoTxt => "todo.txt",
+            ImportFormat::Ndjson => "NDJSON",
+        }
+    }
+
+    /// Reads the tasks in `path`, which should be in this format
+    pub fn read(self, path: &Path) -> io::Result<Vec<Task>> {
+        match self {
+            ImportFormat::Json => Ok(TodoList::from_json_file(path)?.into()),
+            ImportFormat::Ndjson => export::import_json_lines(path),
+            ImportFormat::Csv => from_csv(&fs::read_to_string(path)?),
+            ImportFormat::Markdown => Ok(from_markdown(&fs::read_to_string(path)?)),
+            ImportFormat::TodoTxt => fs::read_to_string(path)?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(Task::from_todotxt)
+                .collect(),
+        }
+    }
+}
+
+/// What to do with the tasks already in the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Adds every imported task, alongside the ones already here
+    Append,
+    /// Throws the tasks already here away
+    Replace,
+    /// Updates the tasks with the same title as an imported one, and adds the rest
+    MergeByTitle,
+}
+
+impl MergeStrategy {
+    pub const ALL: [MergeStrategy; 3] = [
+        MergeStrategy::Append,
+        MergeStrategy::Replace,
+        MergeStrategy::MergeByTitle,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MergeStrategy::Append => "Append",
+            MergeStrategy::Replace => "Replace",
+            MergeStrategy::MergeByTitle => "Merge by title",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            MergeStrategy::Append => "Add them after the tasks already here",
+            MergeStrategy::Replace => "Delete the tasks already here first",
+            MergeStrategy::MergeByTitle => "Update tasks with the same title, add the rest",
+        }
+    }
+}
+
+/// Where the import wizard is up to. Each step keeps what the ones before it chose, so Esc
+/// can go back a step
+#[derive(Debug)]
+pub enum WizardStep {
+    /// `selected` indexes into `ImportFormat::ALL`
+    Format { selected: usize },
+    Path {
+        format: ImportFormat,
+        field: HistoryTextField,
+    },
+    Preview {
+        format: ImportFormat,
+        path: PathBuf,
+        tasks: Vec<Task>,
+    },
+    /// `selected` indexes into `MergeStrategy::ALL`
+    Strategy {
+        format: ImportFormat,
+        path: PathBuf,
+        tasks: Vec<Task>,
+        selected: usize,
+    },
+}
+
+impl WizardStep {
+    /// The step's place in the wizard, counting from 1
+    pub fn number(&self) -> usize {
+        match self {
+            WizardStep::Format { .. } => 1,
+            WizardStep::Path { .. } => 2,
+            WizardStep::Preview { .. } => 3,
+            WizardStep::Strategy { .. } => 4,
+        }
+    }
+}
+
+impl TodoList {
+    /// Adds `tasks` to the list the way `strategy` says, returning how many were added and
+    /// how many updated tasks already here. Dependencies and parents among the imported tasks
+    /// follow them to whatever id they end up with
+    pub fn import_with(&mut self, tasks: Vec<Task>, strategy: MergeStrategy) -> (usize, usize) {
+        if strategy == MergeStrategy::Replace {
+            self.items.clear();
+            self.rebuild_index();
+            self.state.select(None);
+        }
+        let first_added = self.items.len();
+        let mut new_ids = HashMap::new();
+        let mut updated = 0;
+        for mut task in tasks {
+            if strategy == MergeStrategy::MergeByTitle
+                && let Some(existing) = self.items.iter_mut().find(|t| t.title == task.title)
+            {
+                existing.mode = task.mode;
+                existing.start_date = task.start_date;
+                existing.due_date = task.due_date;
+                existing.url = task.url;
+                existing.info = task.info;
+                existing.touch();
+                new_ids.insert(task.id, existing.id);
+                updated += 1;
+                continue;
+            }
+            // The same file imported twice mustn't leave two tasks with one id
+            if self.find_by_id(task.id).is_some() {
+                let id = Uuid::new_v4();
+                new_ids.insert(task.id, id);
+                task.id = id;
+            }
+            self.push(task);
+        }
+        for task in &mut self.items[first_added..] {
+            for link in [&mut task.depends_on, &mut task.parent] {
+                if let Some(&id) = link.as_ref().and_then(|id| new_ids.get(id)) {
+                    *link = Some(id);
+                }
+            }
+        }
+        (self.items.len() - first_added, updated)
+    }
+}
+
+/// Completes the last part of `input` to the entries of its directory starting with it, as
+/// far as they all agree. `None` when nothing matches or there's nothing more to add
+pub fn complete_path(input: &str) -> Option<String> {
+    let (dir, prefix) = match input.rfind(MAIN_SEPARATOR) {
+        Some(i) => input.split_at(i + 1),
+        None => ("", input),
+    };
+    let read_from = if dir.is_empty() { "." } else { dir };
+    let mut matches: Vec<(String, bool)> = fs::read_dir(read_from)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            name.starts_with(prefix).then_some((name, is_dir))
+        })
+        .collect();
+    matches.sort();
+
+    let completed = match matches.as_slice() {
+        [] => return None,
+        [(name, true)] => format!("{dir}{name}{MAIN_SEPARATOR}"),
+        [(name, false)] => format!("{dir}{name}"),
+        [(first, _), rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, (name, _)| {
+                let len = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map_or(0, |((i, a), _)| i + a.len_utf8());
+                &common[..len]
+            });
+            format!("{dir}{common}")
+        }
+    };
+    (completed != input).then_some(completed)
+}
+
+/// Reads a CSV file with a header row. The columns are picked out by name, `title` being the
+/// only one required; `status`, `due_date`, `start_date`, `url` and `info` are read if present.
+/// Rows with a blank or too long title are skipped
+fn from_csv(text: &str) -> io::Result<Vec<Task>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut records = csv_records(text).into_iter();
+    let header = records.next().unwrap_or_default();
+    let column = |name: &str| header.iter().position(|field| field.trim() == name);
+    let title = column("title").ok_or_else(|| invalid("no title column".into()))?;
+    let (status, due_date, start_date) =
+        (column("status"), column("due_date"), column("start_date"));
+    let (url, info) = (column("url"), column("info"));
+
+    let mut tasks = vec![];
+    for (i, record) in records.enumerate() {
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| record.get(column))
+                .map(|field| field.trim())
+                .unwrap_or("")
+        };
+        let date = |column: Option<usize>| match field(column) {
+            "" => Ok(None),
+            date => NaiveDate::parse_from_str(date, DUE_DATE_FORMAT)
+                .map(Some)
+                .map_err(|error| invalid(format!("row {}: {error}", i + 2))),
+        };
+        if TitleError::check(field(Some(title))).is_some() {
+            continue;
+        }
+        let mode = match field(status) {
+            "" => Status::Upcoming,
+            name => Status::from_name(name)
+                .ok_or_else(|| invalid(format!("row {}: unknown status \"{name}\"", i + 2)))?,
+        };
+        let mut task = Task::new(mode, field(Some(title)), field(info));
+        task.due_date = date(due_date)?;
+        task.start_date = date(start_date)?;
+        task.url = Some(field(url))
+            .filter(|url| !url.is_empty())
+            .map(String::from);
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+/// Splits CSV into records of fields. Quoted fields can hold commas, newlines and `""` for a
+/// quote
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|record| record.iter().any(|field| !field.is_empty()));
+    records
+}
+
+/// Reads a Markdown checklist as [`Task::to_markdown`] writes it: a `- [ ]` or `- [x]` item
+/// per task, with the info indented underneath. Anything else is left out, as are items
+/// whose title is blank or too long
+fn from_markdown(text: &str) -> Vec<Task> {
+    let mut tasks: Vec<Task> = vec![];
+    let mut last_skipped = false;
+    for line in text.lines() {
+        let item = line
+            .strip_prefix("- [")
+            .or_else(|| line.strip_prefix("* ["));
+        let Some((check, rest)) = item.and_then(|item| item.split_once("] ")) else {
+            if let (Some(task), Some(info), false) =
+                (tasks.last_mut(), line.strip_prefix("  "), last_skipped)
+            {
+                if !task.info.is_empty() {
+                    task.info.push('\n');
+                }
+                task.info.push_str(info);
+            }
+            continue;
+        };
+        let mode = match check {
+            "x" | "X" => Status::Completed,
+            _ => Status::Upcoming,
+        };
+
+        let mut title = rest.trim();
+        let mut url = None;
+        if let Some(start) = title.rfind(" <")
+            && title.ends_with('>')
+        {
+            url = Some(title[start + 2..title.len() - 1].to_string());
+            title = &title[..start];
+        }
+        let mut due_date = None;
+        if let Some(start) = title.rfind(" (due ")
+            && let Ok(date) =
+                NaiveDate::parse_from_str(title[start + 6..].trim_end_matches(')'), DUE_DATE_FORMAT)
+        {
+            due_date = Some(date);
+            title = &title[..start];
+        }
+        if TitleError::check(title).is_some() {
+            // Its info mustn't end up on the task before it
+            last_skipped = true;
+            continue;
+        }
+        last_skipped = false;
+
+        let mut task = Task::new(mode, title, "");
+        task.url = url;
+        task.due_date = due_date;
+        tasks.push(task);
+    }
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::MAX_TITLE_LEN;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn reads_csv_with_quoted_fields() {
+        let csv = "title,status,due_date,info\r\n\
+                   Buy milk,active,2024-01-15,\r\n\
+                   \"Call Bob, then Alice\",completed,,\"Said \"\"hi\"\"\nTwice\"\r\n\
+                   ,upcoming,,\r\n";
+        let csv = format!("{csv}{},upcoming,,\r\n", "x".repeat(MAX_TITLE_LEN + 1));
+        let tasks = from_csv(&csv).unwrap();
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].title, "Buy milk");
+        assert_eq!(tasks[0].mode, Status::Active);
+        assert_eq!(tasks[0].due_date, NaiveDate::from_ymd_opt(2024, 1, 15));
+        assert_eq!(tasks[1].title, "Call Bob, then Alice");
+        assert_eq!(tasks[1].mode, Status::Completed);
+        assert_eq!(tasks[1].info, "Said \"hi\"\nTwice");
+        assert!(from_csv("name\nBuy milk\n").is_err());
+    }
+
+    #[test]
+    fn reads_markdown_as_it_was_exported() {
+        let mut task = Task::new(Status::Completed, "Pay rent", "First line\nSecond line");
+        task.due_date = NaiveDate::from_ymd_opt(2024, 2, 1);
+        task.url = Some("https://example.com".into());
+        let too_long = "x".repeat(MAX_TITLE_LEN + 1);
+        let markdown = format!(
+            "# Tasks\n{}- [ ] {too_long}\n  Its info\n- [ ] Other\n",
+            task.to_markdown()
+        );
+
+        let tasks = from_markdown(&markdown);
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks[0].same_content(&task));
+        assert_eq!(tasks[1].title, "Other");
+        assert_eq!(tasks[1].mode, Status::Upcoming);
+    }
+
+    #[test]
+    fn strategies_treat_existing_tasks_differently() {
+        let list = TodoList::from_iter([
+            (Status::Upcoming, "Shared", ""),
+            (Status::Upcoming, "Only here", ""),
+        ]);
+        let incoming = || {
+            vec![
+                Task::new(Status::Completed, "Shared", "done now"),
+                Task::new(Status::Active, "New", ""),
+            ]
+        };
+        let titles = |list: &TodoList| -> Vec<String> {
+            list.items.iter().map(|task| task.title.clone()).collect()
+        };
+
+        let mut appended = TodoList::from(list.items.clone());
+        assert_eq!(
+            appended.import_with(incoming(), MergeStrategy::Append),
+            (2, 0)
+        );
+        assert_eq!(titles(&appended), ["Shared", "Only here", "Shared", "New"]);
+
+        let mut replaced = TodoList::from(list.items.clone());
+        assert_eq!(
+            replaced.import_with(incoming(), MergeStrategy::Replace),
+            (2, 0)
+        );
+        assert_eq!(titles(&replaced), ["Shared", "New"]);
+
+        let mut merged = TodoList::from(list.items.clone());
+        assert_eq!(
+            merged.import_with(incoming(), MergeStrategy::MergeByTitle),
+            (1, 1)
+        );
+        assert_eq!(titles(&merged), ["Shared", "Only here", "New"]);
+        assert_eq!(merged.items[0].id, list.items[0].id);
+        assert_eq!(merged.items[0].mode, Status::Completed);
+        assert_eq!(merged.items[0].info, "done now");
+
+        let mut twice = TodoList::from(list.items.clone());
+        twice.import_with(list.items.clone(), MergeStrategy::Append);
+        assert_ne!(twice.items[0].id, twice.items[2].id);
+    }
+
+    #[test]
+    fn imported_dependencies_follow_the_tasks_to_their_ids() {
+        let parent = Task::new(Status::Upcoming, "Parent", "");
+        let mut child = Task::new(Status::Upcoming, "Child", "");
+        child.depends_on = Some(parent.id);
+        child.parent = Some(parent.id);
+        let batch = vec![parent.clone(), child];
+
+        // The file was imported before, so the parent's id is taken
+        let mut appended = TodoList::from(vec![parent.clone()]);
+        assert_eq!(
+            appended.import_with(batch.clone(), MergeStrategy::Append),
+            (2, 0)
+        );
+        assert_ne!(appended.items[1].id, parent.id);
+        assert_eq!(appended.items[2].depends_on, Some(appended.items[1].id));
+        assert_eq!(appended.items[2].parent, Some(appended.items[1].id));
+
+        // Merged into a task already here, the parent keeps that task's id
+        let existing = Task::new(Status::Active, "Parent", "");
+        let mut merged = TodoList::from(vec![existing.clone()]);
+        assert_eq!(
+            merged.import_with(batch, MergeStrategy::MergeByTitle),
+            (1, 1)
+        );
+        assert_eq!(merged.items[1].depends_on, Some(existing.id));
+    }
+
+    #[test]
+    fn completes_paths_as_far_as_they_agree() {
+        let dir = temp_path("");
+        fs::create_dir_all(dir.join("tasks-folder")).unwrap();
+        fs::write(dir.join("tasks-old.json"), "[]").unwrap();
+        fs::write(dir.join("tasks-new.json"), "[]").unwrap();
+        let input = |name: &str| format!("{}{MAIN_SEPARATOR}{name}", dir.display());
+
+        let completed = [
+            complete_path(&input("t")),
+            complete_path(&input("tasks-f")),
+            complete_path(&input("tasks-o")),
+            complete_path(&input("tasks-")),
+            complete_path(&input("x")),
+        ];
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            completed,
+            [
+                Some(input("tasks-")),
+                Some(input(&format!("tasks-folder{MAIN_SEPARATOR}"))),
+                Some(input("tasks-old.json")),
+                None,
+                None,
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::app::{App, Status};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// A task depends on an id that no task in the list has
+    DanglingDependency(Uuid),
+    /// More than one task shares this id
+    DuplicateId(Uuid),
+    /// The task at this position is completed while some of its subtasks aren't
+    SubtaskStatusInconsistency(usize),
+    /// Following dependencies from this task leads back to it, so none of them can be done
+    DependencyCycle(Uuid),
+}
+
+impl App {
+    pub fn check_integrity(&self) -> Vec<IntegrityError> {
+        let mut errors = vec![];
+        let mut seen = HashSet::new();
+        let mut reported = HashSet::new();
+
+        for task in &self.list.items {
+            if !seen.insert(task.id) && reported.insert(task.id) {
+                errors.push(IntegrityError::DuplicateId(task.id));
+            }
+        }
+
+        let mut dangling = HashSet::new();
+        for task in &self.list.items {
+            if let Some(id) = task.depends_on
+                && !seen.contains(&id)
+                && dangling.insert(id)
+            {
+                errors.push(IntegrityError::DanglingDependency(id));
+            }
+        }
+
+        for (i, task) in self.list.items.iter().enumerate() {
+            if task.mode == Status::Completed
+                && self
+                    .list
+                    .subtask_progress(task)
+                    .is_some_and(|(done, total)| done < total)
+            {
+                errors.push(IntegrityError::SubtaskStatusInconsistency(i));
+            }
+        }
+
+        // Each loop is reported once, from the first of its tasks in the list
+        let mut in_loops = HashSet::new();
+        for task in &self.list.items {
+            if in_loops.contains(&task.id) {
+                continue;
+            }
+            let Some(cycle) = self.dependency_cycle(task.id) else {
+                continue;
+            };
+            in_loops.extend(cycle);
+            errors.push(IntegrityError::DependencyCycle(task.id));
+        }
+
+        errors
+    }
+
+    /// The tasks from `id` round to the one that depends on it, when its dependencies loop
+    /// back to it
+    fn dependency_cycle(&self, id: Uuid) -> Option<Vec<Uuid>> {
+        let task = self.list.find_by_id(id)?;
+        let next = self.list.find_by_id(task.depends_on?)?;
+        if !self.list.links_to(next, id, |task| task.depends_on) {
+            return None;
+        }
+        let mut cycle = vec![id];
+        let mut current = next;
+        while current.id != id {
+            cycle.push(current.id);
+            current = self.list.find_by_id(current.depends_on?)?;
+        }
+        Some(cycle)
+    }
+
+    pub fn describe_integrity_error(&self, error: &IntegrityError) -> String {
+        match error {
+            IntegrityError::DanglingDependency(id) => {
+                let titles: Vec<&str> = self
+                    .list
+                    .items
+                    .iter()
+                    .filter(|task| task.depends_on == Some(*id))
+                    .map(|task| task.title.as_str())
+                    .collect();
+                format!("{} depend on missing task {id}", titles.join(", "))
+            }
+            IntegrityError::DuplicateId(id) => {
+                let count = self.list.items.iter().filter(|task| task.id == *id).count();
+                format!("{count} tasks share the id {id}")
+            }
+            IntegrityError::SubtaskStatusInconsistency(i) => {
+                let task = &self.list.items[*i];
+                let (done, total) = self.list.subtask_progress(task).unwrap_or_default();
+                format!(
+                    "\"{}\" is completed but {} of its subtasks aren't",
+                    task.title,
+                    total - done
+                )
+            }
+            IntegrityError::DependencyCycle(id) => {
+                let titles: Vec<String> = self
+                    .dependency_cycle(*id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain([*id])
+                    .filter_map(|id| self.list.find_by_id(id))
+                    .map(|task| format!("\"{}\"", task.title))
+                    .collect();
+                format!("Dependencies go round in a loop: {}", titles.join(" → "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::Task, testing::app_with};
+
+    #[test]
+    fn clean_list_has_no_errors() {
+        let first = Task::new(Status::Upcoming, "First", "");
+        let mut second = Task::new(Status::Upcoming, "Second", "");
+        second.depends_on = Some(first.id);
+
+        assert!(app_with(vec![first, second]).check_integrity().is_empty());
+    }
+
+    #[test]
+    fn reports_each_dangling_dependency_once() {
+        let missing = Uuid::new_v4();
+        let mut first = Task::new(Status::Upcoming, "First", "");
+        let mut second = Task::new(Status::Upcoming, "Second", "");
+        first.depends_on = Some(missing);
+        second.depends_on = Some(missing);
+
+        assert_eq!(
+            app_with(vec![first, second]).check_integrity(),
+            vec![IntegrityError::DanglingDependency(missing)]
+        );
+    }
+
+    #[test]
+    fn reports_each_duplicate_id_once() {
+        let first = Task::new(Status::Upcoming, "First", "");
+        let mut second = Task::new(Status::Upcoming, "Second", "");
+        let mut third = Task::new(Status::Upcoming, "Third", "");
+        second.id = first.id;
+        third.id = first.id;
+        let id = first.id;
+
+        let app = app_with(vec![first, second, third]);
+        assert_eq!(app.check_integrity(), vec![IntegrityError::DuplicateId(id)]);
+        assert_eq!(
+            app.describe_integrity_error(&IntegrityError::DuplicateId(id)),
+            format!("3 tasks share the id {id}")
+        );
+    }
+
+    #[test]
+    fn reports_completed_parents_with_open_subtasks() {
+        let parent = Task::new(Status::Completed, "Move house", "");
+        let mut packed = Task::new(Status::Completed, "Pack", "");
+        let mut keys = Task::new(Status::Upcoming, "Hand in keys", "");
+        packed.parent = Some(parent.id);
+        keys.parent = Some(parent.id);
+
+        let app = app_with(vec![parent, packed, keys]);
+        let error = IntegrityError::SubtaskStatusInconsistency(0);
+        assert_eq!(app.check_integrity(), vec![error]);
+        assert_eq!(
+            app.describe_integrity_error(&error),
+            "\"Move house\" is completed but 1 of its subtasks aren't"
+        );
+    }
+
+    #[test]
+    fn reports_each_dependency_loop_once() {
+        let mut first = Task::new(Status::Upcoming, "First", "");
+        let mut second = Task::new(Status::Upcoming, "Second", "");
+        let mut after = Task::new(Status::Upcoming, "After", "");
+        first.depends_on = Some(second.id);
+        second.depends_on = Some(first.id);
+        // Waiting on the loop isn't part of it
+        after.depends_on = Some(first.id);
+        let id = first.id;
+
+        let app = app_with(vec![first, second, after]);
+        assert_eq!(
+            app.check_integrity(),
+            vec![IntegrityError::DependencyCycle(id)]
+        );
+        assert_eq!(
+            app.describe_integrity_error(&IntegrityError::DependencyCycle(id)),
+            "Dependencies go round in a loop: \"First\" → \"Second\" → \"First\""
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// The modes whose keys are all plain commands. Modes that take typed text or carry state
+/// of their own, like the edit form or the minimap, handle their keys themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModeKey {
+    View,
+    TaskDetail,
+    Help,
+    Focus,
+    Inspector,
+    Logs,
+    IntegrityReport,
+}
+
+/// Something a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Exit,
+    /// Back to the list from whatever's showing
+    Back,
+    NewTask,
+    EditTask,
+    DeleteTask,
+    ToggleStatus,
+    PasteTask,
+    /// Copies the selected task into a new one just below it
+    DuplicateTask,
+    Undo,
+    SelectNext,
+    SelectPrevious,
+    SelectFirst,
+    SelectLast,
+    SelectNextPage,
+    SelectPreviousPage,
+    /// Hides or shows the selected task's subtasks
+    ToggleCollapsed,
+    SelectFirstIncomplete,
+    /// Moves the selection in the task detail view, wrapping around at the ends
+    CycleNext,
+    CyclePrevious,
+    ScrollDown,
+    ScrollUp,
+    ScrollToTop,
+    RenameInline,
+    OpenDetail,
+    OpenHelp,
+    OpenAbout,
+    OpenCommand,
+    OpenSearch,
+    OpenQuickAdd,
+    OpenFocus,
+    OpenInspector,
+    /// Edits the inspected task's JSON directly
+    OpenJsonEditor,
+    OpenRecentFiles,
+    OpenListPicker,
+    /// Walks through importing a file, a step at a time
+    OpenImportWizard,
+    ArchiveTask,
+    OpenArchive,
+    OpenForecast,
+    OpenSplit,
+    ToggleLayout,
+    /// Shows the next tab of the selected task's details
+    CycleDetailTab,
+    PickRandomActive,
+    CopyAsMarkdown,
+}
+
+impl Action {
+    /// Whether it adds, removes or changes tasks, which read-only mode doesn't allow
+    pub fn changes_tasks(&self) -> bool {
+        matches!(
+            self,
+            Action::NewTask
+                | Action::EditTask
+                | Action::DeleteTask
+                | Action::ToggleStatus
+                | Action::PasteTask
+                | Action::DuplicateTask
+                | Action::Undo
+                | Action::RenameInline
+                | Action::OpenQuickAdd
+                | Action::OpenJsonEditor
+                | Action::ArchiveTask
+                | Action::OpenImportWizard
+        )
+    }
+}
+
+pub type Keymap = HashMap<(ModeKey, KeyCode, KeyModifiers), Action>;
+type Key = (KeyCode, KeyModifiers);
+
+/// What the table is looked up with. Shift is already in the character for letters and
+/// symbols, and terminals don't agree on whether to report it as well
+pub fn lookup_key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+    match code {
+        KeyCode::Char(_) => (code, modifiers - KeyModifiers::SHIFT),
+        _ => (code, modifiers),
+    }
+}
+
+pub fn default_keymap() -> Keymap {
+    use Action::*;
+    use KeyCode::{
+        Backspace, Char, Delete, Down, End, Enter, Esc, F, Home, Left, PageDown, PageUp, Right,
+        Tab, Up,
+    };
+
+    let none = KeyModifiers::NONE;
+    let ctrl = KeyModifiers::CONTROL;
+    let bindings: &[(ModeKey, &[Key], Action)] = &[
+        (ModeKey::View, &[(Char('q'), none)], Exit),
+        (ModeKey::View, &[(Char('r'), ctrl)], OpenRecentFiles),
+        (ModeKey::View, &[(Char('o'), ctrl)], OpenListPicker),
+        // Most terminals send Ctrl+I as Tab, so this only fires where the keyboard
+        // enhancement protocol is available. `:inspect` always works
+        (ModeKey::View, &[(Char('i'), ctrl)], OpenInspector),
+        (ModeKey::View, &[(Char('f'), ctrl)], OpenForecast),
+        // Like Ctrl+I, only told apart from Tab with the keyboard enhancement protocol.
+        // `:tab` does the same
+        (ModeKey::View, &[(Tab, ctrl)], CycleDetailTab),
+        // Ctrl+Shift+C, which some terminals report as the lowercase letter once Shift is
+        // dropped, or can't tell apart from Ctrl+C at all
+        (
+            ModeKey::View,
+            &[(Char('C'), ctrl), (Char('c'), ctrl)],
+            CopyAsMarkdown,
+        ),
+        (
+            ModeKey::View,
+            &[
+                (Char('n'), none),
+                (Char('i'), none),
+                (Char('a'), none),
+                (Char('o'), none),
+            ],
+            NewTask,
+        ),
+        (
+            ModeKey::View,
+            &[(Char('j'), none), (Down, none)],
+            SelectNext,
+        ),
+        (
+            ModeKey::View,
+            &[(Char('k'), none), (Up, none)],
+            SelectPrevious,
+        ),
+        (ModeKey::View, &[(Home, none)], SelectFirst),
+        (ModeKey::View, &[(End, none)], SelectLast),
+        (ModeKey::View, &[(PageDown, none)], SelectNextPage),
+        (ModeKey::View, &[(PageUp, none)], SelectPreviousPage),
+        (ModeKey::View, &[(Char('c'), none)], ToggleCollapsed),
+        (ModeKey::View, &[(Char('h'), none)], OpenHelp),
+        (ModeKey::View, &[(Char('?'), none)], OpenAbout),
+        // "What should I work on?", `?` being taken by About
+        (ModeKey::View, &[(Char('w'), none)], PickRandomActive),
+        (ModeKey::View, &[(Char(':'), none)], OpenCommand),
+        (ModeKey::View, &[(Char('/'), none)], OpenSearch),
+        (ModeKey::View, &[(Char(' '), none)], OpenQuickAdd),
+        (ModeKey::View, &[(Char('u'), none)], Undo),
+        (ModeKey::View, &[(F(2), none)], RenameInline),
+        (ModeKey::View, &[(Char('F'), none)], OpenFocus),
+        (ModeKey::View, &[(Char('|'), none)], ToggleLayout),
+        (ModeKey::View, &[(Char('\\'), none)], OpenSplit),
+        (ModeKey::View, &[(Char('e'), none)], EditTask),
+        (ModeKey::View, &[(Char('V'), none)], PasteTask),
+        (ModeKey::View, &[(Char('D'), none)], DuplicateTask),
+        (ModeKey::View, &[(Char('z'), none)], ArchiveTask),
+        (ModeKey::View, &[(Char('Z'), none)], OpenArchive),
+        // Ctrl+I would be the obvious key, but it's the inspector's, and most terminals
+        // can't tell it from Tab anyway. `:import` on its own does the same
+        (ModeKey::View, &[(Char('I'), none)], OpenImportWizard),
+        (ModeKey::View, &[(Enter, none)], OpenDetail),
+        (
+            ModeKey::View,
+            &[(Delete, none), (Backspace, none), (Char('d'), none)],
+            DeleteTask,
+        ),
+        (ModeKey::View, &[(Tab, none)], SelectFirstIncomplete),
+        (
+            ModeKey::View,
+            &[
+                (Char('l'), none),
+                (Right, none),
+                (Left, none),
+                (Char('t'), none),
+            ],
+            ToggleStatus,
+        ),
+        (ModeKey::TaskDetail, &[(Esc, none)], Back),
+        (
+            ModeKey::TaskDetail,
+            &[(Char('j'), none), (Down, none)],
+            CycleNext,
+        ),
+        (
+            ModeKey::TaskDetail,
+            &[(Char('k'), none), (Up, none)],
+            CyclePrevious,
+        ),
+        (ModeKey::TaskDetail, &[(Char('e'), none)], EditTask),
+        (ModeKey::Inspector, &[(Char('j'), ctrl)], OpenJsonEditor),
+        (ModeKey::Help, &[(Esc, none)], Back),
+        (ModeKey::Focus, &[(Esc, none)], Back),
+        (
+            ModeKey::IntegrityReport,
+            &[(Esc, none), (Enter, none), (Char('q'), none)],
+            Back,
+        ),
+    ];
+
+    let mut keymap: Keymap = bindings
+        .iter()
+        .flat_map(|&(mode, keys, action)| {
+            keys.iter()
+                .map(move |&(code, modifiers)| ((mode, code, modifiers), action))
+        })
+        .collect();
+    // The inspector and logs scroll the same way
+    for mode in [ModeKey::Inspector, ModeKey::Logs] {
+        keymap.insert((mode, Esc, none), Back);
+        keymap.insert((mode, Char('q'), none), Back);
+        keymap.insert((mode, Char('j'), none), ScrollDown);
+        keymap.insert((mode, Down, none), ScrollDown);
+        keymap.insert((mode, Char('k'), none), ScrollUp);
+        keymap.insert((mode, Up, none), ScrollUp);
+        keymap.insert((mode, Home, none), ScrollToTop);
+        keymap.insert((mode, Char('g'), none), ScrollToTop);
+    }
+    keymap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shifted_characters_are_found_either_way() {
+        let keymap = default_keymap();
+        let find = |code, modifiers| {
+            let (code, modifiers) = lookup_key(code, modifiers);
+            keymap.get(&(ModeKey::View, code, modifiers)).copied()
+        };
+
+        assert_eq!(
+            find(KeyCode::Char('F'), KeyModifiers::SHIFT),
+            Some(Action::OpenFocus)
+        );
+        assert_eq!(
+            find(KeyCode::Char('F'), KeyModifiers::NONE),
+            Some(Action::OpenFocus)
+        );
+        assert_eq!(
+            find(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            Some(Action::OpenRecentFiles)
+        );
+        // Modifiers still matter for everything else
+        assert_eq!(find(KeyCode::Char('d'), KeyModifiers::ALT), None);
+        assert_eq!(find(KeyCode::Tab, KeyModifiers::SHIFT), None);
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::Local;
+
+use crate::app::App;
+
+/// How many messages `:logs` keeps before dropping the oldest
+const LOG_LEN: usize = 100;
+
+impl App {
+    /// Keeps `message` for `:logs`, stamped with the time it happened
+    pub fn log(&mut self, message: &str) {
+        if self.log_buffer.len() == LOG_LEN {
+            self.log_buffer.pop_front();
+        }
+        let time = Local::now().format("%H:%M:%S");
+        self.log_buffer.push_back(format!("{time} {message}"));
+    }
+
+    /// Logs an error that's also about to be shown in a toast, which would otherwise be the
+    /// only record of it
+    pub fn log_error(&mut self, message: String) -> String {
+        self.log(&message);
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_the_oldest_messages_past_the_limit() {
+        let mut app = App::default();
+        for i in 0..LOG_LEN + 5 {
+            app.log(&format!("message {i}"));
+        }
+
+        assert_eq!(app.log_buffer.len(), LOG_LEN);
+        assert!(app.log_buffer[0].ends_with(" message 5"));
+        assert!(
+            app.log_buffer
+                .back()
+                .unwrap()
+                .ends_with(&format!(" message {}", LOG_LEN + 4))
+        );
+    }
+}
@@ -1,16 +1,112 @@
-use std::io;
-use crate::app::App;
+use std::{env, io, path::Path, process};
+use crate::{
+    app::{App, TodoList},
+    cli::Args,
+    config::{Config, StorageBackend},
+    state::State,
+};
 
 mod app;
+mod archive;
+mod bulk;
+mod cli;
+mod config;
+mod db;
+mod encrypt;
+mod export;
+mod gantt;
+mod history;
+mod import;
+mod integrity;
+mod keymap;
+mod log;
+mod merge;
+mod notes;
+mod query;
+mod snapshot;
+mod splash;
+mod state;
+mod status_bar;
+mod storage;
+mod sync;
+mod tags;
+#[cfg(test)]
+mod testing;
+mod theme;
+mod todotxt;
 mod ui;
+mod undo;
 
-fn main() -> io::Result<()> {
-    let mut terminal = ratatui::init();
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("ratatodo: {error}\n\n{}", cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
+    if let Some(path) = &args.import {
+        return import(path, args.dedup);
+    }
+    if args.dump {
+        print!("{}", storage()?.load()?.to_tree_string());
+        return Ok(());
+    }
 
-    let mut app = App::default();
+    let mut app = App::new(config::config_path(), config::state_path(), config::tasks_path())?;
+    app.read_only = args.read_only;
+    if args.batch || args.quick {
+        // They add to the tasks, so have to wait until there are some to add to
+        app.poll_loading(true)?;
+    }
+    if args.batch {
+        // Has to happen before raw mode takes over the terminal
+        let lines = io::stdin().lines().collect::<io::Result<Vec<_>>>()?;
+        app.add_batch_tasks(lines.iter().map(String::as_str), "stdin", args.infer_status);
+    }
+    if args.quick {
+        app.start_quick_entry(args.quick_title.as_deref().unwrap_or_default());
+    }
 
-    let app_result = app.run(&mut terminal);
+    let mut terminal = ratatui::init();
+
+    let app_result = app.run(&mut terminal).await;
 
     ratatui::restore();
     app_result
 }
+
+/// Where the configured tasks are kept
+fn storage() -> io::Result<StorageBackend> {
+    let config = Config::load(&config::config_path())?;
+    Ok(State::load(&config::state_path()).launch_storage(&config, config::tasks_path()))
+}
+
+/// Merges another tasks file into the default one without starting the TUI
+fn import(path: &Path, dedup: bool) -> io::Result<()> {
+    let storage = storage()?;
+    let mut list = storage.load()?;
+    let other = TodoList::from_json_file(path)?;
+
+    let report = list.merge(other, dedup);
+    for (id, title) in &report.conflicts {
+        eprintln!("warning: kept the existing task {id}, the imported \"{title}\" differs");
+    }
+
+    storage.save(&list)?;
+    println!(
+        "Imported {} tasks from {} ({} already present, {} conflicts)",
+        report.added,
+        path.display(),
+        report.skipped,
+        report.conflicts.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+
+use chrono::Local;
+use uuid::Uuid;
+
+use crate::app::{Status, Task, TodoList};
+
+/// Words that mark a task imported from plain text as already done
+const DONE_WORDS: &[&str] = &["done", "completed", "finished"];
+
+/// An existing task and the imported version that would replace it
+pub type TaskUpdate = (Task, Task);
+
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    pub added: usize,
+    /// Tasks already in the list, either with the same id and content or, when
+    /// de-duplicating, the same title
+    pub skipped: usize,
+    /// Ids present in both lists with different content. The existing task is kept
+    pub conflicts: Vec<(Uuid, String)>,
+}
+
+impl TodoList {
+    /// Appends the tasks in `other` that this list doesn't already have
+    pub fn merge(&mut self, other: TodoList, dedup_by_title: bool) -> MergeReport {
+        let mut report = MergeReport::default();
+        let mut titles: HashSet<String> =
+            self.items.iter().map(|task| task.title.clone()).collect();
+
+        for task in other {
+            if let Some(existing) = self.find_by_id(task.id) {
+                if existing.same_content(&task) {
+                    report.skipped += 1;
+                } else {
+                    report.conflicts.push((task.id, task.title));
+                }
+            } else if dedup_by_title && titles.contains(&task.title) {
+                report.skipped += 1;
+            } else {
+                titles.insert(task.title.clone());
+                self.push(task);
+                report.added += 1;
+            }
+        }
+
+        report
+    }
+}
+
+impl TodoList {
+    /// Splits `incoming` into tasks this list doesn't have yet and `(existing, incoming)` pairs
+    /// whose content differs. Tasks that are already here unchanged are dropped
+    pub fn diff_import(&self, incoming: Vec<Task>) -> (Vec<Task>, Vec<TaskUpdate>) {
+        let mut new_tasks = vec![];
+        let mut updated_tasks = vec![];
+        for task in incoming {
+            match self.find_by_id(task.id) {
+                Some(existing) if !existing.same_content(&task) => {
+                    updated_tasks.push((existing.clone(), task));
+                }
+                Some(_) => {}
+                None => new_tasks.push(task),
+            }
+        }
+        (new_tasks, updated_tasks)
+    }
+
+    /// Applies a diff from [`TodoList::diff_import`], overwriting updated tasks in place
+    pub fn apply_import(&mut self, new_tasks: Vec<Task>, updated_tasks: Vec<TaskUpdate>) {
+        for (_, incoming) in updated_tasks {
+            if let Some(existing) = self.find_by_id_mut(incoming.id) {
+                *existing = incoming;
+            }
+        }
+        for task in new_tasks {
+            self.push(task);
+        }
+    }
+}
+
+impl TodoList {
+    /// Guesses statuses for tasks imported from plain text, which has none: tasks whose title
+    /// says they're done are completed, ones already past due are active and the rest are
+    /// upcoming. Every status is overwritten, so this is for lists fresh from an import
+    pub fn smart_status_inference(&mut self) {
+        let today = Local::now().date_naive();
+        for task in &mut self.items {
+            let says_done = task
+                .title
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|word| {
+                    DONE_WORDS
+                        .iter()
+                        .any(|done| word.eq_ignore_ascii_case(done))
+                });
+            task.mode = if says_done {
+                Status::Completed
+            } else if task.due_date.is_some_and(|due| due < today) {
+                Status::Active
+            } else {
+                Status::Upcoming
+            };
+        }
+    }
+}
+
+impl Task {
+    /// Compares everything the user can change, ignoring bookkeeping like modification times
+    pub fn same_content(&self, other: &Task) -> bool {
+        self.title == other.title
+            && self.info == other.info
+            && self.url == other.url
+            && self.start_date == other.start_date
+            && self.due_date == other.due_date
+            && self.mode == other.mode
+            && self.depends_on == other.depends_on
+            && self.parent == other.parent
+            && self.is_archived == other.is_archived
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_new_tasks() {
+        let mut list = TodoList::from_iter([(Status::Upcoming, "Mine", "")]);
+        let other = TodoList::from_iter([(Status::Active, "Theirs", "")]);
+
+        let report = list.merge(other, false);
+        assert_eq!(report.added, 1);
+        assert_eq!(list.items.len(), 2);
+        assert_eq!(list.items[1].title, "Theirs");
+    }
+
+    #[test]
+    fn dedups_by_title_only_when_asked() {
+        let other = || TodoList::from_iter([(Status::Upcoming, "Same title", "")]);
+
+        let mut list = TodoList::from_iter([(Status::Upcoming, "Same title", "")]);
+        assert_eq!(list.merge(other(), false).added, 1);
+
+        let mut list = TodoList::from_iter([(Status::Upcoming, "Same title", "")]);
+        let report = list.merge(other(), true);
+        assert_eq!(report.added, 0);
+        assert_eq!(report.skipped, 1);
+    }
+
+    #[test]
+    fn diffs_and_applies_an_import() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "Unchanged", ""),
+            (Status::Upcoming, "Original", ""),
+        ]);
+        let mut incoming: Vec<Task> = TodoList::from_iter([
+            (Status::Upcoming, "Unchanged", ""),
+            (Status::Active, "Edited", ""),
+            (Status::Upcoming, "Brand new", ""),
+        ])
+        .into();
+        incoming[0].id = list.items[0].id;
+        incoming[1].id = list.items[1].id;
+
+        let (new_tasks, updated_tasks) = list.diff_import(incoming);
+        assert_eq!(new_tasks.len(), 1);
+        assert_eq!(updated_tasks.len(), 1);
+        assert_eq!(updated_tasks[0].0.title, "Original");
+        assert_eq!(updated_tasks[0].1.title, "Edited");
+
+        list.apply_import(new_tasks, updated_tasks);
+        let titles: Vec<&str> = list.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["Unchanged", "Edited", "Brand new"]);
+        assert_eq!(list.items[1].mode, Status::Active);
+    }
+
+    #[test]
+    fn infers_statuses_from_titles_and_due_dates() {
+        let mut list = TodoList::from_iter([
+            (Status::Active, "Buy milk", ""),
+            (Status::Upcoming, "Taxes (DONE)", ""),
+            (Status::Upcoming, "Finished: the report", ""),
+            (Status::Upcoming, "Renew passport", ""),
+            (Status::Upcoming, "Abandoned project", ""),
+        ]);
+        list.items[3].due_date = Some(Local::now().date_naive() - chrono::Duration::days(1));
+
+        list.smart_status_inference();
+        let statuses: Vec<Status> = list.items.iter().map(|task| task.mode).collect();
+        assert_eq!(
+            statuses,
+            [
+                Status::Upcoming,
+                Status::Completed,
+                Status::Completed,
+                Status::Active,
+                // "done" inside another word doesn't count
+                Status::Upcoming,
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_identical_ids_and_reports_conflicts() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "Unchanged", ""),
+            (Status::Upcoming, "Original", ""),
+        ]);
+        let mut other = TodoList::from_iter([
+            (Status::Upcoming, "Unchanged", ""),
+            (Status::Upcoming, "Edited", ""),
+        ]);
+        other.items[0].id = list.items[0].id;
+        other.items[1].id = list.items[1].id;
+
+        let report = list.merge(other, false);
+        assert_eq!(report.added, 0);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(
+            report.conflicts,
+            vec![(list.items[1].id, "Edited".to_string())]
+        );
+        assert_eq!(list.items[1].title, "Original");
+    }
+}
@@ -0,0 +1,145 @@
+use std::{fs, io, path::PathBuf};
+
+use uuid::Uuid;
+
+use crate::{
+    app::{App, TodoList},
+    config::{self, NotesStorage},
+};
+
+impl App {
+    fn notes_path(&self, task_id: Uuid) -> Option<PathBuf> {
+        match &self.config().notes_storage {
+            NotesStorage::Inline => None,
+            NotesStorage::Files(dir) => Some(dir.join(format!("{task_id}.md"))),
+        }
+    }
+
+    /// Marks every task as having notes still to be read, after loading a tasks file that
+    /// left them out
+    pub fn mark_notes_unread(&mut self) {
+        self.notes_unread.clear();
+        if !matches!(self.config().notes_storage, NotesStorage::Inline) {
+            self.notes_unread = self.list.items.iter().map(|task| task.id).collect();
+        }
+    }
+
+    /// The task's notes, read from its file the first time they're needed when notes are
+    /// kept in files. Tasks from the tasks file have empty info until then
+    pub fn load_notes(&mut self, task_id: Uuid) -> String {
+        let Some(i) = self.list.position_of(task_id) else {
+            return String::new();
+        };
+        if let Some(path) = self.notes_path(task_id)
+            && self.notes_unread.remove(&task_id)
+        {
+            match fs::read_to_string(&path) {
+                Ok(notes) => self.list.items[i].info = notes,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => {
+                    self.log(&format!(
+                        "Couldn't read notes from {}: {error}",
+                        path.display()
+                    ));
+                    // Try again next time rather than let a save overwrite what's there
+                    self.notes_unread.insert(task_id);
+                }
+            }
+        }
+        self.list.items[i].info.clone()
+    }
+
+    pub fn load_selected_notes(&mut self) {
+        if let Some(task_id) = self
+            .list
+            .state
+            .selected()
+            .and_then(|i| self.list.items.get(i))
+            .map(|task| task.id)
+        {
+            self.load_notes(task_id);
+        }
+    }
+
+    /// For anything that looks at every task's info, like search and exports
+    pub fn load_all_notes(&mut self) {
+        if matches!(self.config().notes_storage, NotesStorage::Inline) {
+            return;
+        }
+        let ids: Vec<Uuid> = self.list.items.iter().map(|task| task.id).collect();
+        for task_id in ids {
+            self.load_notes(task_id);
+        }
+    }
+
+    /// Writes the task's notes to its file, removing the file when there's nothing to keep
+    pub fn save_notes(&self, task_id: Uuid, notes: &str) -> io::Result<()> {
+        let Some(path) = self.notes_path(task_id) else {
+            return Ok(());
+        };
+        if !notes.is_empty() {
+            return config::write_creating_dirs(&path, notes);
+        }
+        match fs::remove_file(&path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// The list as it should be written to the tasks file. With notes in files, that's the
+    /// notes that have been read written out, and every task's info left empty
+    pub fn list_for_saving(&self) -> io::Result<Option<TodoList>> {
+        if matches!(self.config().notes_storage, NotesStorage::Inline) {
+            return Ok(None);
+        }
+        for task in self
+            .list
+            .items
+            .iter()
+            .filter(|task| !self.notes_unread.contains(&task.id))
+        {
+            self.save_notes(task.id, &task.info)?;
+        }
+
+        let mut tasks = self.list.items.clone();
+        for task in &mut tasks {
+            task.info.clear();
+        }
+        let mut list = TodoList::from(tasks);
+        list.default_status = self.list.default_status;
+        Ok(Some(list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Status;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn notes_round_trip_through_files() {
+        let dir = temp_path("");
+        let mut app = App::default();
+        app.config_mut().notes_storage = NotesStorage::Files(dir.clone());
+        app.list = TodoList::from_iter([(Status::Upcoming, "Write", "the notes")]);
+        let id = app.list.items[0].id;
+
+        let saved = app.list_for_saving().unwrap().unwrap();
+        assert_eq!(saved.items[0].info, "");
+        assert_eq!(
+            fs::read_to_string(dir.join(format!("{id}.md"))).unwrap(),
+            "the notes"
+        );
+
+        // As if freshly loaded from the tasks file
+        app.list = saved;
+        app.mark_notes_unread();
+        assert_eq!(app.load_notes(id), "the notes");
+        assert_eq!(app.list.items[0].info, "the notes");
+
+        app.list.items[0].info.clear();
+        app.list_for_saving().unwrap();
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{Local, NaiveDate};
+
+use crate::app::{DUE_DATE_FORMAT, Status, Task};
+
+/// The part of a task a clause looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Status,
+    Due,
+    Tag,
+    Title,
+    /// Title or info, for words typed without a field
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `field:value`
+    Is,
+    /// `field<value`
+    Before,
+    /// `field>value`
+    After,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Status(Status),
+    Date(NaiveDate),
+    /// `due:none`, for tasks without a due date
+    Nothing,
+    /// Lowercased, matched as a substring
+    Text(String),
+}
+
+pub type Clause = (Field, Operator, Value);
+
+/// A search like `status:active due<tomorrow #home milk`, where every clause has to match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub clauses: Vec<Clause>,
+}
+
+impl Query {
+    /// Whether `input` should be parsed as a query rather than searched for as it is
+    pub fn is_structured(input: &str) -> bool {
+        input.contains([':', '<', '>'])
+    }
+
+    pub fn parse(input: &str) -> Result<Query, String> {
+        Self::parse_on(input, Local::now().date_naive())
+    }
+
+    /// Parses with `today` standing in for the current date, which `today` and `tomorrow`
+    /// are resolved against
+    pub fn parse_on(input: &str, today: NaiveDate) -> Result<Query, String> {
+        let clauses = input
+            .split_whitespace()
+            .map(|word| parse_clause(word, today))
+            .collect::<Result<_, _>>()?;
+        Ok(Query { clauses })
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        self.clauses
+            .iter()
+            .all(|clause| clause_matches(clause, task))
+    }
+}
+
+fn parse_clause(word: &str, today: NaiveDate) -> Result<Clause, String> {
+    if let Some(tag) = word.strip_prefix('#') {
+        return Ok((Field::Tag, Operator::Is, Value::Text(tag.to_lowercase())));
+    }
+    let Some(at) = word.find([':', '<', '>']) else {
+        return Ok((Field::Text, Operator::Is, Value::Text(word.to_lowercase())));
+    };
+
+    let (name, rest) = word.split_at(at);
+    let operator = match &rest[..1] {
+        "<" => Operator::Before,
+        ">" => Operator::After,
+        _ => Operator::Is,
+    };
+    let value = &rest[1..];
+    let field = match name.to_lowercase().as_str() {
+        "status" => Field::Status,
+        "due" => Field::Due,
+        "tag" => Field::Tag,
+        "title" => Field::Title,
+        _ => return Err(format!("Unknown field \"{name}\"")),
+    };
+    if operator != Operator::Is && field != Field::Due {
+        return Err(format!(
+            "Only due dates can be compared with < and >, in \"{word}\""
+        ));
+    }
+
+    let value =
+        match field {
+            Field::Status => Value::Status(
+                Status::from_name(value).ok_or_else(|| format!("Unknown status \"{value}\""))?,
+            ),
+            Field::Due => match value.to_lowercase().as_str() {
+                "none" if operator == Operator::Is => Value::Nothing,
+                "today" => Value::Date(today),
+                "tomorrow" => Value::Date(today.succ_opt().unwrap_or(today)),
+                _ => Value::Date(NaiveDate::parse_from_str(value, DUE_DATE_FORMAT).map_err(
+                    |_| format!("Due dates are YYYY-MM-DD, today or tomorrow: \"{value}\""),
+                )?),
+            },
+            Field::Tag | Field::Title | Field::Text => Value::Text(value.to_lowercase()),
+        };
+    Ok((field, operator, value))
+}
+
+fn clause_matches((field, operator, value): &Clause, task: &Task) -> bool {
+    match (field, value) {
+        (Field::Status, Value::Status(status)) => task.mode == *status,
+        (Field::Due, Value::Nothing) => task.due_date.is_none(),
+        (Field::Due, Value::Date(date)) => task.due_date.is_some_and(|due| match operator {
+            Operator::Is => due == *date,
+            Operator::Before => due < *date,
+            Operator::After => due > *date,
+        }),
+        (Field::Tag, Value::Text(text)) => {
+            task.tags().iter().any(|tag| tag.to_lowercase() == *text)
+        }
+        (Field::Title, Value::Text(text)) => task.title.to_lowercase().contains(text),
+        (Field::Text, Value::Text(text)) => {
+            task.title.to_lowercase().contains(text) || task.info.to_lowercase().contains(text)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 10).unwrap()
+    }
+
+    fn task(mode: Status, title: &str, due: Option<(u32, u32)>) -> Task {
+        let mut task = Task::new(mode, title, "");
+        task.due_date = due.and_then(|(month, day)| NaiveDate::from_ymd_opt(2026, month, day));
+        task
+    }
+
+    #[test]
+    fn parses_fields_operators_and_plain_words() {
+        let query = Query::parse_on("status:active due<tomorrow #Home milk", today()).unwrap();
+        assert_eq!(
+            query.clauses,
+            [
+                (Field::Status, Operator::Is, Value::Status(Status::Active)),
+                (
+                    Field::Due,
+                    Operator::Before,
+                    Value::Date(today().succ_opt().unwrap())
+                ),
+                (Field::Tag, Operator::Is, Value::Text("home".into())),
+                (Field::Text, Operator::Is, Value::Text("milk".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn every_clause_has_to_match() {
+        let query = Query::parse_on("status:active due:today", today()).unwrap();
+        assert!(query.matches(&task(Status::Active, "Now", Some((3, 10)))));
+        assert!(!query.matches(&task(Status::Upcoming, "Now", Some((3, 10)))));
+        assert!(!query.matches(&task(Status::Active, "Later", Some((3, 11)))));
+        assert!(!query.matches(&task(Status::Active, "Whenever", None)));
+
+        let query = Query::parse_on("due>2026-03-01 #home", today()).unwrap();
+        assert!(query.matches(&task(Status::Upcoming, "Vacuum #home", Some((3, 2)))));
+        assert!(!query.matches(&task(Status::Upcoming, "Vacuum #home", Some((3, 1)))));
+    }
+
+    #[test]
+    fn rejects_what_it_cant_match() {
+        assert!(Query::parse_on("priority:high", today()).is_err());
+        assert!(Query::parse_on("status:someday", today()).is_err());
+        assert!(Query::parse_on("status>active", today()).is_err());
+        assert!(Query::parse_on("due:soon", today()).is_err());
+    }
+}
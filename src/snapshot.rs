@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::{App, Task, TodoList};
+
+/// The parts of an `App` that key presses change, for tests to compare before and after
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSnapshot {
+    pub items: Vec<Task>,
+    pub selected: Option<usize>,
+    pub mode_name: String,
+}
+
+impl App {
+    pub fn snapshot(&self) -> AppSnapshot {
+        AppSnapshot {
+            items: self.list.items.clone(),
+            selected: self.list.state.selected(),
+            mode_name: self.mode_name().into(),
+        }
+    }
+
+    /// Puts the tasks, selection and mode back as they were in `snapshot`. Everything else,
+    /// like the config and text fields, is left alone
+    pub fn restore_snapshot(&mut self, snapshot: AppSnapshot) {
+        let mut list = TodoList::from(snapshot.items);
+        list.last_opened = self.list.last_opened;
+        list.default_status = self.list.default_status;
+        list.state.select(snapshot.selected);
+        self.list = list;
+        self.restore_mode(&snapshot.mode_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+    use crate::app::Status;
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key_events(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn titles(snapshot: &AppSnapshot) -> Vec<&str> {
+        snapshot
+            .items
+            .iter()
+            .map(|task| task.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn snapshots_track_key_presses_and_restore() {
+        let mut app = App::default();
+        app.list = TodoList::from_iter([
+            (Status::Upcoming, "First", ""),
+            (Status::Upcoming, "Second", ""),
+        ]);
+        let before = app.snapshot();
+        assert_eq!(before.mode_name, "view");
+        assert_eq!(before.selected, None);
+
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('h'));
+        let after = app.snapshot();
+        assert_eq!(titles(&after), ["Second"]);
+        assert_eq!(after.mode_name, "help");
+
+        app.restore_snapshot(before);
+        let restored = app.snapshot();
+        assert_eq!(titles(&restored), ["First", "Second"]);
+        assert_eq!(restored.selected, None);
+        assert_eq!(restored.mode_name, "view");
+        assert_eq!(app.list.position_of(restored.items[1].id), Some(1));
+    }
+
+    #[test]
+    fn renders_without_a_real_terminal() {
+        let mut app = App::default();
+        app.list = TodoList::from_iter([(Status::Active, "Draw me", "")]);
+        app.list.state.select(Some(0));
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(&mut app, frame.area()))
+            .unwrap();
+
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Draw me"));
+    }
+
+    #[test]
+    fn snapshots_serialize() {
+        let mut app = App::default();
+        app.list = TodoList::from_iter([(Status::Upcoming, "Saved", "")]);
+        let json = serde_json::to_string(&app.snapshot()).unwrap();
+
+        let snapshot: AppSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(titles(&snapshot), ["Saved"]);
+        assert_eq!(snapshot.mode_name, "view");
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{app::TodoList, config::StorageBackend};
+
+/// Loads that finish sooner than this go straight to the list, without the splash screen
+/// flashing up
+pub const SPLASH_DELAY: Duration = Duration::from_millis(200);
+const CHUNK_SIZE: usize = 64 * 1024;
+
+enum LoadProgress {
+    /// `read` of `total` bytes of the tasks file so far
+    Reading {
+        read: u64,
+        total: u64,
+    },
+    /// All read, and being turned into tasks
+    Parsing,
+    Done(io::Result<TodoList>),
+}
+
+/// Tasks being loaded on another thread, and how far along they are for the splash screen
+pub struct Loading {
+    receiver: Receiver<LoadProgress>,
+    /// How much is done, from 0 to 1
+    pub ratio: f64,
+    pub message: String,
+}
+
+impl Loading {
+    pub fn start(storage: StorageBackend) -> Loading {
+        let (sender, receiver) = mpsc::channel();
+        let message = format!("Reading {}", storage.path().display());
+        thread::spawn(move || {
+            let loaded = load(&storage, &sender);
+            // Nobody's waiting any more if the app quit while loading
+            let _ = sender.send(LoadProgress::Done(loaded));
+        });
+        Loading {
+            receiver,
+            ratio: 0.0,
+            message,
+        }
+    }
+
+    /// Takes in the progress sent so far, waiting up to `wait` for the tasks. Returns them
+    /// once they're loaded
+    pub fn poll(&mut self, wait: Duration) -> Option<io::Result<TodoList>> {
+        let deadline = Instant::now() + wait;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            let progress = match self.receiver.recv_timeout(wait) {
+                Ok(progress) => progress,
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Some(Err(io::Error::other("loading the tasks stopped partway")));
+                }
+            };
+            match progress {
+                LoadProgress::Reading { read, total } => {
+                    self.ratio = read as f64 / total.max(1) as f64;
+                }
+                LoadProgress::Parsing => {
+                    self.ratio = 1.0;
+                    self.message = "Loading tasks…".into();
+                }
+                LoadProgress::Done(loaded) => return Some(loaded),
+            }
+        }
+    }
+
+    /// Blocks until the tasks are loaded
+    pub fn wait(self) -> io::Result<TodoList> {
+        loop {
+            match self.receiver.recv() {
+                Ok(LoadProgress::Done(loaded)) => return loaded,
+                Ok(_) => {}
+                Err(_) => return Err(io::Error::other("loading the tasks stopped partway")),
+            }
+        }
+    }
+}
+
+/// Like `StorageBackend::load`, reading a tasks file a chunk at a time to say how far along
+/// it is. A database is loaded in one go
+fn load(storage: &StorageBackend, sender: &Sender<LoadProgress>) -> io::Result<TodoList> {
+    let StorageBackend::File(path) = storage else {
+        return storage.load();
+    };
+    if !storage.exists()? {
+        return storage.load();
+    }
+
+    let mut file = File::open(path)?;
+    let total = file.metadata()?.len();
+    let mut contents = Vec::with_capacity(total as usize);
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        contents.extend_from_slice(&chunk[..read]);
+        let read = contents.len() as u64;
+        let _ = sender.send(LoadProgress::Reading { read, total });
+    }
+
+    let _ = sender.send(LoadProgress::Parsing);
+    let contents = String::from_utf8(contents)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    TodoList::from_json_str(&contents, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Status, Task};
+    use crate::testing::temp_path;
+
+    #[test]
+    fn loads_a_tasks_file_in_the_background() {
+        let path = temp_path(".json");
+        let titles: Vec<String> = (0..5000).map(|i| format!("Task {i}")).collect();
+        let list = TodoList::from(
+            titles
+                .iter()
+                .map(|title| Task::new(Status::Upcoming, title, ""))
+                .collect::<Vec<_>>(),
+        );
+        list.save_json_file(&path).unwrap();
+
+        let mut loading = Loading::start(StorageBackend::File(path.clone()));
+        let loaded = loop {
+            if let Some(loaded) = loading.poll(Duration::from_millis(10)) {
+                break loaded.unwrap();
+            }
+        };
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.items.len(), 5000);
+        assert_eq!(loaded.items[4999].title, "Task 4999");
+    }
+}
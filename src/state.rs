@@ -0,0 +1,97 @@
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, Config, StorageBackend};
+
+pub const RECENT_FILES_LEN: usize = 10;
+
+/// What ratatodo remembers between runs on its own. It's kept out of the config file, which
+/// is only written when the user changes a setting from inside the app
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// Tasks files opened before, most recent first
+    pub recent_files: VecDeque<PathBuf>,
+    /// The tasks file last switched to, which launching opens instead of the configured one
+    pub last_opened: Option<PathBuf>,
+}
+
+impl State {
+    /// Reads the state file. A missing or unreadable one only forgets the recent files, so
+    /// it reads as empty rather than stopping the app from starting
+    pub fn load(path: &Path) -> State {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = toml::to_string(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        config::write_creating_dirs(path, contents)
+    }
+
+    /// Moves `path` to the front of the recent files, dropping the oldest past the limit
+    pub fn remember_file(&mut self, path: &Path) {
+        self.recent_files.retain(|recent| recent != path);
+        self.recent_files.push_front(path.to_path_buf());
+        self.recent_files.truncate(RECENT_FILES_LEN);
+    }
+
+    /// The tasks file last switched to while it's still there, or else what `config` says
+    pub fn launch_storage(&self, config: &Config, tasks_path: PathBuf) -> StorageBackend {
+        match &self.last_opened {
+            Some(path) if path.exists() => StorageBackend::for_path(path.clone()),
+            _ => config.storage_or(tasks_path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn recent_files_are_deduped_and_capped() {
+        let mut state = State::default();
+        for i in 0..RECENT_FILES_LEN + 2 {
+            state.remember_file(Path::new(&format!("{i}.json")));
+        }
+        state.remember_file(Path::new("5.json"));
+
+        assert_eq!(state.recent_files.len(), RECENT_FILES_LEN);
+        assert_eq!(state.recent_files[0], Path::new("5.json"));
+        assert_eq!(state.recent_files[1], Path::new("11.json"));
+        assert_eq!(
+            state
+                .recent_files
+                .iter()
+                .filter(|path| *path == Path::new("5.json"))
+                .count(),
+            1
+        );
+        assert!(!state.recent_files.contains(&PathBuf::from("0.json")));
+    }
+
+    #[test]
+    fn round_trips_and_forgets_broken_files() {
+        let path = temp_path(".toml");
+        let mut state = State::default();
+        state.remember_file(Path::new("/tmp/work.json"));
+        state.last_opened = Some("/tmp/work.json".into());
+        state.save(&path).unwrap();
+        assert_eq!(State::load(&path), state);
+
+        fs::write(&path, "recent_files = 3").unwrap();
+        assert_eq!(State::load(&path), State::default());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(State::load(&path), State::default());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::app::{App, Status};
+
+/// A piece of `status_bar_format`, parsed once so rendering is just filling in the numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusBarSegment {
+    Text(String),
+    Mode,
+    /// The selected task's position, counting from 1, or `-` when nothing is selected
+    Selected,
+    Total,
+    /// The share of tasks that are completed, as a whole percentage
+    Completed,
+}
+
+impl StatusBarSegment {
+    /// Splits `format` into its `{token}`s and the text around them. Unknown tokens and
+    /// unclosed braces are kept as text
+    pub fn parse(format: &str) -> Vec<StatusBarSegment> {
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut rest = format;
+
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let Some(end) = rest.find('}') else {
+                break;
+            };
+            let segment = match &rest[1..end] {
+                "mode" => StatusBarSegment::Mode,
+                "selected" => StatusBarSegment::Selected,
+                "total" => StatusBarSegment::Total,
+                "completed" => StatusBarSegment::Completed,
+                _ => {
+                    text.push_str(&rest[..=end]);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            };
+            if !text.is_empty() {
+                segments.push(StatusBarSegment::Text(std::mem::take(&mut text)));
+            }
+            segments.push(segment);
+            rest = &rest[end + 1..];
+        }
+
+        text.push_str(rest);
+        if !text.is_empty() {
+            segments.push(StatusBarSegment::Text(text));
+        }
+        segments
+    }
+}
+
+impl App {
+    /// The status bar with its tokens filled in
+    pub fn status_bar_text(&self) -> String {
+        // Counted as the list shows them, without the archived tasks
+        let total = self.list.shown_count();
+        let completed = self
+            .list
+            .items
+            .iter()
+            .filter(|task| !task.is_archived && task.mode == Status::Completed)
+            .count();
+
+        self.status_bar
+            .iter()
+            .map(|segment| match segment {
+                StatusBarSegment::Text(text) => text.clone(),
+                StatusBarSegment::Mode => self.mode_name().to_string(),
+                StatusBarSegment::Selected => match self.list.selected_row() {
+                    Some(row) if total > 0 => (row.min(total - 1) + 1).to_string(),
+                    _ => "-".to_string(),
+                },
+                StatusBarSegment::Total => total.to_string(),
+                StatusBarSegment::Completed => match total {
+                    0 => "0".to_string(),
+                    _ => (completed * 100 / total).to_string(),
+                },
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::TodoList;
+
+    #[test]
+    fn parses_tokens_and_keeps_unknown_ones_as_text() {
+        use StatusBarSegment::*;
+
+        assert_eq!(
+            StatusBarSegment::parse("{mode} | {selected}/{total} | {completed}% done"),
+            [
+                Mode,
+                Text(" | ".into()),
+                Selected,
+                Text("/".into()),
+                Total,
+                Text(" | ".into()),
+                Completed,
+                Text("% done".into()),
+            ]
+        );
+        assert_eq!(
+            StatusBarSegment::parse("{due} {total} {oops"),
+            [Text("{due} ".into()), Total, Text(" {oops".into())]
+        );
+        assert!(StatusBarSegment::parse("").is_empty());
+    }
+
+    #[test]
+    fn fills_in_the_tokens() {
+        let mut app = App::default();
+        app.list = TodoList::from_iter([
+            (Status::Completed, "Done", ""),
+            (Status::Upcoming, "Left", ""),
+            (Status::Upcoming, "Also left", ""),
+        ]);
+        app.status_bar = StatusBarSegment::parse("{mode}: {selected}/{total}, {completed}%");
+        assert_eq!(app.status_bar_text(), "view: -/3, 33%");
+
+        app.list.state.select(Some(1));
+        assert_eq!(app.status_bar_text(), "view: 2/3, 33%");
+
+        // Archived tasks aren't rows, so aren't counted
+        app.list.items[0].is_archived = true;
+        assert_eq!(app.status_bar_text(), "view: 1/2, 0%");
+    }
+}
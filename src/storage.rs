@@ -0,0 +1,311 @@
+use std::{fs, io, path::Path};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Serializer, Value, json, ser::PrettyFormatter};
+use uuid::Uuid;
+
+use crate::{
+    app::{Status, Task, TodoList},
+    config::{self, StorageBackend},
+    db,
+};
+
+pub const SCHEMA_VERSION: u64 = 1;
+
+#[derive(Serialize)]
+struct TasksFileRef<'a> {
+    version: u64,
+    last_opened: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_status: Option<Status>,
+    tasks: Vec<TaskRef<'a>>,
+}
+
+/// A task's fields in the order they read best in, what it's called first and when it was
+/// last touched at the end
+#[derive(Serialize)]
+struct TaskRef<'a> {
+    id: Uuid,
+    title: &'a str,
+    info: &'a str,
+    status: Status,
+    start_date: Option<NaiveDate>,
+    due_date: Option<NaiveDate>,
+    url: Option<&'a str>,
+    depends_on: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<Uuid>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    is_archived: bool,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl<'a> From<&'a Task> for TaskRef<'a> {
+    fn from(task: &'a Task) -> Self {
+        TaskRef {
+            id: task.id,
+            title: &task.title,
+            info: &task.info,
+            status: task.mode,
+            start_date: task.start_date,
+            due_date: task.due_date,
+            url: task.url.as_deref(),
+            depends_on: task.depends_on,
+            parent: task.parent,
+            is_archived: task.is_archived,
+            last_modified: task.last_modified,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TasksFile {
+    last_opened: Option<DateTime<Utc>>,
+    #[serde(default)]
+    default_status: Option<Status>,
+    tasks: Vec<Task>,
+}
+
+impl TodoList {
+    pub fn from_json_file(path: &Path) -> io::Result<TodoList> {
+        Self::from_json_str(&fs::read_to_string(path)?, path)
+    }
+
+    /// Parses the contents of a tasks file, migrating older versions. `path` is only used to
+    /// say where the contents came from in errors
+    pub fn from_json_str(contents: &str, path: &Path) -> io::Result<TodoList> {
+        let mut raw: Value = serde_json::from_str(contents)?;
+
+        let version = schema_version(&raw);
+        if version > SCHEMA_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} was written by a newer ratatodo (schema version {version})",
+                    path.display()
+                ),
+            ));
+        }
+        if version < 1 {
+            raw = migrate_v0_to_v1(raw);
+        }
+
+        let file: TasksFile = serde_json::from_value(raw)?;
+        let mut list = Self::from(file.tasks);
+        list.last_opened = file.last_opened;
+        list.default_status = file.default_status;
+        Ok(list)
+    }
+
+    pub fn save_json_file(&self, path: &Path) -> io::Result<()> {
+        config::write_creating_dirs(path, self.serialize_pretty())
+    }
+
+    /// What `save_json_file` writes, indented and with each task's fields in a fixed order
+    /// so the file is easy to read and diff
+    pub fn serialize_pretty(&self) -> String {
+        let file = TasksFileRef {
+            version: SCHEMA_VERSION,
+            last_opened: Utc::now(),
+            default_status: self.default_status,
+            tasks: self.items.iter().map(TaskRef::from).collect(),
+        };
+        let mut out = Vec::new();
+        let mut serializer =
+            Serializer::with_formatter(&mut out, PrettyFormatter::with_indent(b"  "));
+        file.serialize(&mut serializer)
+            .expect("tasks serialize to JSON");
+        String::from_utf8(out).expect("serde_json writes UTF-8")
+    }
+}
+
+impl StorageBackend {
+    /// The file the tasks live in, whichever format it is
+    pub fn path(&self) -> &Path {
+        match self {
+            StorageBackend::File(path) | StorageBackend::Sqlite(path) => path,
+        }
+    }
+
+    pub fn exists(&self) -> io::Result<bool> {
+        self.path().try_exists()
+    }
+
+    /// Loads the list, which is empty if nothing has been saved yet
+    pub fn load(&self) -> io::Result<TodoList> {
+        match self {
+            _ if !self.exists()? => Ok(TodoList::from_iter([])),
+            StorageBackend::File(path) => TodoList::from_json_file(path),
+            StorageBackend::Sqlite(path) => {
+                let conn = open_db(path)?;
+                db::load_list(&conn).map_err(io::Error::other)
+            }
+        }
+    }
+
+    pub fn save(&self, list: &TodoList) -> io::Result<()> {
+        match self {
+            StorageBackend::File(path) => list.save_json_file(path),
+            StorageBackend::Sqlite(path) => {
+                let mut conn = open_db(path)?;
+                db::save_list(&mut conn, list).map_err(io::Error::other)
+            }
+        }
+    }
+}
+
+fn open_db(path: &Path) -> io::Result<rusqlite::Connection> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let conn = rusqlite::Connection::open(path).map_err(io::Error::other)?;
+    db::init_db(&conn).map_err(io::Error::other)?;
+    Ok(conn)
+}
+
+/// Files without a version are either a bare task array or a wrapper that predates it
+fn schema_version(raw: &Value) -> u64 {
+    raw.get("version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Wraps a bare task array and fills in every field that version 1 requires
+pub fn migrate_v0_to_v1(raw: Value) -> Value {
+    let tasks = match raw {
+        Value::Array(tasks) => tasks,
+        Value::Object(mut object) => match object.remove("tasks") {
+            Some(Value::Array(tasks)) => tasks,
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+
+    let tasks: Vec<Value> = tasks
+        .into_iter()
+        .map(|mut task| {
+            if let Some(task) = task.as_object_mut() {
+                task.entry("id").or_insert_with(|| json!(Uuid::new_v4()));
+                task.entry("depends_on").or_insert(Value::Null);
+                task.entry("title").or_insert_with(|| json!(""));
+                task.entry("info").or_insert_with(|| json!(""));
+                task.entry("url").or_insert(Value::Null);
+                task.entry("due_date").or_insert(Value::Null);
+                task.entry("status").or_insert_with(|| json!("upcoming"));
+            }
+            task
+        })
+        .collect();
+
+    json!({ "version": 1, "tasks": tasks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Status;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn migrates_bare_array_with_missing_fields() {
+        let raw = json!([{ "title": "Old task" }, { "title": "Done", "status": "completed" }]);
+        let migrated = migrate_v0_to_v1(raw);
+
+        assert_eq!(migrated["version"], 1);
+        let file: TasksFile = serde_json::from_value(migrated).unwrap();
+        assert_eq!(file.tasks.len(), 2);
+        assert_eq!(file.tasks[0].title, "Old task");
+        assert_eq!(file.tasks[0].mode, Status::Upcoming);
+        assert_eq!(file.tasks[0].info, "");
+        assert_eq!(file.tasks[1].mode, Status::Completed);
+        assert_ne!(file.tasks[0].id, file.tasks[1].id);
+    }
+
+    #[test]
+    fn migration_keeps_existing_values() {
+        let id = Uuid::new_v4();
+        let raw =
+            json!({ "tasks": [{ "id": id, "title": "Keep", "info": "me", "status": "active" }] });
+        let file: TasksFile = serde_json::from_value(migrate_v0_to_v1(raw)).unwrap();
+
+        assert_eq!(file.tasks[0].id, id);
+        assert_eq!(file.tasks[0].info, "me");
+        assert_eq!(file.tasks[0].mode, Status::Active);
+    }
+
+    #[test]
+    fn pretty_json_orders_every_task_field() {
+        let mut list = TodoList::from_iter([(Status::Active, "Write tests", "for storage")]);
+        // Only saved when set
+        list.items[0].is_archived = true;
+        list.items[0].parent = Some(Uuid::new_v4());
+        let pretty = list.serialize_pretty();
+
+        let keys = [
+            "id",
+            "title",
+            "info",
+            "status",
+            "start_date",
+            "due_date",
+            "url",
+            "depends_on",
+            "parent",
+            "is_archived",
+            "last_modified",
+        ];
+        let positions: Vec<usize> = keys
+            .iter()
+            .map(|key| pretty.find(&format!("\n      \"{key}\": ")).unwrap())
+            .collect();
+        assert!(positions.is_sorted());
+
+        // Nothing the task itself would save gets left out
+        let task = serde_json::to_value(&list.items[0]).unwrap();
+        assert_eq!(task.as_object().unwrap().len(), keys.len());
+        let file: Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(file["tasks"][0], task);
+    }
+
+    #[test]
+    fn round_trips_current_version() {
+        let path = temp_path(".json");
+        let mut list = TodoList::from_iter([(Status::Active, "Write tests", "for storage")]);
+        list.default_status = Some(Status::Active);
+        list.save_json_file(&path).unwrap();
+
+        let raw: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(raw["version"], SCHEMA_VERSION);
+
+        let loaded = TodoList::from_json_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.items.len(), 1);
+        assert_eq!(loaded.items[0].id, list.items[0].id);
+        assert_eq!(loaded.items[0].title, "Write tests");
+        assert_eq!(loaded.items[0].mode, Status::Active);
+        assert_eq!(loaded.items[0].last_modified, list.items[0].last_modified);
+        assert!(loaded.last_opened.is_some());
+        assert_eq!(loaded.default_status, Some(Status::Active));
+    }
+
+    #[test]
+    fn loads_unversioned_files() {
+        let path = temp_path(".json");
+        fs::write(&path, r#"[{ "title": "From before versioning" }]"#).unwrap();
+
+        let loaded = TodoList::from_json_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.items[0].title, "From before versioning");
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let path = temp_path(".json");
+        fs::write(&path, r#"{ "version": 99, "tasks": [] }"#).unwrap();
+
+        let error = TodoList::from_json_file(&path)
+            .err()
+            .expect("newer versions should be rejected");
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
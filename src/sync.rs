@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use uuid::Uuid;
+
+use crate::{
+    app::{App, Task, TodoList},
+    config::{NotesStorage, SyncMode},
+};
+
+/// How often the watcher looks at the tasks file's modification time
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A task on the runtime telling whenever a file's modification time changes
+pub struct FileWatcher {
+    pub path: PathBuf,
+    changes: UnboundedReceiver<()>,
+    /// A change `wait` woke up for, which `changed` still has to report
+    woken: bool,
+}
+
+impl FileWatcher {
+    /// Has to be called from inside the Tokio runtime `App::run` is on
+    pub fn start(path: PathBuf) -> FileWatcher {
+        let (sender, changes) = mpsc::unbounded_channel();
+        let watched = path.clone();
+        tokio::spawn(async move {
+            let mut last = modified(&watched).await;
+            loop {
+                tokio::time::sleep(WATCH_INTERVAL).await;
+                let now = modified(&watched).await;
+                // A watcher that's been replaced stops at the next change it sees
+                if now != last && sender.send(()).is_err() {
+                    break;
+                }
+                last = now;
+            }
+        });
+        FileWatcher {
+            path,
+            changes,
+            woken: false,
+        }
+    }
+
+    /// Resolves at the next change, so the main loop can wake up for it
+    pub async fn wait(&mut self) {
+        match self.changes.recv().await {
+            Some(()) => self.woken = true,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Whether the file has changed since this was last asked
+    fn changed(&mut self) -> bool {
+        let mut changed = std::mem::take(&mut self.woken);
+        while self.changes.try_recv().is_ok() {
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Resolves when the watcher, if there is one, sees a change
+pub async fn wait_for_change(watcher: &mut Option<FileWatcher>) {
+    match watcher {
+        Some(watcher) => watcher.wait().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn modified(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path)
+        .await
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// A hash of what's in the file, `None` when there's no file
+pub fn file_hash(path: &Path) -> io::Result<Option<u64>> {
+    match fs::read(path) {
+        Ok(contents) => {
+            let mut hasher = DefaultHasher::new();
+            contents.hash(&mut hasher);
+            Ok(Some(hasher.finish()))
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+impl App {
+    /// Remembers the tasks file as this instance last loaded or wrote it, so the watcher can
+    /// tell its own saves from other instances'
+    pub fn mark_synced(&mut self) {
+        self.synced_hash = file_hash(self.tasks_path()).ok().flatten();
+        self.synced_at = Utc::now();
+    }
+
+    /// Picks up whatever another instance has written to the tasks file since it was last
+    /// loaded or saved here. Unsaved changes here are merged in rather than lost
+    pub fn sync_from_disk(&mut self) {
+        if self.config().sync_mode != SyncMode::FileWatch || self.splash().is_some() {
+            self.watcher = None;
+            return;
+        }
+        let path = self.tasks_path().to_path_buf();
+        let Some(watcher) = self.watcher.as_mut().filter(|watcher| watcher.path == path) else {
+            self.watcher = Some(FileWatcher::start(path));
+            return;
+        };
+        if !watcher.changed() {
+            return;
+        }
+
+        let hash = match file_hash(&path) {
+            Ok(hash) if hash == self.synced_hash => return,
+            Ok(hash) => hash,
+            Err(error) => {
+                self.log(&format!(
+                    "Couldn't check {} for changes: {error}",
+                    path.display()
+                ));
+                return;
+            }
+        };
+        let remote = match self.storage().load() {
+            Ok(remote) => remote,
+            Err(error) => {
+                let message = format!("Couldn't load changes to {}: {error}", path.display());
+                self.toast = Some(self.log_error(message));
+                return;
+            }
+        };
+
+        let selected = self
+            .list
+            .state
+            .selected()
+            .and_then(|i| self.list.items.get(i))
+            .map(|task| task.id);
+        let from_remote = if self.is_dirty() {
+            self.list.merge_synced(remote, self.synced_at)
+        } else {
+            let ids = remote.items.iter().map(|task| task.id).collect();
+            self.list.items = remote.items;
+            self.list.default_status = remote.default_status;
+            self.list.rebuild_index();
+            ids
+        };
+        self.list
+            .state
+            .select(selected.and_then(|id| self.list.position_of(id)));
+        // Their notes are still in their files, like when the tasks file was first loaded
+        if !matches!(self.config().notes_storage, NotesStorage::Inline) {
+            self.notes_unread.extend(from_remote);
+        }
+        self.synced_hash = hash;
+        self.synced_at = Utc::now();
+        self.toast = Some("Picked up changes from another window".into());
+    }
+}
+
+impl TodoList {
+    /// Merges in the list as another instance saved it, keeping whichever version of each
+    /// task was modified last. Tasks only here are kept if they were changed since `since`,
+    /// the last time the two agreed, and otherwise taken to have been deleted there. Returns
+    /// the ids of the tasks taken from `remote`
+    pub fn merge_synced(&mut self, remote: TodoList, since: DateTime<Utc>) -> Vec<Uuid> {
+        let order: Vec<Uuid> = self.items.iter().map(|task| task.id).collect();
+        let mut local: HashMap<Uuid, Task> = std::mem::take(&mut self.items)
+            .into_iter()
+            .map(|task| (task.id, task))
+            .collect();
+
+        let mut from_remote = vec![];
+        for task in remote.items {
+            match local.remove(&task.id) {
+                Some(mine) if mine.last_modified > task.last_modified => self.items.push(mine),
+                _ => {
+                    from_remote.push(task.id);
+                    self.items.push(task);
+                }
+            }
+        }
+        for id in order {
+            if let Some(mine) = local.remove(&id)
+                && mine.last_modified.is_some_and(|modified| modified > since)
+            {
+                self.items.push(mine);
+            }
+        }
+
+        self.default_status = remote.default_status;
+        self.rebuild_index();
+        from_remote
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Status;
+    use crate::testing::temp_path;
+
+    #[tokio::test]
+    async fn watcher_wakes_up_for_changes() {
+        let path = temp_path(".json");
+        fs::write(&path, "[]").unwrap();
+        let mut watcher = FileWatcher::start(path.clone());
+        // Let it see the file as it was before changing it
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        fs::write(&path, "[{}]").unwrap();
+        let woke = tokio::time::timeout(WATCH_INTERVAL * 10, watcher.wait()).await;
+        fs::remove_file(&path).unwrap();
+
+        assert!(woke.is_ok());
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+    }
+
+    #[test]
+    fn merging_keeps_the_latest_edit_of_each_task() {
+        let since = Utc::now() - chrono::Duration::hours(1);
+        let mut local = TodoList::from_iter([
+            (Status::Upcoming, "Edited here", ""),
+            (Status::Upcoming, "Edited there", ""),
+            (Status::Upcoming, "Deleted there", ""),
+            (Status::Upcoming, "Added here", ""),
+        ]);
+        let mut remote = TodoList::from(local.items[..3].to_vec());
+        remote.push(Task::new(Status::Active, "Added there", ""));
+
+        local.items[0].title = "Edited here, newer".into();
+        remote.items[0].last_modified = Some(since);
+        remote.items[1].title = "Edited there, newer".into();
+        local.items[1].last_modified = Some(since);
+        local.items[2].last_modified = Some(since - chrono::Duration::hours(1));
+        remote.items.remove(2);
+
+        let from_remote = local.merge_synced(remote, since);
+        let titles: Vec<&str> = local.items.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Edited here, newer",
+                "Edited there, newer",
+                "Added there",
+                "Added here"
+            ]
+        );
+        assert_eq!(from_remote, [local.items[1].id, local.items[2].id]);
+        assert_eq!(local.position_of(local.items[3].id), Some(3));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::app::{TitleError, TodoList};
+
+/// What the tag manager is asking for a name for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagEdit {
+    Rename,
+    /// Into another tag, which may already be on some of the same tasks
+    Merge,
+}
+
+impl TagEdit {
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            TagEdit::Rename => "rename to #",
+            TagEdit::Merge => "merge into #",
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TagReport {
+    pub changed: usize,
+    /// Tasks left alone because their title would have grown past `MAX_TITLE_LEN`
+    pub skipped: usize,
+}
+
+impl TodoList {
+    /// Every tag on any task, in order, with how many tasks have it
+    pub fn tag_counts(&self) -> Vec<(String, usize)> {
+        self.group_by_tag()
+            .into_iter()
+            .filter(|(tag, _)| !tag.is_empty())
+            .map(|(tag, tasks)| (tag, tasks.len()))
+            .collect()
+    }
+
+    /// Swaps `#tag` for `#replacement` in every title, or takes it out with no replacement.
+    /// A task that already has the replacement keeps just the one, and one whose title was
+    /// only the tag keeps the word without the `#`
+    pub fn replace_tag(&mut self, tag: &str, replacement: Option<&str>) -> TagReport {
+        let mut report = TagReport::default();
+        let old = format!("#{tag}");
+        let new = replacement.map(|name| format!("#{name}"));
+
+        for task in &mut self.items {
+            if !task.tags().contains(tag) {
+                continue;
+            }
+            let already_tagged = new
+                .as_ref()
+                .is_some_and(|new| task.title.split_whitespace().any(|word| word == new));
+            let mut words: Vec<&str> = Vec::new();
+            for word in task.title.split_whitespace() {
+                if word != old {
+                    words.push(word);
+                } else if let Some(new) = &new
+                    && !already_tagged
+                {
+                    words.push(new);
+                }
+            }
+            let title = if words.is_empty() {
+                tag.to_string()
+            } else {
+                words.join(" ")
+            };
+
+            if TitleError::check(&title).is_some() {
+                report.skipped += 1;
+                continue;
+            }
+            task.title = title;
+            task.touch();
+            report.changed += 1;
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::{Status, TodoList};
+
+    fn titles(list: &TodoList) -> Vec<&str> {
+        list.items.iter().map(|task| task.title.as_str()).collect()
+    }
+
+    #[test]
+    fn renames_merges_and_removes_tags() {
+        let mut list = TodoList::from_iter([
+            (Status::Upcoming, "Buy milk #shop", ""),
+            (Status::Upcoming, "#shop Bread #food", ""),
+            (Status::Upcoming, "#shop", ""),
+            (Status::Upcoming, "Call mum", ""),
+        ]);
+        assert_eq!(
+            list.tag_counts(),
+            [("food".to_string(), 1), ("shop".to_string(), 3)]
+        );
+
+        let report = list.replace_tag("shop", Some("errands"));
+        assert_eq!((report.changed, report.skipped), (3, 0));
+        assert_eq!(
+            titles(&list),
+            ["Buy milk #errands", "#errands Bread #food", "#errands", "Call mum"]
+        );
+
+        list.replace_tag("food", Some("errands"));
+        assert_eq!(titles(&list)[1], "#errands Bread");
+
+        list.replace_tag("errands", None);
+        assert_eq!(titles(&list), ["Buy milk", "Bread", "errands", "Call mum"]);
+        assert!(list.tag_counts().is_empty());
+    }
+}
@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+use crate::app::{App, Task, TodoList};
+
+/// A path in the temp directory that nothing else uses, ending in `suffix`. Tests that need a
+/// directory create it there themselves
+pub fn temp_path(suffix: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ratatodo-test-{}{suffix}", Uuid::new_v4()))
+}
+
+/// An app listing `tasks`, with the list's id index built for them
+pub fn app_with(tasks: Vec<Task>) -> App {
+    let mut app = App::default();
+    app.list = TodoList::from(tasks);
+    app
+}
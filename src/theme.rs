@@ -0,0 +1,161 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde::{Deserialize, Serialize};
+
+/// The built-in colour schemes. Everything is drawn in the terminal's named colours, which a
+/// theme swaps for its own once the frame has been rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// The terminal's own colours, untouched
+    #[default]
+    Default,
+    SolarizedDark,
+    Gruvbox,
+    Nord,
+    /// No colours at all, with backgrounds shown as reversed text instead
+    Monochrome,
+}
+
+/// What a theme draws each named colour as. The light variants share their colour
+struct Palette {
+    background: Color,
+    foreground: Color,
+    gray: Color,
+    red: Color,
+    green: Color,
+    yellow: Color,
+    blue: Color,
+    magenta: Color,
+    cyan: Color,
+}
+
+impl Palette {
+    const fn new(colors: [u32; 9]) -> Palette {
+        let [
+            background,
+            foreground,
+            gray,
+            red,
+            green,
+            yellow,
+            blue,
+            magenta,
+            cyan,
+        ] = colors;
+        Palette {
+            background: Color::from_u32(background),
+            foreground: Color::from_u32(foreground),
+            gray: Color::from_u32(gray),
+            red: Color::from_u32(red),
+            green: Color::from_u32(green),
+            yellow: Color::from_u32(yellow),
+            blue: Color::from_u32(blue),
+            magenta: Color::from_u32(magenta),
+            cyan: Color::from_u32(cyan),
+        }
+    }
+
+    /// `default` stands in for `Color::Reset`, the foreground or the background. Colours given
+    /// exactly, like the tailwind ones, are left as they are
+    fn map(&self, color: Color, default: Color) -> Color {
+        match color {
+            Color::Reset => default,
+            Color::Black => self.background,
+            Color::White | Color::Gray => self.foreground,
+            Color::DarkGray => self.gray,
+            Color::Red | Color::LightRed => self.red,
+            Color::Green | Color::LightGreen => self.green,
+            Color::Yellow | Color::LightYellow => self.yellow,
+            Color::Blue | Color::LightBlue => self.blue,
+            Color::Magenta | Color::LightMagenta => self.magenta,
+            Color::Cyan | Color::LightCyan => self.cyan,
+            color => color,
+        }
+    }
+}
+
+const SOLARIZED_DARK: Palette = Palette::new([
+    0x002b36, 0x839496, 0x586e75, 0xdc322f, 0x859900, 0xb58900, 0x268bd2, 0xd33682, 0x2aa198,
+]);
+const GRUVBOX: Palette = Palette::new([
+    0x282828, 0xebdbb2, 0x928374, 0xfb4934, 0xb8bb26, 0xfabd2f, 0x83a598, 0xd3869b, 0x8ec07c,
+]);
+const NORD: Palette = Palette::new([
+    0x2e3440, 0xd8dee9, 0x4c566a, 0xbf616a, 0xa3be8c, 0xebcb8b, 0x81a1c1, 0xb48ead, 0x88c0d0,
+]);
+
+impl Theme {
+    pub const ALL: [Theme; 5] = [
+        Theme::Default,
+        Theme::SolarizedDark,
+        Theme::Gruvbox,
+        Theme::Nord,
+        Theme::Monochrome,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Default => "Default",
+            Theme::SolarizedDark => "Solarized Dark",
+            Theme::Gruvbox => "Gruvbox",
+            Theme::Nord => "Nord",
+            Theme::Monochrome => "Monochrome",
+        }
+    }
+
+    /// Recolours everything already drawn in `buf`
+    pub fn apply(self, buf: &mut Buffer) {
+        let palette = match self {
+            Theme::Default => return,
+            Theme::SolarizedDark => &SOLARIZED_DARK,
+            Theme::Gruvbox => &GRUVBOX,
+            Theme::Nord => &NORD,
+            Theme::Monochrome => {
+                for cell in &mut buf.content {
+                    // Dimmed text stays dim, it's how hints are told from the rest
+                    if cell.fg != Color::DarkGray {
+                        cell.fg = Color::Reset;
+                    }
+                    if cell.bg != Color::Reset {
+                        cell.bg = Color::Reset;
+                        cell.modifier |= Modifier::REVERSED;
+                    }
+                }
+                return;
+            }
+        };
+        for cell in &mut buf.content {
+            cell.fg = palette.map(cell.fg, palette.foreground);
+            cell.bg = palette.map(cell.bg, palette.background);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn swaps_named_colours_for_the_theme_ones() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buf[(0, 0)].set_style(Style::new().fg(Color::Blue));
+        buf[(1, 0)].set_style(Style::new().fg(Color::Rgb(1, 2, 3)).bg(Color::Yellow));
+        let mut monochrome = buf.clone();
+
+        Theme::Nord.apply(&mut buf);
+        assert_eq!(buf[(0, 0)].fg, NORD.blue);
+        assert_eq!(buf[(0, 0)].bg, NORD.background);
+        assert_eq!(buf[(1, 0)].fg, Color::Rgb(1, 2, 3));
+        assert_eq!(buf[(1, 0)].bg, NORD.yellow);
+        assert_eq!(buf[(2, 0)].fg, NORD.foreground);
+
+        Theme::Monochrome.apply(&mut monochrome);
+        assert_eq!(monochrome[(0, 0)].fg, Color::Reset);
+        assert_eq!(monochrome[(1, 0)].bg, Color::Reset);
+        assert!(monochrome[(1, 0)].modifier.contains(Modifier::REVERSED));
+    }
+}
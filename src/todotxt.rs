@@ -0,0 +1,202 @@
+use std::{fs, io, path::Path};
+
+use crate::app::{App, Status, Task};
+
+pub const DEFAULT_TODOTXT_PATH: &str = "todo.txt";
+
+// todo.txt has no "in progress" state, so Active tasks carry it as a key:value tag
+const ACTIVE_TAG: &str = "status:active";
+
+impl Task {
+    pub fn to_todotxt(&self) -> String {
+        match self.mode {
+            Status::Upcoming => self.title.clone(),
+            Status::Active => format!("{} {}", self.title, ACTIVE_TAG),
+            Status::Completed => format!("x {}", self.title),
+        }
+    }
+
+    pub fn from_todotxt(s: &str) -> io::Result<Task> {
+        let mut tokens = s.split_whitespace().peekable();
+        let mut mode = Status::Upcoming;
+
+        if tokens.next_if_eq(&"x").is_some() {
+            mode = Status::Completed;
+        }
+
+        // Priority isn't tracked on tasks, so it is accepted and dropped
+        tokens.next_if(|token| is_priority(token));
+
+        // Completion and creation dates aren't tracked either
+        tokens.next_if(|token| is_date(token));
+        tokens.next_if(|token| is_date(token));
+
+        let mut words: Vec<&str> = vec![];
+        for token in tokens {
+            if token == ACTIVE_TAG {
+                if mode == Status::Upcoming {
+                    mode = Status::Active;
+                }
+            } else {
+                words.push(token);
+            }
+        }
+
+        if words.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("todo.txt line has no title: {s:?}"),
+            ));
+        }
+
+        Ok(Task::new(mode, &words.join(" "), ""))
+    }
+}
+
+impl App {
+    pub fn export_todotxt(&self, path: &Path) -> io::Result<()> {
+        let mut contents = String::new();
+        for task in &self.list.items {
+            contents.push_str(&task.to_todotxt());
+            contents.push('\n');
+        }
+        fs::write(path, contents)
+    }
+
+    pub fn import_todotxt(&mut self, path: &Path) -> io::Result<usize> {
+        let contents = fs::read_to_string(path)?;
+        let tasks = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Task::from_todotxt)
+            .collect::<io::Result<Vec<Task>>>()?;
+
+        let count = tasks.len();
+        for task in tasks {
+            self.list.push(task);
+        }
+        Ok(count)
+    }
+}
+
+fn is_priority(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() == 3 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')'
+}
+
+fn is_date(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Task {
+        Task::from_todotxt(line).expect("line should parse")
+    }
+
+    #[test]
+    fn parses_plain_title() {
+        let task = parse("Buy milk");
+        assert_eq!(task.title, "Buy milk");
+        assert_eq!(task.mode, Status::Upcoming);
+        assert_eq!(task.info, "");
+    }
+
+    #[test]
+    fn parses_completed_marker() {
+        let task = parse("x Buy milk");
+        assert_eq!(task.mode, Status::Completed);
+        assert_eq!(task.title, "Buy milk");
+    }
+
+    #[test]
+    fn x_must_be_a_separate_token() {
+        let task = parse("xylophone lessons");
+        assert_eq!(task.mode, Status::Upcoming);
+        assert_eq!(task.title, "xylophone lessons");
+
+        let task = parse("X marks the spot");
+        assert_eq!(task.mode, Status::Upcoming);
+    }
+
+    #[test]
+    fn skips_priority_and_dates() {
+        let task = parse("x (A) 2024-01-15 2024-01-10 Task title +project @context due:2024-02-01");
+        assert_eq!(task.mode, Status::Completed);
+        assert_eq!(task.title, "Task title +project @context due:2024-02-01");
+
+        let task = parse("(B) 2024-01-10 Call mom");
+        assert_eq!(task.mode, Status::Upcoming);
+        assert_eq!(task.title, "Call mom");
+    }
+
+    #[test]
+    fn priority_is_only_recognised_in_position() {
+        let task = parse("Call (A) mom");
+        assert_eq!(task.title, "Call (A) mom");
+
+        let task = parse("(a) lowercase is not a priority");
+        assert_eq!(task.title, "(a) lowercase is not a priority");
+    }
+
+    #[test]
+    fn dates_are_only_recognised_in_position() {
+        let task = parse("Pay rent 2024-02-01");
+        assert_eq!(task.title, "Pay rent 2024-02-01");
+
+        let task = parse("2024-1-10 is not a date");
+        assert_eq!(task.title, "2024-1-10 is not a date");
+    }
+
+    #[test]
+    fn parses_active_tag_anywhere() {
+        let task = parse("Write report status:active +work");
+        assert_eq!(task.mode, Status::Active);
+        assert_eq!(task.title, "Write report +work");
+    }
+
+    #[test]
+    fn completed_wins_over_active_tag() {
+        let task = parse("x Write report status:active");
+        assert_eq!(task.mode, Status::Completed);
+        assert_eq!(task.title, "Write report");
+    }
+
+    #[test]
+    fn collapses_whitespace() {
+        let task = parse("  Buy \t milk  ");
+        assert_eq!(task.title, "Buy milk");
+    }
+
+    #[test]
+    fn rejects_lines_without_a_title() {
+        for line in ["", "   ", "x", "x (A)", "(A) 2024-01-15", "status:active"] {
+            let error = Task::from_todotxt(line).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn round_trips_every_status() {
+        for mode in [Status::Upcoming, Status::Active, Status::Completed] {
+            let task = Task::new(mode, "Task title +project @context", "");
+            let parsed = parse(&task.to_todotxt());
+            assert_eq!(parsed.mode, mode);
+            assert_eq!(parsed.title, task.title);
+        }
+    }
+
+    #[test]
+    fn serializes_each_status() {
+        assert_eq!(Task::new(Status::Upcoming, "a", "").to_todotxt(), "a");
+        assert_eq!(Task::new(Status::Active, "a", "").to_todotxt(), "a status:active");
+        assert_eq!(Task::new(Status::Completed, "a", "").to_todotxt(), "x a");
+    }
+}
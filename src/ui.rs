@@ -1,20 +1,38 @@
-use crate::app::{App, CurrentlyEditing, Status};
+use crate::{
+    app::{
+        App, CurrentlyEditing, DUE_DATE_FORMAT, DetailTab, FORECAST_DAYS, LayoutMode, Status,
+        Task,
+    },
+    config::{Column, FieldFormat, PreviewFormat},
+    history::HistoryTextField,
+    import::{self, ImportFormat, MergeStrategy, WizardStep},
+    theme::Theme,
+};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style, Stylize},
+    style::{Color, Modifier, Style, Stylize, palette::tailwind::ORANGE},
     symbols::border,
     text::{Line, Span},
     widgets::{
-        Block, BorderType, Borders, HighlightSpacing, List, ListItem, Padding, Paragraph,
-        StatefulWidget, Widget, Wrap,
+        Block, BorderType, Borders, Cell, Clear, Gauge, HighlightSpacing, List, ListItem,
+        ListState, Padding, Paragraph, Row, StatefulWidget, Table, TableState, Widget, Wrap,
     },
 };
+use chrono::{Datelike, Local, NaiveDate};
+use serde_json::Value;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 const SELECTED_STYLE: Style = Style::new().add_modifier(Modifier::BOLD);
+const NUDGE_BG_COLOR: Color = ORANGE.c800;
+/// Wide enough for a date like `Wed 14 Oct` and most of a short title
+const TIMELINE_COLUMN_WIDTH: u16 = 16;
+/// Room for the day of the month above each day's column in the Gantt chart
+const GANTT_DAY_WIDTH: u16 = 3;
 
 impl App {
     pub fn render_view_mode(&mut self, area: Rect, buf: &mut Buffer) {
+        self.load_selected_notes();
         let title = Line::from(" Ratatodo ".bold());
         let instructions = Line::from(vec![
             " [".into(),
@@ -37,14 +55,30 @@ impl App {
             .padding(Padding::vertical(1))
             .border_type(BorderType::Rounded);
 
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Fill(1), Constraint::Length(3)])
-            .split(Block::inner(&block, area));
+        let [content_area, command_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+                .areas(Block::inner(&block, area));
+
+        let layout = match self.layout_mode {
+            LayoutMode::Vertical => Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(vec![Constraint::Fill(1), Constraint::Length(5)])
+                .split(content_area),
+            LayoutMode::Horizontal => Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![Constraint::Percentage(40), Constraint::Percentage(60)])
+                .spacing(1)
+                .split(content_area),
+        };
 
         block.render(area, buf);
-        self.render_list(layout[0], buf);
-        self.render_selected_item(layout[1], buf);
+        if self.group_by_tag {
+            self.render_grouped_by_tag(layout[0], buf);
+        } else {
+            self.render_list_with_virtual_scroll(layout[0], buf);
+        }
+        self.render_selected_item_v2(layout[1], buf);
+        self.render_command_line(command_area, buf);
     }
 
     pub fn render_edit_mode(&mut self, area: Rect, buf: &mut Buffer) {
@@ -59,6 +93,9 @@ impl App {
             " [".into(),
             "Enter".blue().bold(),
             "] Submit".into(),
+            " [".into(),
+            "F3".blue().bold(),
+            "] Preview ".into(),
         ]);
 
         let block = Block::bordered()
@@ -67,122 +104,2019 @@ impl App {
             .padding(Padding::uniform(1))
             .border_type(BorderType::Rounded);
 
+        let inner = Block::inner(&block, area);
+        let (form_area, preview_area) = if self.show_preview {
+            let [form, preview] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .spacing(1)
+                    .areas(inner);
+            (form, Some(preview))
+        } else {
+            (inner, None)
+        };
+
         let layout = Layout::default()
             .direction(Direction::Vertical)
-            .constraints(vec![Constraint::Percentage(30), Constraint::Percentage(70)])
-            .split(Block::inner(&block, area));
+            .constraints(vec![
+                Constraint::Percentage(30),
+                Constraint::Length(1),
+                Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ])
+            .split(form_area);
 
         let title_border_style = match self.currently_editing {
             CurrentlyEditing::Title => BorderType::Double,
-            CurrentlyEditing::Info => BorderType::Plain,
+            _ => BorderType::Plain,
+        };
+
+        let url_border_style = match self.currently_editing {
+            CurrentlyEditing::Url => BorderType::Double,
+            _ => BorderType::Plain,
+        };
+
+        let due_date_border_style = match self.currently_editing {
+            CurrentlyEditing::DueDate => BorderType::Double,
+            _ => BorderType::Plain,
         };
 
         let info_border_style = match self.currently_editing {
             CurrentlyEditing::Info => BorderType::Double,
-            CurrentlyEditing::Title => BorderType::Plain,
+            _ => BorderType::Plain,
+        };
+
+        let markdown_hint = |field| match self.field_format(field) {
+            FieldFormat::Markdown => " (Markdown) ",
+            _ => " ",
         };
 
         let title_block = Block::bordered()
-            .title(Line::raw(" Task Title "))
+            .title(Line::raw(format!(" Task Title{}", markdown_hint(CurrentlyEditing::Title))))
             .border_type(title_border_style)
             .padding(Padding::uniform(1));
 
+        let url_block = Block::bordered()
+            .title(Line::raw(" URL (optional) "))
+            .border_type(url_border_style)
+            .padding(Padding::horizontal(1));
+
+        let due_date_block = Block::bordered()
+            .title(Line::raw(" Due Date (YYYY-MM-DD) "))
+            .border_type(due_date_border_style)
+            .padding(Padding::horizontal(1));
+
         let info_block = Block::bordered()
-            .title(Line::raw(" Task Details "))
+            .title(Line::raw(format!(" Task Details{}", markdown_hint(CurrentlyEditing::Info))))
             .border_type(info_border_style)
             .padding(Padding::uniform(1));
 
         let title_cursor_style = match self.currently_editing {
             CurrentlyEditing::Title => Style::reversed(Style::default()),
-            CurrentlyEditing::Info => Style::default(),
+            _ => Style::default(),
+        };
+
+        let url_cursor_style = match self.currently_editing {
+            CurrentlyEditing::Url => Style::reversed(Style::default()),
+            _ => Style::default(),
+        };
+
+        let due_date_cursor_style = match self.currently_editing {
+            CurrentlyEditing::DueDate => Style::reversed(Style::default()),
+            _ => Style::default(),
         };
 
         let info_cursor_style = match self.currently_editing {
             CurrentlyEditing::Info => Style::reversed(Style::default()),
-            CurrentlyEditing::Title => Style::default(),
+            _ => Style::default(),
+        };
+
+        let title_field = Paragraph::new(text_field_line(&self.title_field, title_cursor_style))
+            .wrap(Wrap { trim: true })
+            .block(title_block);
+
+        let url_field =
+            Paragraph::new(text_field_line(&self.url_field, url_cursor_style)).block(url_block);
+
+        let due_date_field =
+            Paragraph::new(text_field_line(&self.due_date_field, due_date_cursor_style))
+                .block(due_date_block);
+
+        let info_field = Paragraph::new(text_field_line(&self.info_field, info_cursor_style))
+            .wrap(Wrap { trim: true })
+            .block(info_block);
+
+        let title_error = match self.title_error() {
+            Some(error) => Line::from(error.message().red()),
+            None => Line::default(),
         };
 
-        let title_field = Paragraph::new(Line::from(vec![
-            Span::raw(self.title_field.clone()),
-            Span::styled(" ", title_cursor_style),
-        ]))
-        .wrap(Wrap { trim: true })
-        .block(title_block);
+        let field_error = match self.due_date_error().or(self.url_error()) {
+            Some(error) => Line::from(error.red()),
+            None => Line::default(),
+        };
 
-        let info_field = Paragraph::new(Line::from(vec![
-            Span::raw(self.info_field.clone()),
-            Span::styled(" ", info_cursor_style),
-        ]))
-        .wrap(Wrap { trim: true })
-        .block(info_block);
+        let [url_area, due_date_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(26)]).areas(layout[2]);
 
         block.render(area, buf);
         title_field.render(layout[0], buf);
-        info_field.render(layout[1], buf);
+        title_error.render(layout[1], buf);
+        url_field.render(url_area, buf);
+        due_date_field.render(due_date_area, buf);
+        field_error.render(layout[3], buf);
+        info_field.render(layout[4], buf);
+
+        // The focused field's cursor is the one reversed cell in it, wherever wrapping put it
+        let focused_area = match self.currently_editing {
+            CurrentlyEditing::Title => layout[0],
+            CurrentlyEditing::Url => url_area,
+            CurrentlyEditing::DueDate => due_date_area,
+            CurrentlyEditing::Info => layout[4],
+        };
+        self.edit_cursor = focused_area
+            .positions()
+            .find(|&position| buf[position].modifier.contains(Modifier::REVERSED));
+
+        if let Some(preview_area) = preview_area {
+            self.render_export_preview(preview_area, buf);
+        }
     }
 
-    pub fn render_help_mode(&mut self, area: Rect, buf: &mut Buffer) {
-        Line::raw("Help Screen").render(area, buf);
+    fn render_export_preview(&self, area: Rect, buf: &mut Buffer) {
+        let format = self.config().preview_format;
+        let name = match format {
+            PreviewFormat::Markdown => "Markdown",
+            PreviewFormat::TodoTxt => "todo.txt",
+            PreviewFormat::Ical => "iCalendar",
+            PreviewFormat::Json => "JSON",
+            PreviewFormat::Org => "Org",
+        };
+        let block = Block::bordered()
+            .title(Line::raw(format!(" Preview ({name}) ")))
+            .padding(Padding::horizontal(1));
+
+        Paragraph::new(self.draft_task().export_preview(format))
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
     }
 
-    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
-        let items: Vec<ListItem> = self
+    pub fn render_splash(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some((ratio, message)) = self.splash() else {
+            return;
+        };
+
+        let block = Block::bordered()
+            .title(Line::from(" Ratatodo ".bold()).centered())
+            .padding(Padding::horizontal(1))
+            .border_type(BorderType::Rounded);
+        let area = area.centered(Constraint::Percentage(60), Constraint::Length(5));
+        let [message_area, gauge_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)])
+                .spacing(1)
+                .areas(block.inner(area));
+        block.render(area, buf);
+
+        Line::raw(message.to_string()).centered().render(message_area, buf);
+        Gauge::default()
+            .gauge_style(SELECTED_STYLE)
+            .ratio(ratio.clamp(0.0, 1.0))
+            .render(gauge_area, buf);
+    }
+
+    pub fn render_welcome_mode(&mut self, area: Rect, buf: &mut Buffer) {
+        let key = |key: &'static str, action: &'static str| {
+            Line::from(vec![format!("{key:>7}").blue().bold(), format!("  {action}").into()])
+        };
+
+        let lines = vec![
+            Line::from("Your tasks, right in the terminal.").centered(),
+            Line::default(),
+            key("n", "Create a new task"),
+            key("e", "Edit the selected task"),
+            key("t", "Cycle the status of the selected task"),
+            key("d", "Delete the selected task"),
+            key("j / k", "Move the selection down / up"),
+            key("Enter", "Show the selected task in full"),
+            key(":", "Run a command"),
+            key("h", "Show help"),
+            key("q", "Quit"),
+            Line::default(),
+            Line::from("Press any key to continue".italic()).centered(),
+        ];
+
+        let block = Block::bordered()
+            .title(Line::from(" Welcome to Ratatodo ".bold()).centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let height = lines.len() as u16 + 4;
+        Paragraph::new(lines)
+            .block(block)
+            .render(area.centered(Constraint::Length(56), Constraint::Length(height)), buf);
+    }
+
+    pub fn render_inspector_mode(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = Line::from(" Inspector ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "J/K".blue().bold(),
+            "] Scroll".into(),
+            " [".into(),
+            "Ctrl+J".blue().bold(),
+            "] Edit".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let json = match self.list.state.selected().and_then(|i| self.list.items.get(i)) {
+            Some(task) => serde_json::to_string_pretty(task)
+                .unwrap_or_else(|error| format!("Couldn't serialize task: {error}")),
+            None => "Nothing selected...".to_string(),
+        };
+
+        // No wrapping, so long values run off the edge instead of splitting mid-field
+        Paragraph::new(json)
+            .block(block)
+            .scroll((self.inspector_scroll, 0))
+            .render(area, buf);
+    }
+
+    pub fn render_json_editor(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some((field, error)) = self.json_editor() else {
+            return;
+        };
+        let title = Line::from(" Edit JSON ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "Ctrl+S".blue().bold(),
+            "] Save".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [error_area, editor_area] = Layout::vertical([
+            Constraint::Length(if error.is_some() { 2 } else { 0 }),
+            Constraint::Fill(1),
+        ])
+        .areas(inner);
+        if let Some(error) = error {
+            Line::from(format!(" {error} "))
+                .style(Style::new().white().on_red().bold())
+                .render(error_area, buf);
+        }
+
+        let (lines, cursor_row) = text_field_lines(field, Style::new().reversed());
+        // Keeps the cursor's row on screen, scrolling no further than needed
+        let scroll = (cursor_row + 1).saturating_sub(editor_area.height as usize);
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .render(editor_area, buf);
+    }
+
+    pub fn render_split(&mut self, area: Rect, buf: &mut Buffer) {
+        let columns = self.config().visible_columns.clone();
+        let spacing = self.config().highlight_spacing.clone();
+        let Some((list, name, project, focus_right)) = self.split_panes() else {
+            return;
+        };
+
+        let instructions = Line::from(vec![
+            " [".into(),
+            "H/L".blue().bold(),
+            "] Pane".into(),
+            " [".into(),
+            "X".blue().bold(),
+            "] Send".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(area);
+
+        let title = format!(" {name} ");
+        for (pane, area, title, focused) in [
+            (list, left, " Tasks ", !focus_right),
+            (project, right, title.as_str(), focus_right),
+        ] {
+            // Double borders for the focused pane, like the focused field in the edit form
+            let mut block = Block::bordered()
+                .title(Line::from(title.bold()))
+                .padding(Padding::horizontal(1))
+                .border_type(if focused { BorderType::Double } else { BorderType::Rounded });
+            if focused {
+                block = block.title_bottom(instructions.clone().centered());
+            }
+
+            let items: Vec<ListItem> = pane
+                .items
+                .iter()
+                .map(|task| ListItem::new(task.to_line(&columns)))
+                .collect();
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(if focused { SELECTED_STYLE } else { Style::new() })
+                .highlight_symbol(">")
+                .highlight_spacing(spacing.clone());
+            StatefulWidget::render(list, area, buf, &mut pane.state);
+        }
+    }
+
+    pub fn render_minimap(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(cursor) = self.minimap_cursor() else {
+            return;
+        };
+
+        let title = Line::from(" Minimap ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "HJKL".blue().bold(),
+            "] Move".into(),
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Jump".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+        let [grid_area, _, footer_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(Block::inner(&block, area));
+        block.render(area, buf);
+
+        let width = usize::from(grid_area.width).clamp(1, 80);
+        let height = usize::from(grid_area.height).max(1);
+        // Scroll just far enough to keep the cursor's row on screen
+        let cursor_row = cursor / width;
+        self.minimap_scroll = self.minimap_scroll.clamp(
+            (cursor_row + 1).saturating_sub(height),
+            cursor_row,
+        );
+        self.minimap_grid = Rect { width: width as u16, ..grid_area };
+
+        let lines: Vec<Line> = self
             .list
             .items
-            .iter()
-            .map(|todo_item| ListItem::from(todo_item))
+            .chunks(width)
+            .enumerate()
+            .skip(self.minimap_scroll)
+            .take(height)
+            .map(|(row, tasks)| {
+                let cells = tasks.iter().enumerate().map(|(column, task)| {
+                    let color = match task.mode {
+                        Status::Upcoming => Color::DarkGray,
+                        Status::Active => Color::Yellow,
+                        Status::Completed => Color::Green,
+                    };
+                    let style = if row * width + column == cursor {
+                        Style::new().fg(color).reversed()
+                    } else {
+                        Style::new().fg(color)
+                    };
+                    Span::styled("█", style)
+                });
+                Line::from(cells.collect::<Vec<_>>())
+            })
             .collect();
+        Paragraph::new(lines).render(grid_area, buf);
 
-        let list = List::new(items)
-            .highlight_style(SELECTED_STYLE)
-            .highlight_symbol(">")
-            .highlight_spacing(HighlightSpacing::Always);
-
-        StatefulWidget::render(list, area, buf, &mut self.list.state);
+        let footer = match self.list.items.get(cursor) {
+            Some(task) => format!("{}/{} {}", cursor + 1, self.list.items.len(), task.title),
+            None => "No tasks".into(),
+        };
+        Line::from(footer).render(footer_area, buf);
     }
 
-    fn render_selected_item(&self, area: Rect, buf: &mut Buffer) {
-        let mut lines: Vec<Line<'_>> = vec![];
-        let task = if let Some(i) = self.list.state.selected() {
-            match self.list.items[i].mode {
-                Status::Upcoming => format!("{} ", self.list.items[i].title),
-                Status::Active => format!("{} ", self.list.items[i].title),
-                Status::Completed => format!("{} ", self.list.items[i].title),
+    pub fn render_timeline(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some((offset, selected)) = self.timeline_state() else {
+            return;
+        };
+
+        let title = Line::from(" Timeline ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "H/L".blue().bold(),
+            "] Scroll".into(),
+            " [".into(),
+            "J/K".blue().bold(),
+            "] Select".into(),
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Jump".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+        let inner = Block::inner(&block, area);
+        block.render(area, buf);
+
+        let today = Local::now().date_naive();
+        let timeline = self.timeline(today);
+        if timeline.is_empty() {
+            Line::raw("No tasks have a due date").render(inner, buf);
+            return;
+        }
+
+        // Overdue tasks all share the first column, so they're never scrolled away
+        let has_overdue = timeline.first().is_some_and(|&(day, _)| day < 0);
+        let columns = inner.width / TIMELINE_COLUMN_WIDTH;
+        let days = usize::from(columns.saturating_sub(u16::from(has_overdue))).max(1);
+        self.timeline_days = days;
+
+        let headers = has_overdue.then_some(None).into_iter().chain(
+            (offset..offset + days).map(|day| Some(today + chrono::Days::new(day as u64))),
+        );
+        let areas = Layout::horizontal(vec![
+            Constraint::Length(TIMELINE_COLUMN_WIDTH);
+            days + usize::from(has_overdue)
+        ])
+        .split(inner);
+
+        for (date, &area) in headers.zip(areas.iter()) {
+            let (header, header_style) = match date {
+                Some(date) => (date.format("%a %d %b").to_string(), Style::new().bold()),
+                None => ("Overdue".to_string(), Style::new().bold().red()),
+            };
+            let mut lines = vec![
+                Line::styled(header, header_style),
+                Line::raw("─".repeat(usize::from(TIMELINE_COLUMN_WIDTH - 1))).dim(),
+            ];
+            let on_this_day = timeline.iter().enumerate().filter(|&(_, &(day, _))| match date {
+                Some(date) => day == (date - today).num_days(),
+                None => day < 0,
+            });
+            for (position, &(_, i)) in on_this_day {
+                let mut style = match date {
+                    Some(_) => Style::new(),
+                    None => Style::new().red(),
+                };
+                if position == selected {
+                    style = style.reversed();
+                }
+                lines.push(Line::styled(self.list.items[i].title.clone(), style));
             }
-        } else {
-            " Nothing selected... ".to_string()
+            let column = Rect { width: area.width.saturating_sub(1), ..area };
+            Paragraph::new(lines).render(column, buf);
+        }
+    }
+
+    pub fn render_gantt(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some((offset, selected)) = self.gantt_state() else {
+            return;
         };
 
-        let info = if let Some(i) = self.list.state.selected() {
-            &self.list.items[i].info
-        } else {
-            ""
+        let title = Line::from(" Gantt ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "H/L".blue().bold(),
+            "] Scroll".into(),
+            " [".into(),
+            "J/K".blue().bold(),
+            "] Select".into(),
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Jump".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+        let inner = Block::inner(&block, area);
+        block.render(area, buf);
+
+        let rows = self.list.gantt_rows();
+        let Some((first, _)) = self.list.gantt_range() else {
+            Line::raw("No tasks have a start or due date, :start sets when one starts")
+                .render(inner, buf);
+            return;
+        };
+
+        let [titles_area, chart_area] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Fill(1)])
+                .spacing(1)
+                .areas(inner);
+        let days = usize::from(chart_area.width / GANTT_DAY_WIDTH).max(1);
+        self.gantt_days = days;
+        let dates: Vec<NaiveDate> =
+            (offset..offset + days).map(|day| first + chrono::Days::new(day as u64)).collect();
+        let today = Local::now().date_naive();
+
+        // The month over its first day and the chart's first column, the day under it
+        let mut months = vec![];
+        let mut day_numbers = vec![];
+        for (column, date) in dates.iter().enumerate() {
+            let month = match column == 0 || date.day() == 1 {
+                true => date.format("%b").to_string(),
+                false => String::new(),
+            };
+            months.push(Span::from(format!("{month:<3}")).bold());
+            let day = Span::from(format!("{:>2} ", date.day()));
+            day_numbers.push(match *date == today {
+                true => day.yellow().bold(),
+                false => day.dim(),
+            });
+        }
+
+        let visible_rows = usize::from(inner.height.saturating_sub(2)).max(1);
+        let row_offset = (selected + 1).saturating_sub(visible_rows);
+        let mut titles = vec![Line::default(), Line::default()];
+        let mut bars = vec![Line::from(months), Line::from(day_numbers)];
+        for (row, &(i, start, end)) in rows.iter().enumerate().skip(row_offset).take(visible_rows)
+        {
+            let task = &self.list.items[i];
+            let color = match task.mode {
+                Status::Upcoming => Color::Blue,
+                Status::Active => Color::Yellow,
+                Status::Completed => Color::Green,
+            };
+            let cells = dates.iter().map(|&date| match date {
+                _ if (start..=end).contains(&date) => Span::from("███").fg(color),
+                _ if date == today => Span::from(" ┆ ").dim(),
+                _ => Span::from("   "),
+            });
+            bars.push(Line::from(cells.collect::<Vec<_>>()));
+            let title = Line::from(task.title.as_str());
+            titles.push(match row == selected {
+                true => title.reversed(),
+                false => title,
+            });
+        }
+        Paragraph::new(titles).render(titles_area, buf);
+        Paragraph::new(bars).render(chart_area, buf);
+    }
+
+    /// Drawn over the top right corner on a dithered background, so what's underneath
+    /// still shows through
+    #[cfg(debug_assertions)]
+    pub fn render_debug_overlay(&self, area: Rect, buf: &mut Buffer) {
+        let lines = [
+            format!("Mode: {}", self.mode_name()),
+            format!("Items: {}", self.list.items.len()),
+            format!("Selected: {:?}", self.list.state.selected()),
+            format!("Dirty: {}", self.is_dirty()),
+            format!("Last render: {:.2}ms", self.last_render.as_secs_f64() * 1000.0),
+        ];
+        let width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16 + 2;
+        let overlay = Rect {
+            x: area.right().saturating_sub(width + 1),
+            y: area.y + 1,
+            width,
+            height: lines.len() as u16,
+        }
+        .intersection(area);
+
+        for position in overlay.positions() {
+            if (position.x + position.y) % 2 == 0 {
+                buf[position].set_symbol("░").set_style(Style::new().dim());
+            }
+        }
+        for (line, row) in lines.iter().zip(overlay.rows()) {
+            let text_area = Rect { x: row.x + 1, width: line.width() as u16, ..row };
+            Line::from(line.as_str()).yellow().render(text_area.intersection(row), buf);
+        }
+    }
+
+    pub fn render_forecast(&self, area: Rect, buf: &mut Buffer) {
+        let Some((selected_column, selected_row)) = self.forecast_cell() else {
+            return;
         };
 
-        let task_mode = if let Some(i) = self.list.state.selected() {
-            match self.list.items[i].mode {
-                Status::Upcoming => "> Status - Upcoming ",
-                Status::Active => "> Status - Active ",
-                Status::Completed => "> Status - Completed ",
+        let title = Line::from(" Forecast ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "HJKL".blue().bold(),
+            "] Move".into(),
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Jump".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let today = Local::now().date_naive();
+        let forecast = self.forecast(today);
+        let header = Row::new((0..=FORECAST_DAYS).map(|column| match column {
+            0 => Cell::from("Past Due".red().bold()),
+            _ => {
+                let date = today + chrono::Days::new(column as u64 - 1);
+                Cell::from(date.format("%a %d").to_string().bold())
             }
+        }));
+
+        let height = forecast.iter().map(Vec::len).max().unwrap_or(0);
+        let rows = (0..height).map(|row| {
+            Row::new(forecast.iter().enumerate().map(|(column, tasks)| {
+                let Some(&i) = tasks.get(row) else {
+                    return Cell::default();
+                };
+                let mut style = match column {
+                    0 => Style::new().red(),
+                    _ => Style::new(),
+                };
+                if (column, row) == (selected_column, selected_row) {
+                    style = style.reversed();
+                }
+                Cell::from(self.list.items[i].title.clone()).style(style)
+            }))
+        });
+
+        // An empty column still shows where the cursor is
+        let mut table_state = TableState::default().with_selected_column(Some(selected_column));
+        let table = Table::new(rows, vec![Constraint::Fill(1); FORECAST_DAYS + 1])
+            .header(header.bottom_margin(1))
+            .column_highlight_style(Style::new().underlined())
+            .column_spacing(1)
+            .block(block);
+        StatefulWidget::render(table, area, buf, &mut table_state);
+    }
+
+    pub fn render_logs_mode(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = Line::from(" Logs ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "J/K".blue().bold(),
+            "] Scroll".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let lines: Vec<Line> = if self.log_buffer.is_empty() {
+            vec![Line::from("Nothing logged yet").dim()]
         } else {
-            ""
+            self.log_buffer.iter().map(|message| Line::from(message.as_str())).collect()
         };
 
-        lines.push(Line::from(task));
-        lines.push(Line::from(info));
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .scroll((self.log_scroll, 0))
+            .render(area, buf);
+    }
 
-        // We show the list item's info under the list in this paragraph
-        let block = Block::new()
-            .title(Line::from(task_mode).bold())
-            .borders(Borders::TOP)
-            .border_set(border::LIGHT_TRIPLE_DASHED)
-            .padding(Padding::horizontal(1));
+    pub fn render_integrity_report(&mut self, area: Rect, buf: &mut Buffer) {
+        let instructions = Line::from(vec![" [".into(), "Esc".blue().bold(), "] Close ".into()]);
+        let block = Block::bordered()
+            .title(Line::from(" Integrity Check ".bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded)
+            .red();
 
-        // We can now render the item info
+        let lines: Vec<Line> = self
+            .integrity_errors
+            .iter()
+            .map(|error| Line::from(format!("⚠ {}", self.describe_integrity_error(error))).reset())
+            .collect();
+
+        let height = lines.len() as u16 + 4;
+        let popup = area.centered(Constraint::Percentage(70), Constraint::Length(height));
+        Clear.render(popup, buf);
         Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
             .block(block)
+            .render(popup, buf);
+    }
+
+    pub fn render_welcome_back(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" Welcome back ".bold()).centered())
+            .title_bottom(Line::from(" Press any key to continue ".italic()).centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let mut lines = vec![Line::from("Changed since your last session:".bold()), Line::default()];
+        lines.extend(
+            self.changed_since_last_session
+                .iter()
+                .map(|title| Line::from(format!("• {title}"))),
+        );
+
+        let height = lines.len() as u16 + 4;
+        let popup = area.centered(Constraint::Percentage(60), Constraint::Length(height));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
             .wrap(Wrap { trim: false })
-            .render(area, buf);
+            .block(block)
+            .render(popup, buf);
+    }
+
+    pub fn render_focus_mode(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(task) = self.list.state.selected().and_then(|i| self.list.items.get(i)) else {
+            return;
+        };
+
+        // Grey out the list behind so only the focused task stands out
+        buf.set_style(area, Style::new().fg(Color::DarkGray).remove_modifier(Modifier::BOLD));
+
+        // Alternate the border about once a second while focused
+        let border_set = match (self.ticks() / 4) % 2 {
+            0 => border::DOUBLE,
+            _ => border::ROUNDED,
+        };
+        let instructions = Line::from(vec![" [".into(), "Esc".blue().bold(), "] Back ".into()]);
+        let block = Block::bordered()
+            .title(Line::from(format!(" {} ", task.title).bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_set(border_set)
+            .reset();
+
+        let popup = area.centered(Constraint::Percentage(80), Constraint::Percentage(80));
+        Clear.render(popup, buf);
+        Paragraph::new(info_lines(&task.info))
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup, buf);
+    }
+
+    pub fn render_about(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(" About ".bold()).centered())
+            .title_bottom(Line::from(" Press any key to close ".italic()).centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let mut lines = vec![
+            Line::from(format!("Ratatodo {}", env!("CARGO_PKG_VERSION")).bold()).centered(),
+            Line::from(env!("CARGO_PKG_DESCRIPTION")).centered(),
+            Line::default(),
+        ];
+        // Cargo leaves these empty when the manifest doesn't set them, and joins authors with ':'
+        for (label, value) in [
+            ("Authors: ", env!("CARGO_PKG_AUTHORS").replace(':', ", ")),
+            ("Repository: ", env!("CARGO_PKG_REPOSITORY").to_string()),
+        ] {
+            if !value.is_empty() {
+                lines.push(Line::from(vec![label.bold(), value.into()]));
+            }
+        }
+
+        let height = lines.len() as u16 + 4;
+        let popup = area.centered(Constraint::Percentage(60), Constraint::Length(height));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup, buf);
+    }
+
+    pub fn render_column_editor(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some((columns, selected)) = self.column_editor() else {
+            return;
+        };
+
+        let instructions = Line::from(vec![
+            " [".into(),
+            "Space".blue().bold(),
+            "] Show/Hide".into(),
+            " [".into(),
+            "Shift+J/K".blue().bold(),
+            "] Move".into(),
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Save".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Cancel ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(Line::from(" Columns ".bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let items: Vec<ListItem> = columns
+            .iter()
+            .map(|(column, shown)| {
+                let check = if *shown { "[x]" } else { "[ ]" };
+                ListItem::new(format!("{check} {}", column.name()))
+            })
+            .collect();
+
+        let height = (items.len() as u16 + 4).min(area.height);
+        let popup = area.centered(Constraint::Percentage(60), Constraint::Length(height));
+        Clear.render(popup, buf);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(selected));
+        StatefulWidget::render(list, popup, buf, &mut state);
+    }
+
+    pub fn render_recent_files(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(selected) = self.recent_files_selection() else {
+            return;
+        };
+
+        let instructions = Line::from(vec![
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Open".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Close ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(Line::from(" Recent files ".bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let current = self.tasks_path();
+        let items: Vec<ListItem> = self
+            .state()
+            .recent_files
+            .iter()
+            .map(|path| {
+                let mut line = Line::from(path.display().to_string());
+                if path == current {
+                    line.push_span(" (open)".dim());
+                }
+                ListItem::new(line)
+            })
+            .collect();
+
+        let height = (items.len() as u16 + 4).min(area.height);
+        let popup = area.centered(Constraint::Percentage(70), Constraint::Length(height));
+        Clear.render(popup, buf);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(selected));
+        StatefulWidget::render(list, popup, buf, &mut state);
+    }
+
+    pub fn render_archive(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(selected) = self.archive_selection() else {
+            return;
+        };
+
+        let instructions = Line::from(vec![
+            " [".into(),
+            "R".blue().bold(),
+            "]estore".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(Line::from(" Archive ".bold()))
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let columns = &self.config().visible_columns;
+        let items: Vec<ListItem> = self
+            .list
+            .items
+            .iter()
+            .filter(|task| task.is_archived)
+            .map(|task| ListItem::new(task.to_line(columns)))
+            .collect();
+        if items.is_empty() {
+            Paragraph::new(Line::from("Nothing archived yet, z archives the selected task").dim())
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(selected));
+        StatefulWidget::render(list, area, buf, &mut state);
+    }
+
+    pub fn render_list_picker(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(selected) = self.list_picker_selection() else {
+            return;
+        };
+
+        let instructions = Line::from(vec![
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Switch".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Close ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(Line::from(" Tasks files ".bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let current = self.tasks_path();
+        let items: Vec<ListItem> = self
+            .config()
+            .task_files
+            .iter()
+            .map(|path| match path == current {
+                true => ListItem::new(Line::from(vec![
+                    Span::from(path.display().to_string()).green(),
+                    " (open)".dim(),
+                ])),
+                false => ListItem::new(path.display().to_string()),
+            })
+            .collect();
+
+        let height = (items.len() as u16 + 4).min(area.height);
+        let popup = area.centered(Constraint::Percentage(70), Constraint::Length(height));
+        Clear.render(popup, buf);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(selected));
+        StatefulWidget::render(list, popup, buf, &mut state);
+    }
+
+    pub fn render_theme_picker(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(selected) = self.theme_picker_selection() else {
+            return;
+        };
+
+        let instructions = Line::from(vec![
+            " [".into(),
+            "Enter".blue().bold(),
+            "] Use".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Cancel ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(Line::from(" Themes ".bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let current = self.config().theme;
+        let items: Vec<ListItem> = Theme::ALL
+            .iter()
+            .map(|&theme| match theme == current {
+                true => ListItem::new(Line::from(vec![
+                    Span::from(theme.name()).green(),
+                    " (current)".dim(),
+                ])),
+                false => ListItem::new(theme.name()),
+            })
+            .collect();
+
+        // Small and off to the side, so the tasks can be seen in the theme being tried
+        let height = (items.len() as u16 + 4).min(area.height);
+        let [_, column, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(32),
+            Constraint::Length(2),
+        ])
+        .areas(area);
+        let popup = column.centered_vertically(Constraint::Length(height));
+        Clear.render(popup, buf);
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(selected));
+        StatefulWidget::render(list, popup, buf, &mut state);
+    }
+
+    pub fn render_tag_manager(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(selected) = self.tag_manager_selection() else {
+            return;
+        };
+
+        let mut instructions = Line::default();
+        for (key, action) in [("R", "Rename"), ("M", "Merge"), ("D", "Delete"), ("Esc", "Close")] {
+            instructions.push_span(" [");
+            instructions.push_span(key.blue().bold());
+            instructions.push_span(format!("] {action}"));
+        }
+        instructions.push_span(" ");
+        let block = Block::bordered()
+            .title(Line::from(" Tags ".bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let items: Vec<ListItem> = self
+            .list
+            .tag_counts()
+            .into_iter()
+            .map(|(tag, count)| {
+                let tasks = if count == 1 { "task" } else { "tasks" };
+                ListItem::new(Line::from(vec![
+                    format!("#{tag}").into(),
+                    format!("  {count} {tasks}").dim(),
+                ]))
+            })
+            .collect();
+
+        let height = (items.len().max(1) as u16 + 4).min(area.height);
+        let popup = area.centered(Constraint::Percentage(70), Constraint::Length(height));
+        Clear.render(popup, buf);
+        if items.is_empty() {
+            let inner = block.inner(popup);
+            block.render(popup, buf);
+            Line::raw("No tags yet, add one to a title as #tag").render(inner, buf);
+            return;
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(HighlightSpacing::Always);
+        let mut state = ListState::default().with_selected(Some(selected));
+        StatefulWidget::render(list, popup, buf, &mut state);
+    }
+
+    pub fn render_diff_import(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some((new_tasks, updated_tasks)) = self.pending_import() else {
+            return;
+        };
+
+        let instructions = Line::from(vec![
+            " [".into(),
+            "Y".blue().bold(),
+            "] Import".into(),
+            " [".into(),
+            "N".blue().bold(),
+            "] Cancel ".into(),
+        ]);
+        let block = Block::bordered()
+            .title(Line::from(" Import ".bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let mut lines: Vec<Line> = new_tasks
+            .iter()
+            .map(|task| Line::from(format!("+ {}", task.title)).green())
+            .collect();
+        lines.extend(updated_tasks.iter().map(|(existing, incoming)| {
+            let line = if existing.title == incoming.title {
+                format!("~ {}", incoming.title)
+            } else {
+                format!("~ {} → {}", existing.title, incoming.title)
+            };
+            Line::from(line).yellow()
+        }));
+
+        let height = (lines.len() as u16 + 4).min(area.height);
+        let popup = area.centered(Constraint::Percentage(70), Constraint::Length(height));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup, buf);
+    }
+
+    pub fn render_import_wizard(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(step) = self.import_wizard() else {
+            return;
+        };
+
+        let key = |key: &'static str, action: &'static str| -> [Span<'static>; 3] {
+            [" [".into(), key.blue().bold(), format!("] {action}").into()]
+        };
+        let choice = |name: String, selected: bool| match selected {
+            true => Line::from(format!("> {name}")).style(SELECTED_STYLE),
+            false => Line::from(format!("  {name}")),
+        };
+        let (lines, keys): (Vec<Line>, Vec<[Span; 3]>) = match step {
+            WizardStep::Format { selected } => (
+                std::iter::once(Line::from("What's the file to import?"))
+                    .chain(std::iter::once(Line::default()))
+                    .chain(ImportFormat::ALL.iter().enumerate().map(|(i, format)| {
+                        choice(format.name().to_string(), i == *selected)
+                    }))
+                    .collect(),
+                vec![key("Enter", "Next"), key("Esc", "Cancel ")],
+            ),
+            WizardStep::Path { format, field } => {
+                let (mut field_lines, _) = text_field_lines(field, Style::new().reversed());
+                let mut lines = vec![
+                    Line::from(format!("Where's the {} file?", format.name())),
+                    Line::default(),
+                ];
+                lines.append(&mut field_lines);
+                (
+                    lines,
+                    vec![key("Tab", "Complete"), key("Enter", "Read"), key("Esc", "Back ")],
+                )
+            }
+            WizardStep::Preview { path, tasks, .. } => {
+                let mut lines = vec![
+                    Line::from(format!("{} tasks in {}", tasks.len(), path.display())),
+                    Line::default(),
+                ];
+                lines.extend(tasks.iter().take(import::PREVIEW_LEN).map(|task| {
+                    let mut line = format!("{} {}", task.mode.marker(), task.title);
+                    if let Some(due_date) = task.due_date {
+                        line.push_str(&format!(" (due {})", due_date.format(DUE_DATE_FORMAT)));
+                    }
+                    Line::from(line)
+                }));
+                if let Some(more) = tasks.len().checked_sub(import::PREVIEW_LEN + 1) {
+                    lines.push(Line::from(format!("…and {} more", more + 1)).dim());
+                }
+                (lines, vec![key("Enter", "Next"), key("Esc", "Back ")])
+            }
+            WizardStep::Strategy { tasks, selected, .. } => {
+                let mut lines = vec![
+                    Line::from(format!("How should the {} tasks go in?", tasks.len())),
+                    Line::default(),
+                ];
+                lines.extend(MergeStrategy::ALL.iter().enumerate().map(|(i, strategy)| {
+                    choice(strategy.name().to_string(), i == *selected)
+                }));
+                lines.push(Line::default());
+                lines.push(Line::from(MergeStrategy::ALL[*selected].description()).dim());
+                (lines, vec![key("Enter", "Import"), key("Esc", "Back ")])
+            }
+        };
+
+        let instructions = Line::from(keys.into_iter().flatten().collect::<Vec<_>>());
+        let block = Block::bordered()
+            .title(Line::from(format!(" Import (step {} of 4) ", step.number()).bold()).centered())
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let height = (lines.len() as u16 + 4).min(area.height);
+        let popup = area.centered(Constraint::Percentage(70), Constraint::Length(height));
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup, buf);
+    }
+
+    pub fn render_notification(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(message) = self.notification() else {
+            return;
+        };
+
+        let block = Block::bordered()
+            .padding(Padding::horizontal(1))
+            .border_type(BorderType::Rounded);
+        let width = (message.chars().count() as u16 + 4).min(area.width);
+        // Top right, just inside the main border, where it covers as little as possible
+        let [_, row] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(3)]).areas(area);
+        let [_, popup, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(width),
+            Constraint::Length(2),
+        ])
+        .areas(row);
+        Clear.render(popup, buf);
+        Paragraph::new(message).block(block).render(popup, buf);
+    }
+
+    pub fn render_help_mode(&mut self, area: Rect, buf: &mut Buffer) {
+        Line::raw("Help Screen").render(area, buf);
+    }
+
+    pub fn render_task_detail_mode(&mut self, area: Rect, buf: &mut Buffer) {
+        self.load_selected_notes();
+        let title = Line::from(" Ratatodo ".bold());
+        let instructions = Line::from(vec![
+            " [".into(),
+            "J/K".blue().bold(),
+            "] Next/Previous".into(),
+            " [".into(),
+            "E".blue().bold(),
+            "]dit".into(),
+            " [".into(),
+            "Esc".blue().bold(),
+            "] Back ".into(),
+        ]);
+
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(instructions.centered())
+            .padding(Padding::uniform(1))
+            .border_type(BorderType::Rounded);
+
+        let layout = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .split(Block::inner(&block, area));
+
+        block.render(area, buf);
+
+        let Some(task) = self.list.state.selected().and_then(|i| self.list.items.get(i)) else {
+            Line::raw(" Nothing selected... ").render(layout[0], buf);
+            return;
+        };
+
+        let status = match task.mode {
+            Status::Upcoming => "Upcoming",
+            Status::Active => "Active",
+            Status::Completed => "Completed",
+        };
+
+        Paragraph::new(Line::from(task.title.clone().bold()))
+            .wrap(Wrap { trim: true })
+            .block(Block::new().borders(Borders::BOTTOM))
+            .render(layout[0], buf);
+
+        let mut status_line = Line::from(vec!["Status: ".bold(), status.into()]);
+        if let Some(modified) = task.last_modified {
+            let modified = modified.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+            status_line.push_span("   Modified: ".bold());
+            status_line.push_span(modified.to_string());
+        }
+        status_line.render(layout[1], buf);
+
+        if let Some(due_date) = task.due_date {
+            Line::from(vec!["Due: ".bold(), due_date.format(DUE_DATE_FORMAT).to_string().into()])
+                .render(layout[2], buf);
+        }
+
+        if let Some(blocker) = self.list.blocking_task(task) {
+            Line::from(vec!["Blocked by: ".bold(), blocker.title.clone().into()])
+                .render(layout[3], buf);
+        }
+
+        if let Some(url) = &task.url {
+            let [label, link] =
+                Layout::horizontal([Constraint::Length(5), Constraint::Fill(1)]).areas(layout[4]);
+            "URL: ".bold().render(label, buf);
+            Hyperlink::new(url).render(link, buf);
+        }
+
+        Paragraph::new(info_lines(&task.info))
+            .wrap(Wrap { trim: false })
+            .block(Block::new().title(Line::from(" Details ").bold()).borders(Borders::TOP))
+            .render(layout[5], buf);
+    }
+
+    fn render_command_line(&self, area: Rect, buf: &mut Buffer) {
+        let prompt = self.prompt();
+        let line = if let Some((symbol, input)) = prompt {
+            let mut line = Line::from(vec![
+                Span::raw(format!("{symbol}{input}")),
+                Span::styled(" ", Style::reversed(Style::default())),
+            ]);
+            // Prompts that reject their input leave it open and explain why
+            if let Some(toast) = &self.toast {
+                line.push_span(format!("  {toast}").italic().dim());
+            }
+            line
+        } else if let Some(toast) = &self.toast {
+            Line::from(format!(" {toast} ").italic())
+        } else {
+            Line::default()
+        };
+
+        let area = Block::new().padding(Padding::horizontal(1)).inner(area);
+        let position = self.position_indicator();
+        let [area, position_area] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(position.width() as u16),
+        ])
+        .spacing(1)
+        .areas(area);
+
+        Paragraph::new(line).render(area, buf);
+        position.render(position_area, buf);
+
+        if self.is_auto_save_pending() && prompt.is_none() {
+            Line::from(format!("{} Unsaved changes", self.spinner()).dim())
+                .right_aligned()
+                .render(area, buf);
+        }
+    }
+
+    /// `status_bar_format` filled in, e.g. `3/12`. Dimmed while nothing is selected, and left
+    /// out for an empty list
+    fn position_indicator(&self) -> Line<'static> {
+        let text = self.status_bar_text();
+        let mut line = match self.list.state.selected() {
+            _ if self.list.items.is_empty() => Line::default(),
+            Some(_) => Line::from(text),
+            None => Line::from(text.dim()),
+        };
+        if self.read_only {
+            line.spans.insert(0, "[READ ONLY] ".red().bold());
+        }
+        line
+    }
+
+    /// Only the tasks that fit are handed to the List, so long lists don't build thousands of
+    /// items every frame. They start at the list's scroll offset, moved just far enough to
+    /// keep the selection in view, so it scrolls a row at a time
+    fn render_list_with_virtual_scroll(&mut self, area: Rect, buf: &mut Buffer) {
+        let density = self.config().render_density;
+        let lines_per_task = if density { 2 } else { 1 };
+        let visible = (usize::from(area.height) / lines_per_task).max(1);
+        self.page_size = visible;
+        // The List used to clamp the selection but now it only sees what fits, and archived
+        // tasks and the subtasks of collapsed ones aren't shown at all
+        self.list.snap_selection();
+
+        let rows = self.list.shown_rows();
+        let last_start = rows.len().saturating_sub(visible);
+        let mut offset = self.list.state.offset().min(last_start);
+        let selected = self.list.state.selected();
+        if let Some(row) = rows.iter().position(|&(_, i)| Some(i) == selected) {
+            offset = offset.clamp((row + 1).saturating_sub(visible), row);
+        }
+        *self.list.state.offset_mut() = offset;
+        let window: Vec<(usize, usize, &Task)> = rows
+            .iter()
+            .skip(offset)
+            .take(visible)
+            .map(|&(depth, i)| (depth, i, &self.list.items[i]))
+            .collect();
+        let editing = self.list.state.selected().filter(|_| self.is_editing_inline());
+        let nudged = self.nudged_task();
+        let columns: &[Column] = &self.config().visible_columns;
+        // The highlight symbol takes up the first column, when there's room kept for it
+        let cursor_width = match self.config().highlight_spacing {
+            HighlightSpacing::Always => 1,
+            HighlightSpacing::WhenSelected if self.list.state.selected().is_some() => 1,
+            HighlightSpacing::WhenSelected | HighlightSpacing::Never => 0,
+        };
+        let progress_bars = self.config().progress_bars_in_list;
+        let wrap_width = self
+            .config()
+            .wrap_text_in_list
+            .then(|| usize::from(area.width.saturating_sub(cursor_width)));
+        let items: Vec<ListItem> = window
+            .iter()
+            .map(|&(depth, i, task)| {
+                // Subtasks sit under their parent, two columns further in per level
+                let indent = "  ".repeat(depth);
+                let wrap_width = wrap_width.map(|width| width.saturating_sub(indent.len()));
+                let mut lines = match self.list.blocking_task(task) {
+                    _ if editing == Some(i) => vec![Line::from(vec![
+                        Span::raw(format!(" ✎ {}", self.inline_title_field)),
+                        Span::styled(" ", Style::reversed(Style::default())),
+                    ])],
+                    Some(_) => {
+                        wrap_list_line(Line::raw(task.list_text(columns, "🔒")).dim(), wrap_width)
+                    }
+                    None => wrap_list_line(task.to_line(columns), wrap_width),
+                };
+                if let Some(query) = self.search_query() {
+                    lines = lines.into_iter().map(|line| highlight_match(line, query)).collect();
+                }
+                if let Some((done, total)) =
+                    self.list.subtask_progress(task).filter(|_| progress_bars)
+                    && let Some(line) = lines.last_mut()
+                {
+                    line.push_span(format!(" {}", progress_bar(done, total)).dim());
+                }
+                if self.list.collapsed.contains(&task.id)
+                    && self.list.subtask_progress(task).is_some()
+                    && let Some(line) = lines.first_mut()
+                {
+                    line.push_span(" ▸".dim());
+                }
+                if depth > 0 {
+                    for line in &mut lines {
+                        line.spans.insert(0, Span::raw(indent.clone()));
+                    }
+                }
+                if density {
+                    lines.push(render_summary_line(task));
+                }
+                if nudged == Some(task.id) {
+                    return ListItem::new(lines).bg(NUDGE_BG_COLOR);
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(self.config().highlight_spacing.clone());
+
+        let mut page_state = ListState::default()
+            .with_selected(window.iter().position(|&(_, i, _)| Some(i) == selected));
+        StatefulWidget::render(list, area, buf, &mut page_state);
+    }
+
+    /// The list as a section per tag, headed by its name. The selected task is bold wherever
+    /// it appears, and marked and scrolled to where it first does
+    pub fn render_grouped_by_tag(&mut self, area: Rect, buf: &mut Buffer) {
+        // The list is drawn through a state of its own, so nothing else keeps the selection
+        // on a task that's still there
+        self.list.snap_selection();
+        let selected_id = self
+            .list
+            .state
+            .selected()
+            .and_then(|i| self.list.items.get(i))
+            .map(|task| task.id);
+        let columns: &[Column] = &self.config().visible_columns;
+
+        let mut items: Vec<ListItem> = vec![];
+        let mut first_selected_row = None;
+        for (tag, tasks) in self.list.group_by_tag() {
+            let heading = if tag.is_empty() { "Untagged".to_string() } else { format!("#{tag}") };
+            items.push(ListItem::new(Line::from(heading).bold().underlined()));
+            for task in tasks {
+                let mut line = task.to_line(columns);
+                if Some(task.id) == selected_id {
+                    first_selected_row.get_or_insert(items.len());
+                    line = line.patch_style(SELECTED_STYLE);
+                }
+                items.push(ListItem::new(line));
+            }
+        }
+
+        let list = List::new(items)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">")
+            .highlight_spacing(self.config().highlight_spacing.clone());
+        let mut state = ListState::default().with_selected(first_selected_row);
+        StatefulWidget::render(list, area, buf, &mut state);
+    }
+
+    /// The selected task's details a tab at a time, the overview being what
+    /// `render_selected_item` shows
+    fn render_selected_item_v2(&self, area: Rect, buf: &mut Buffer) {
+        let task = self.list.state.selected().and_then(|i| self.list.items.get(i));
+        let lines = match (task, self.detail_tab) {
+            (_, DetailTab::Overview) => return self.render_selected_item(area, buf),
+            (None, _) => vec![Line::from(" Nothing selected... ")],
+            (Some(task), DetailTab::Notes) if task.info.is_empty() => {
+                vec![Line::from("No notes").dim()]
+            }
+            (Some(task), DetailTab::Notes) => task.info.lines().map(Line::from).collect(),
+            (Some(task), DetailTab::History) => match self.status_changes.get(&task.id) {
+                Some(changes) => changes
+                    .iter()
+                    .rev()
+                    .map(|(time, from, to)| {
+                        Line::from(vec![
+                            Span::from(time.format("%H:%M:%S ").to_string()).dim(),
+                            Span::from(format!("{from:?} → {to:?}")),
+                        ])
+                    })
+                    .collect(),
+                None => vec![Line::from("No status changes this session").dim()],
+            },
+            (Some(task), DetailTab::Metadata) => self.metadata_lines(task),
+        };
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(self.detail_block())
+            .render(area, buf);
+    }
+
+    fn metadata_lines(&self, task: &Task) -> Vec<Line<'static>> {
+        let field = |name: &str, value: String| {
+            Line::from(vec![Span::from(format!("{name}: ")).dim(), Span::from(value)])
+        };
+        let mut lines = vec![field("Id", task.id.to_string())];
+        if let Some(modified) = task.last_modified {
+            let modified = modified.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S");
+            lines.push(field("Last modified", modified.to_string()));
+        }
+        if let Some(id) = task.depends_on {
+            let title = self.list.find_by_id(id).map_or("missing task", |task| &task.title);
+            lines.push(field("Depends on", format!("{title} ({id})")));
+        }
+        if let Some(id) = task.parent {
+            let title = self.list.find_by_id(id).map_or("missing task", |task| &task.title);
+            lines.push(field("Subtask of", format!("{title} ({id})")));
+        }
+        if let Some(url) = &task.url {
+            lines.push(field("URL", url.clone()));
+        }
+        lines.push(field("Words", task.word_count().to_string()));
+        lines
+    }
+
+    /// The rule above the details, with the task's status, the tabs and its due date
+    fn detail_block(&self) -> Block<'static> {
+        let task = self.list.state.selected().and_then(|i| self.list.items.get(i));
+        let task_mode = match task.map(|task| task.mode) {
+            Some(Status::Upcoming) => "> Status - Upcoming ",
+            Some(Status::Active) => "> Status - Active ",
+            Some(Status::Completed) => "> Status - Completed ",
+            None => "",
+        };
+        let due_date = task
+            .and_then(|task| task.due_date)
+            .map(|date| format!(" Due {} ", date.format(DUE_DATE_FORMAT)));
+
+        let mut tabs = vec![];
+        for tab in DetailTab::ALL {
+            let name = format!(" {} ", tab.name());
+            tabs.push(match tab == self.detail_tab {
+                true => Span::from(name).reversed(),
+                false => Span::from(name).dim(),
+            });
+        }
+
+        Block::new()
+            .title(Line::from(task_mode).bold())
+            .title(Line::from(tabs).centered())
+            .title(Line::from(due_date.unwrap_or_default()).right_aligned())
+            .borders(Borders::TOP)
+            .border_set(border::LIGHT_TRIPLE_DASHED)
+            .padding(Padding::horizontal(1))
+    }
+
+    fn render_selected_item(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line<'_>> = vec![];
+        let selected = self.list.state.selected().and_then(|i| self.list.items.get(i));
+        let task = match selected {
+            Some(task) => format!("{} ", task.title),
+            None => " Nothing selected... ".to_string(),
+        };
+        let info = selected.map_or("", |task| task.info.as_str());
+
+        lines.push(Line::from(task));
+        lines.extend(info_lines(info));
+        if let Some(blocker) = selected.and_then(|task| self.list.blocking_task(task)) {
+            lines.push(Line::from(format!("Blocked by: {}", blocker.title)).dim());
+        }
+        if let Some(task) = selected {
+            lines.push(Line::from(format!("Words: {}", task.word_count())).dim());
+        }
+
+        // We show the list item's info under the list in this paragraph
+        let block = self.detail_block();
+
+        let url = selected.and_then(|task| task.url.as_deref());
+        let [info_area, url_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+        block.render(area, buf);
+
+        // We can now render the item info
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .render(info_area, buf);
+
+        if let Some(url) = url {
+            Hyperlink::new(url).render(url_area, buf);
+        }
+    }
+}
+
+/// The field's text with the character under the cursor drawn in `cursor_style`, or a space
+/// after the text when the cursor is at the end
+fn text_field_line(field: &HistoryTextField, cursor_style: Style) -> Line<'_> {
+    let (before, after) = field.split_at_cursor();
+    let mut chars = after.chars();
+    let under_cursor = chars.next().map_or(" ".to_string(), String::from);
+    Line::from(vec![
+        Span::raw(before),
+        Span::styled(under_cursor, cursor_style),
+        Span::raw(chars.as_str()),
+    ])
+}
+
+/// Like `text_field_line` for a field holding several lines, with the row the cursor is on
+fn text_field_lines(field: &HistoryTextField, cursor_style: Style) -> (Vec<Line<'_>>, usize) {
+    let (before, after) = field.split_at_cursor();
+    let mut lines: Vec<Line> = before.split('\n').map(Line::from).collect();
+    let cursor_row = lines.len() - 1;
+    let (rest_of_row, later_rows) = after.split_once('\n').unwrap_or((after, ""));
+    let mut chars = rest_of_row.chars();
+    let under_cursor = chars.next().map_or(" ".to_string(), String::from);
+    let row = &mut lines[cursor_row];
+    row.push_span(Span::styled(under_cursor, cursor_style));
+    row.push_span(Span::raw(chars.as_str()));
+    if after.contains('\n') {
+        lines.extend(later_rows.split('\n').map(Line::from));
+    }
+    (lines, cursor_row)
+}
+
+/// Five cells showing `done` out of `total`, filled an eighth of a cell at a time
+fn progress_bar(done: usize, total: usize) -> String {
+    const CELLS: usize = 5;
+    const PARTIAL: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = (done * CELLS * 8 + total / 2) / total.max(1);
+    (0..CELLS)
+        .map(|cell| match eighths.saturating_sub(cell * 8) {
+            0 => '░',
+            filled @ 1..8 => PARTIAL[filled - 1],
+            _ => '█',
+        })
+        .collect()
+}
+
+/// Splits a single-styled line into rows no wider than `width`, or leaves it alone when
+/// `width` is `None`. The rows go in one item so they're selected together
+fn wrap_list_line(line: Line<'static>, width: Option<usize>) -> Vec<Line<'static>> {
+    match width {
+        Some(width) => {
+            let style = line.style;
+            let rows = wrap_text(&line.to_string(), width);
+            rows.into_iter().map(|row| Line::styled(row, style)).collect()
+        }
+        None => vec![line],
+    }
+}
+
+/// Splits out the first match of `query` in `line` and gives it a yellow background. Matches
+/// ignore case like the search itself does, unless lowercasing would move the byte offsets
+fn highlight_match(line: Line<'static>, query: &str) -> Line<'static> {
+    let text = line.to_string();
+    let lowercase = text.to_lowercase();
+    let needle = query.to_lowercase();
+    let found = if lowercase.len() == text.len() {
+        lowercase.find(&needle).map(|start| start..start + needle.len())
+    } else {
+        text.find(query).map(|start| start..start + query.len())
+    };
+    let Some(matched) = found.filter(|range| text.get(range.clone()).is_some()) else {
+        return line;
+    };
+
+    let spans = vec![
+        Span::raw(text[..matched.start].to_string()),
+        Span::styled(text[matched.clone()].to_string(), Style::new().black().on_yellow()),
+        Span::raw(text[matched.end..].to_string()),
+    ];
+    Line::from(spans).style(line.style)
+}
+
+/// The start of the first line of `info`, shown under the title in the denser list layout.
+/// Always one line, even when there's no info, so every task is the same height
+fn render_summary_line(task: &Task) -> Line<'static> {
+    const PREVIEW_LEN: usize = 50;
+    let summary = task.summary_line();
+    let mut preview: String = summary.chars().take(PREVIEW_LEN).collect();
+    if summary.chars().count() > PREVIEW_LEN || task.info.trim() != summary {
+        preview.push('…');
+    }
+    Line::from(format!("   {preview}")).dim()
+}
+
+/// Greedy word wrap, indenting continuation rows to line up past the status marker. Words
+/// longer than a whole row are broken wherever they hit the edge
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    const INDENT: &str = "   ";
+    let mut rows = vec![String::new()];
+
+    for word in text.split_inclusive(' ') {
+        let row = rows.last().expect("rows starts non-empty");
+        let word_width = word.trim_end().width();
+        // Only move a word down if that makes it fit, otherwise it gets broken up anyway
+        if row.width() + word_width > width && INDENT.len() + word_width <= width {
+            rows.push(INDENT.into());
+        }
+        for c in word.chars() {
+            let row = rows.last().expect("rows starts non-empty");
+            let overflows = row.width() + c.width().unwrap_or(0) > width;
+            if c != ' ' && overflows && row.width() > INDENT.len() {
+                rows.push(INDENT.into());
+            }
+            rows.last_mut().expect("rows starts non-empty").push(c);
+        }
+    }
+
+    rows
+}
+
+/// Splits `info` into lines, syntax highlighting it when it holds a JSON object or array
+fn info_lines(info: &str) -> Vec<Line<'_>> {
+    match serde_json::from_str::<Value>(info) {
+        Ok(value) if value.is_object() || value.is_array() => {
+            let mut lines = vec![];
+            push_json_lines(&mut lines, vec![], &value, 0, "");
+            lines
+        }
+        _ => info.lines().map(Line::from).collect(),
+    }
+}
+
+/// Pretty prints `value` the way `serde_json::to_string_pretty` would, starting on a line
+/// that already holds `prefix` and ending with `suffix`
+fn push_json_lines(
+    lines: &mut Vec<Line<'static>>,
+    mut prefix: Vec<Span<'static>>,
+    value: &Value,
+    depth: usize,
+    suffix: &'static str,
+) {
+    let (open, close, children): (_, _, Vec<(Option<&String>, &Value)>) = match value {
+        Value::Object(map) if !map.is_empty() => {
+            ("{", "}", map.iter().map(|(key, value)| (Some(key), value)).collect())
+        }
+        Value::Array(values) if !values.is_empty() => {
+            ("[", "]", values.iter().map(|value| (None, value)).collect())
+        }
+        _ => {
+            prefix.push(json_scalar_span(value));
+            prefix.push(Span::raw(suffix));
+            lines.push(Line::from(prefix));
+            return;
+        }
+    };
+
+    prefix.push(Span::raw(open));
+    lines.push(Line::from(prefix));
+
+    let last = children.len() - 1;
+    for (i, (key, child)) in children.into_iter().enumerate() {
+        let mut child_prefix = vec![Span::raw("  ".repeat(depth + 1))];
+        if let Some(key) = key {
+            child_prefix.push(Span::styled(Value::from(key.as_str()).to_string(), Color::Cyan));
+            child_prefix.push(Span::raw(": "));
+        }
+        push_json_lines(lines, child_prefix, child, depth + 1, if i == last { "" } else { "," });
+    }
+
+    lines.push(Line::from(vec![
+        Span::raw("  ".repeat(depth)),
+        Span::raw(close),
+        Span::raw(suffix),
+    ]));
+}
+
+fn json_scalar_span(value: &Value) -> Span<'static> {
+    let color = match value {
+        Value::String(_) => Color::Green,
+        Value::Number(_) => Color::Yellow,
+        Value::Bool(_) => Color::Magenta,
+        _ => Color::DarkGray,
+    };
+    // Empty objects and arrays fall through to here too and print as {} and []
+    Span::styled(value.to_string(), color)
+}
+
+/// A single line of text that terminals supporting OSC 8 render as a clickable link
+struct Hyperlink<'a> {
+    url: &'a str,
+}
+
+impl<'a> Hyperlink<'a> {
+    fn new(url: &'a str) -> Self {
+        Self { url }
+    }
+}
+
+impl Widget for Hyperlink<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Line::from(self.url.underlined().cyan()).render(area, buf);
+
+        // Each cell's symbol is only as wide as its visible text, so the link is written
+        // over the already rendered text in two character chunks, each one replacing the
+        // two cells it covers
+        let chars: Vec<char> = self.url.chars().collect();
+        for (i, chunk) in chars.chunks(2).enumerate() {
+            let x = area.x + i as u16 * 2;
+            if x >= area.right() {
+                break;
+            }
+            let display: String = chunk.iter().take((area.right() - x) as usize).collect();
+            let link = format!("\x1b]8;;{}\x1b\\{display}\x1b]8;;\x1b\\", self.url);
+            buf[(x, area.y)].set_symbol(&link);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::TodoList;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{Terminal, backend::TestBackend};
+    use std::time::{Duration, Instant};
+
+    fn numbered_tasks(count: usize) -> App {
+        let mut app = App::default();
+        app.list = TodoList::from(
+            (0..count)
+                .map(|i| Task::new(Status::Upcoming, &format!("Task {i}"), ""))
+                .collect::<Vec<_>>(),
+        );
+        app
+    }
+
+    /// The list area's rows, between the header and the details
+    fn list_rows(terminal: &Terminal<TestBackend>) -> Vec<String> {
+        let buffer = terminal.backend().buffer();
+        (2..6)
+            .map(|y| {
+                let row: String = (1..buffer.area.width - 1)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deleting_the_last_task_while_grouped_by_tag() {
+        let mut app = numbered_tasks(2);
+        app.group_by_tag = true;
+        app.list.state.select(Some(1));
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+        assert_eq!(app.list.state.selected(), Some(0));
+
+        // A selection left past the end by anything else is drawn without panicking too
+        app.list.state.select(Some(5));
+        let mut terminal = Terminal::new(TestBackend::new(40, 14)).unwrap();
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert_eq!(app.list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn completing_a_subtask_fills_its_parents_bar() {
+        let parent = Task::new(Status::Active, "Plan trip", "");
+        let mut flights = Task::new(Status::Upcoming, "Flights", "");
+        let mut hotel = Task::new(Status::Upcoming, "Hotel", "");
+        flights.parent = Some(parent.id);
+        hotel.parent = Some(parent.id);
+        let mut app = App::default();
+        app.list = TodoList::from(vec![parent, flights, hotel]);
+        app.config_mut().progress_bars_in_list = true;
+        let mut terminal = Terminal::new(TestBackend::new(40, 14)).unwrap();
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert!(list_rows(&terminal)[0].ends_with(&progress_bar(0, 2)));
+
+        // The parent is still open, and that doesn't hold its subtasks back
+        app.list.state.select(Some(1));
+        for _ in 0..2 {
+            app.handle_key_events(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
+        }
+        assert_eq!(app.list.items[1].mode, Status::Completed);
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert!(list_rows(&terminal)[0].ends_with(&progress_bar(1, 2)));
+    }
+
+    #[test]
+    fn subtasks_are_nested_under_their_parent_and_collapse() {
+        let trip = Task::new(Status::Active, "Plan trip", "");
+        let laundry = Task::new(Status::Upcoming, "Laundry", "");
+        let mut flights = Task::new(Status::Upcoming, "Flights", "");
+        flights.parent = Some(trip.id);
+        let mut app = App::default();
+        app.list = TodoList::from(vec![trip, laundry, flights]);
+        app.list.state.select(Some(0));
+        let mut terminal = Terminal::new(TestBackend::new(40, 14)).unwrap();
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert_eq!(
+            list_rows(&terminal)[..3],
+            ["> ☐ Plan trip", "    _ Flights", "  _ Laundry"]
+        );
+
+        // j follows the rows, not where the tasks are stored
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+        assert_eq!(app.list.state.selected(), Some(2));
+
+        // Collapsing from a subtask is refused, and the parent hides it
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE));
+        assert_eq!(app.toast.as_deref(), Some("No subtasks to collapse"));
+        app.list.state.select(Some(0));
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE));
+        app.list.state.select(Some(2));
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert_eq!(list_rows(&terminal)[..2], ["> ☐ Plan trip ▸", "  _ Laundry"]);
+        assert_eq!(app.list.state.selected(), Some(0));
+
+        app.handle_key_events(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE));
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert_eq!(list_rows(&terminal)[1], "    _ Flights");
+    }
+
+    #[test]
+    fn scrolls_a_row_at_a_time() {
+        let mut app = numbered_tasks(5000);
+        // Four rows of list above the details
+        let mut terminal = Terminal::new(TestBackend::new(40, 14)).unwrap();
+        app.list.state.select(Some(4000));
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert_eq!(list_rows(&terminal)[3], "> _ Task 4000");
+
+        app.list.state.select(Some(4001));
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert_eq!(list_rows(&terminal)[..2], ["  _ Task 3998", "  _ Task 3999"]);
+
+        // Moving back up inside the window doesn't scroll it
+        app.list.state.select(Some(3999));
+        terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+        assert_eq!(list_rows(&terminal)[1], "> _ Task 3999");
+    }
+
+    #[test]
+    #[ignore = "a benchmark, run with `cargo test --release -- --ignored --nocapture`"]
+    fn renders_long_lists_as_fast_as_short_ones() {
+        let time_frames = |count: usize| {
+            let mut app = numbered_tasks(count);
+            let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+            let started = Instant::now();
+            for i in 0..200 {
+                app.list.state.select(Some(i * count / 200));
+                terminal.draw(|frame| frame.render_widget(&mut app, frame.area())).unwrap();
+            }
+            started.elapsed() / 200
+        };
+
+        let short = time_frames(1_000);
+        let long = time_frames(10_000);
+        println!("1000 tasks: {short:?} a frame, 10000 tasks: {long:?} a frame");
+        // Ten times the tasks costs a few passes over them, not ten times the rendering
+        assert!(long < short * 5 + Duration::from_millis(1));
+    }
+
+    #[test]
+    fn wraps_at_word_boundaries() {
+        assert_eq!(
+            wrap_text(" _ Buy milk and eggs", 12),
+            [" _ Buy milk ", "   and eggs"]
+        );
+        assert_eq!(wrap_text(" _ Short", 12), [" _ Short"]);
+    }
+
+    #[test]
+    fn breaks_words_longer_than_a_row() {
+        assert_eq!(wrap_text(" _ abcdefghij", 8), [" _ abcde", "   fghij"]);
+    }
+
+    #[test]
+    fn fills_progress_bars_by_eighths() {
+        assert_eq!(progress_bar(0, 3), "░░░░░");
+        assert_eq!(progress_bar(1, 2), "██▌░░");
+        assert_eq!(progress_bar(1, 3), "█▋░░░");
+        assert_eq!(progress_bar(3, 3), "█████");
+    }
+
+    #[test]
+    fn highlights_the_first_match_ignoring_case() {
+        let line = highlight_match(Line::raw(" _ Buy milk, more Milk"), "MILK");
+        let spans: Vec<&str> = line.spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(spans, [" _ Buy ", "milk", ", more Milk"]);
+        assert_eq!(line.spans[1].style.bg, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn leaves_lines_without_a_match_alone() {
+        let line = highlight_match(Line::raw(" _ Buy milk"), "eggs");
+        assert_eq!(line.spans.len(), 1);
     }
 }
@@ -0,0 +1,91 @@
+use uuid::Uuid;
+
+use crate::app::{App, Task};
+
+/// How many steps back `u` can go
+const UNDO_LIMIT: usize = 100;
+
+/// The inverse of something the user did, applied as-is to undo it
+#[derive(Debug, Clone)]
+pub enum UndoAction {
+    Reorder {
+        from: usize,
+        to: usize,
+    },
+    /// Puts tasks back as they were before a bulk edit, matched by id
+    Restore(Vec<Task>),
+    /// Takes tasks that were just added back out, all of a paste at once
+    Remove {
+        ids: Vec<Uuid>,
+    },
+}
+
+impl App {
+    pub fn push_undo(&mut self, action: UndoAction) {
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(action);
+    }
+
+    /// Reverts the most recent undoable change, returning a message for the toast
+    pub fn undo(&mut self) -> String {
+        let Some(action) = self.undo_stack.pop() else {
+            return "Nothing to undo".into();
+        };
+
+        match action {
+            UndoAction::Reorder { from, to } => {
+                self.list.reorder(from, to);
+                self.list.state.select(Some(to));
+            }
+            UndoAction::Restore(tasks) => {
+                for task in tasks {
+                    if let Some(current) = self.list.find_by_id_mut(task.id) {
+                        *current = task;
+                    }
+                }
+            }
+            UndoAction::Remove { ids } => {
+                for id in ids {
+                    if let Some(i) = self.list.position_of(id) {
+                        self.list.remove(i);
+                    }
+                }
+                self.list.snap_selection();
+            }
+        }
+        self.mark_dirty();
+        "Undone".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::{App, Status, TodoList};
+
+    #[test]
+    fn reorder_then_undo_restores_the_order() {
+        let mut app = App::default();
+        app.list = TodoList::from_iter([
+            (Status::Upcoming, "1", ""),
+            (Status::Upcoming, "2", ""),
+            (Status::Upcoming, "3", ""),
+            (Status::Upcoming, "4", ""),
+        ]);
+        let titles = |app: &App| -> Vec<String> {
+            app.list
+                .items
+                .iter()
+                .map(|task| task.title.clone())
+                .collect()
+        };
+
+        app.move_task(2, 0);
+        assert_eq!(titles(&app), ["3", "1", "2", "4"]);
+
+        assert_eq!(app.undo(), "Undone");
+        assert_eq!(titles(&app), ["1", "2", "3", "4"]);
+        assert_eq!(app.undo(), "Nothing to undo");
+    }
+}